@@ -0,0 +1,2 @@
+-- Drop avito_import_profiles table
+DROP TABLE IF EXISTS avito_import_profiles;
\ No newline at end of file
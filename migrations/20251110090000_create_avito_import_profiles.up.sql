@@ -0,0 +1,15 @@
+-- Create avito_import_profiles table (spreadsheet column -> Avito tag mappings)
+CREATE TABLE IF NOT EXISTS avito_import_profiles (
+    profile_id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    account_id UUID NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    category VARCHAR(255),
+    avito_slug VARCHAR(255),
+    mapping JSONB NOT NULL DEFAULT '{}'::jsonb,
+    required_tags TEXT[] NOT NULL DEFAULT '{}',
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create index for better query performance
+CREATE INDEX IF NOT EXISTS idx_avito_import_profiles_account_id ON avito_import_profiles(account_id);
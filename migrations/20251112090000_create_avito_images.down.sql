@@ -0,0 +1,4 @@
+-- Drop avito_images tables
+DROP TABLE IF EXISTS avito_ad_images;
+DROP TABLE IF EXISTS avito_image_sources;
+DROP TABLE IF EXISTS avito_images;
\ No newline at end of file
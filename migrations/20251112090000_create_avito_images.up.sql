@@ -0,0 +1,34 @@
+-- Create avito_images table (mirrored image files, one row per unique content)
+CREATE TABLE IF NOT EXISTS avito_images (
+    image_id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    content_hash VARCHAR(64) NOT NULL UNIQUE,
+    format VARCHAR(16) NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    file_name VARCHAR(255) NOT NULL,
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create avito_image_sources table (remote URL -> mirrored image, failed downloads keep the error)
+CREATE TABLE IF NOT EXISTS avito_image_sources (
+    source_url TEXT NOT NULL PRIMARY KEY,
+    image_id UUID REFERENCES avito_images(image_id) ON DELETE CASCADE,
+    status VARCHAR(32) NOT NULL,
+    error TEXT,
+    fetched_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create avito_ad_images table (images of an ad in the order of the Images field)
+CREATE TABLE IF NOT EXISTS avito_ad_images (
+    ad_id UUID NOT NULL REFERENCES avito_ads(ad_id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    source_url TEXT NOT NULL,
+    image_id UUID REFERENCES avito_images(image_id) ON DELETE SET NULL,
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (ad_id, position)
+);
+
+-- Create indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_avito_image_sources_image_id ON avito_image_sources(image_id);
+CREATE INDEX IF NOT EXISTS idx_avito_ad_images_image_id ON avito_ad_images(image_id);
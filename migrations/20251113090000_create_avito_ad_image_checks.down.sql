@@ -0,0 +1,2 @@
+-- Drop avito_ad_image_checks table
+DROP TABLE IF EXISTS avito_ad_image_checks;
\ No newline at end of file
@@ -0,0 +1,15 @@
+-- Create avito_ad_image_checks table (quality check result per image of an ad)
+CREATE TABLE IF NOT EXISTS avito_ad_image_checks (
+    ad_id UUID NOT NULL REFERENCES avito_ads(ad_id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    issues JSONB NOT NULL DEFAULT '[]'::jsonb,
+    format VARCHAR(16),
+    width INTEGER,
+    height INTEGER,
+    size_bytes BIGINT,
+    phash BIGINT,
+    checked_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (ad_id, position)
+);
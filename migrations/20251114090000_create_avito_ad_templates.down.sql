@@ -0,0 +1,2 @@
+-- Drop avito_ad_templates table
+DROP TABLE IF EXISTS avito_ad_templates;
\ No newline at end of file
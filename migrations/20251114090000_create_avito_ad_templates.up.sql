@@ -0,0 +1,13 @@
+-- Create avito_ad_templates table (reusable tag values for new ads)
+CREATE TABLE IF NOT EXISTS avito_ad_templates (
+    template_id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    account_id UUID NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    category VARCHAR(255),
+    fields JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create index for better query performance
+CREATE INDEX IF NOT EXISTS idx_avito_ad_templates_account_id ON avito_ad_templates(account_id);
@@ -0,0 +1,11 @@
+-- Drop avito_ad_search table
+DROP TRIGGER IF EXISTS avito_ad_search_values_inserted ON avito_ad_field_values;
+DROP TRIGGER IF EXISTS avito_ad_search_values_updated ON avito_ad_field_values;
+DROP TRIGGER IF EXISTS avito_ad_search_fields_deleted ON avito_ad_fields;
+DROP TRIGGER IF EXISTS avito_ad_search_ads_inserted ON avito_ads;
+DROP TRIGGER IF EXISTS avito_ad_search_ads_updated ON avito_ads;
+DROP FUNCTION IF EXISTS avito_ad_search_values_changed();
+DROP FUNCTION IF EXISTS avito_ad_search_fields_deleted();
+DROP FUNCTION IF EXISTS avito_ad_search_ads_changed();
+DROP FUNCTION IF EXISTS refresh_avito_ad_search(UUID[]);
+DROP TABLE IF EXISTS avito_ad_search;
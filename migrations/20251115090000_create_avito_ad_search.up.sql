@@ -0,0 +1,117 @@
+-- Create avito_ad_search table (denormalized ad text and facets for full-text search)
+CREATE TABLE IF NOT EXISTS avito_ad_search (
+    ad_id UUID NOT NULL PRIMARY KEY REFERENCES avito_ads(ad_id) ON DELETE CASCADE,
+    feed_id UUID NOT NULL,
+    account_id UUID NOT NULL,
+    status VARCHAR(255),
+    category TEXT,
+    title TEXT,
+    description TEXT,
+    price NUMERIC,
+    document TSVECTOR NOT NULL,
+    updated_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_avito_ad_search_document ON avito_ad_search USING GIN (document);
+CREATE INDEX IF NOT EXISTS idx_avito_ad_search_account_id ON avito_ad_search(account_id);
+CREATE INDEX IF NOT EXISTS idx_avito_ad_search_feed_id ON avito_ad_search(feed_id);
+
+-- Rebuild search rows of the given ads from the EAV tables.
+-- Title is weighted A, Description B, other text tags C; HTML is stripped.
+CREATE OR REPLACE FUNCTION refresh_avito_ad_search(target_ad_ids UUID[]) RETURNS VOID AS $$
+BEGIN
+    INSERT INTO avito_ad_search (ad_id, feed_id, account_id, status, category, title, description, price, document, updated_ts)
+    SELECT
+        a.ad_id,
+        a.feed_id,
+        f.account_id,
+        a.status,
+        MAX(v.value) FILTER (WHERE af.tag = 'Category'),
+        MAX(v.value) FILTER (WHERE af.tag = 'Title'),
+        MAX(regexp_replace(v.value, '<[^>]*>', ' ', 'g')) FILTER (WHERE af.tag = 'Description'),
+        MAX(
+            CASE WHEN regexp_replace(replace(v.value, ',', '.'), '\s', '', 'g') ~ '^[0-9]+(\.[0-9]+)?$'
+                THEN regexp_replace(replace(v.value, ',', '.'), '\s', '', 'g')::numeric
+            END
+        ) FILTER (WHERE af.tag = 'Price'),
+        setweight(to_tsvector('russian', COALESCE(string_agg(v.value, ' ') FILTER (WHERE af.tag = 'Title'), '')), 'A')
+            || setweight(to_tsvector('russian', COALESCE(regexp_replace(string_agg(v.value, ' ') FILTER (WHERE af.tag = 'Description'), '<[^>]*>', ' ', 'g'), '')), 'B')
+            || setweight(to_tsvector('russian', COALESCE(string_agg(v.value, ' ') FILTER (WHERE af.tag NOT IN ('Title', 'Description', 'Images', 'Id')), '')), 'C'),
+        NOW()
+    FROM avito_ads a
+    JOIN avito_feeds f ON f.feed_id = a.feed_id
+    LEFT JOIN avito_ad_fields af ON af.ad_id = a.ad_id
+    LEFT JOIN avito_ad_field_values v ON v.field_id = af.field_id
+    WHERE a.ad_id = ANY(target_ad_ids)
+    GROUP BY a.ad_id, a.feed_id, f.account_id, a.status
+    ON CONFLICT (ad_id) DO UPDATE
+    SET feed_id = EXCLUDED.feed_id,
+        account_id = EXCLUDED.account_id,
+        status = EXCLUDED.status,
+        category = EXCLUDED.category,
+        title = EXCLUDED.title,
+        description = EXCLUDED.description,
+        price = EXCLUDED.price,
+        document = EXCLUDED.document,
+        updated_ts = EXCLUDED.updated_ts;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Statement-level triggers keep the search rows in sync with every write path (imports,
+-- updates, bulk edits) while refreshing each touched ad once per statement
+CREATE OR REPLACE FUNCTION avito_ad_search_values_changed() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM refresh_avito_ad_search(ARRAY(
+        SELECT DISTINCT af.ad_id FROM changed_values c JOIN avito_ad_fields af ON af.field_id = c.field_id
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION avito_ad_search_fields_deleted() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM refresh_avito_ad_search(ARRAY(SELECT DISTINCT ad_id FROM changed_fields));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION avito_ad_search_ads_changed() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM refresh_avito_ad_search(ARRAY(SELECT ad_id FROM changed_ads));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS avito_ad_search_values_inserted ON avito_ad_field_values;
+CREATE TRIGGER avito_ad_search_values_inserted
+    AFTER INSERT ON avito_ad_field_values
+    REFERENCING NEW TABLE AS changed_values
+    FOR EACH STATEMENT EXECUTE FUNCTION avito_ad_search_values_changed();
+
+DROP TRIGGER IF EXISTS avito_ad_search_values_updated ON avito_ad_field_values;
+CREATE TRIGGER avito_ad_search_values_updated
+    AFTER UPDATE ON avito_ad_field_values
+    REFERENCING NEW TABLE AS changed_values
+    FOR EACH STATEMENT EXECUTE FUNCTION avito_ad_search_values_changed();
+
+DROP TRIGGER IF EXISTS avito_ad_search_fields_deleted ON avito_ad_fields;
+CREATE TRIGGER avito_ad_search_fields_deleted
+    AFTER DELETE ON avito_ad_fields
+    REFERENCING OLD TABLE AS changed_fields
+    FOR EACH STATEMENT EXECUTE FUNCTION avito_ad_search_fields_deleted();
+
+DROP TRIGGER IF EXISTS avito_ad_search_ads_inserted ON avito_ads;
+CREATE TRIGGER avito_ad_search_ads_inserted
+    AFTER INSERT ON avito_ads
+    REFERENCING NEW TABLE AS changed_ads
+    FOR EACH STATEMENT EXECUTE FUNCTION avito_ad_search_ads_changed();
+
+DROP TRIGGER IF EXISTS avito_ad_search_ads_updated ON avito_ads;
+CREATE TRIGGER avito_ad_search_ads_updated
+    AFTER UPDATE ON avito_ads
+    REFERENCING NEW TABLE AS changed_ads
+    FOR EACH STATEMENT EXECUTE FUNCTION avito_ad_search_ads_changed();
+
+-- Index ads that already exist
+SELECT refresh_avito_ad_search(ARRAY(SELECT ad_id FROM avito_ads));
@@ -0,0 +1,6 @@
+-- Drop keyset pagination indexes
+DROP INDEX IF EXISTS idx_avito_feeds_account_created;
+DROP INDEX IF EXISTS idx_avito_ads_feed_created;
+DROP INDEX IF EXISTS idx_avito_requests_created;
+DROP INDEX IF EXISTS idx_avito_requests_user_created;
+DROP INDEX IF EXISTS idx_avito_analytics_ads_request_position;
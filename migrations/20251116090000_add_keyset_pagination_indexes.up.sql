@@ -0,0 +1,15 @@
+-- Indexes matching the keyset pagination sort keys (sort expression, id)
+CREATE INDEX IF NOT EXISTS idx_avito_feeds_account_created ON avito_feeds(account_id, COALESCE(created_ts, 'epoch'::timestamptz), feed_id);
+CREATE INDEX IF NOT EXISTS idx_avito_ads_feed_created ON avito_ads(feed_id, COALESCE(created_ts, 'epoch'::timestamptz), ad_id);
+
+-- avito_requests and avito_analytics_ads are created outside these migrations
+DO $$
+BEGIN
+    IF to_regclass('avito_requests') IS NOT NULL THEN
+        CREATE INDEX IF NOT EXISTS idx_avito_requests_created ON avito_requests(COALESCE(created_ts, 'epoch'::timestamptz), request_id);
+        CREATE INDEX IF NOT EXISTS idx_avito_requests_user_created ON avito_requests(user_id, COALESCE(created_ts, 'epoch'::timestamptz), request_id);
+    END IF;
+    IF to_regclass('avito_analytics_ads') IS NOT NULL THEN
+        CREATE INDEX IF NOT EXISTS idx_avito_analytics_ads_request_position ON avito_analytics_ads(avito_request_id, position, ad_id);
+    END IF;
+END $$;
@@ -0,0 +1,7 @@
+-- Drop avito_ad_state_history table
+DROP TRIGGER IF EXISTS avito_ad_state_history_ads_created ON avito_ads;
+DROP FUNCTION IF EXISTS avito_ad_state_history_ads_created();
+DROP TABLE IF EXISTS avito_ad_state_history;
+ALTER TABLE avito_ads DROP CONSTRAINT IF EXISTS avito_ads_status_check;
+ALTER TABLE avito_ads ALTER COLUMN status DROP NOT NULL;
+ALTER TABLE avito_ads ALTER COLUMN status DROP DEFAULT;
@@ -0,0 +1,49 @@
+-- Map free-form ad statuses onto lifecycle states
+UPDATE avito_ads
+SET status = CASE
+    WHEN status = 'active' AND avito_ad_id IS NOT NULL AND avito_ad_id <> '' THEN 'published'
+    WHEN status = 'active' THEN 'ready'
+    WHEN status IN ('draft', 'ready', 'published', 'rejected', 'blocked', 'expired', 'archived') THEN status
+    ELSE 'draft'
+END;
+UPDATE avito_ads SET is_active = (status NOT IN ('archived', 'expired'));
+
+ALTER TABLE avito_ads ALTER COLUMN status SET DEFAULT 'draft';
+ALTER TABLE avito_ads ALTER COLUMN status SET NOT NULL;
+ALTER TABLE avito_ads ADD CONSTRAINT avito_ads_status_check
+    CHECK (status IN ('draft', 'ready', 'published', 'rejected', 'blocked', 'expired', 'archived'));
+
+-- Create avito_ad_state_history table
+CREATE TABLE IF NOT EXISTS avito_ad_state_history (
+    history_id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    ad_id UUID NOT NULL REFERENCES avito_ads(ad_id) ON DELETE CASCADE,
+    from_status VARCHAR(32),
+    to_status VARCHAR(32) NOT NULL,
+    -- user, autoload or system
+    source VARCHAR(32) NOT NULL,
+    reason TEXT,
+    -- Autoload report that caused the change
+    report_id BIGINT,
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_avito_ad_state_history_ad_id ON avito_ad_state_history(ad_id, created_ts);
+
+-- Every new ad starts its history with the state it was created in
+CREATE OR REPLACE FUNCTION avito_ad_state_history_ads_created() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO avito_ad_state_history (ad_id, from_status, to_status, source, reason)
+    SELECT ad_id, NULL, status, 'system', 'Ad created' FROM created_ads;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS avito_ad_state_history_ads_created ON avito_ads;
+CREATE TRIGGER avito_ad_state_history_ads_created
+    AFTER INSERT ON avito_ads
+    REFERENCING NEW TABLE AS created_ads
+    FOR EACH STATEMENT EXECUTE FUNCTION avito_ad_state_history_ads_created();
+
+INSERT INTO avito_ad_state_history (ad_id, from_status, to_status, source, reason)
+SELECT ad_id, NULL, status, 'system', 'Initial state' FROM avito_ads;
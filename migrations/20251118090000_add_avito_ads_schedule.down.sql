@@ -0,0 +1,5 @@
+-- Drop avito_ads schedule columns
+DROP INDEX IF EXISTS idx_avito_ads_publish_at;
+DROP INDEX IF EXISTS idx_avito_ads_unpublish_at;
+ALTER TABLE avito_ads DROP COLUMN IF EXISTS publish_at;
+ALTER TABLE avito_ads DROP COLUMN IF EXISTS unpublish_at;
@@ -0,0 +1,7 @@
+-- Add publish/unpublish schedule to avito_ads
+ALTER TABLE avito_ads ADD COLUMN IF NOT EXISTS publish_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE avito_ads ADD COLUMN IF NOT EXISTS unpublish_at TIMESTAMP WITH TIME ZONE;
+
+-- The scheduler only looks at ads with a pending date
+CREATE INDEX IF NOT EXISTS idx_avito_ads_publish_at ON avito_ads(publish_at) WHERE publish_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_avito_ads_unpublish_at ON avito_ads(unpublish_at) WHERE unpublish_at IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Drop avito_requests status columns
+DROP INDEX IF EXISTS idx_avito_requests_status;
+ALTER TABLE avito_requests DROP CONSTRAINT IF EXISTS avito_requests_status_check;
+ALTER TABLE avito_requests
+    DROP COLUMN IF EXISTS status,
+    DROP COLUMN IF EXISTS started_ts,
+    DROP COLUMN IF EXISTS finished_ts,
+    DROP COLUMN IF EXISTS progress_current,
+    DROP COLUMN IF EXISTS progress_total,
+    DROP COLUMN IF EXISTS ads_count,
+    DROP COLUMN IF EXISTS last_error;
@@ -0,0 +1,48 @@
+-- avito_requests predates these migrations; create it on fresh databases
+CREATE TABLE IF NOT EXISTS avito_requests (
+    request_id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL,
+    request VARCHAR,
+    city VARCHAR,
+    coords VARCHAR,
+    radius VARCHAR,
+    district VARCHAR,
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Crawl job lifecycle, updated from the crawler progress messages
+ALTER TABLE avito_requests
+    ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'queued',
+    ADD COLUMN IF NOT EXISTS started_ts TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS finished_ts TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS progress_current INTEGER,
+    ADD COLUMN IF NOT EXISTS progress_total INTEGER,
+    ADD COLUMN IF NOT EXISTS ads_count INTEGER,
+    ADD COLUMN IF NOT EXISTS last_error TEXT;
+
+ALTER TABLE avito_requests DROP CONSTRAINT IF EXISTS avito_requests_status_check;
+ALTER TABLE avito_requests ADD CONSTRAINT avito_requests_status_check
+    CHECK (status IN ('queued', 'running', 'done', 'failed', 'cancelled'));
+
+CREATE INDEX IF NOT EXISTS idx_avito_requests_status ON avito_requests(status)
+    WHERE status IN ('queued', 'running');
+
+-- Earlier crawls either stored results or never reported back
+DO $$
+BEGIN
+    IF to_regclass('avito_analytics_ads') IS NOT NULL THEN
+        UPDATE avito_requests r
+        SET status = CASE WHEN c.ads_count > 0 THEN 'done' ELSE 'failed' END,
+            ads_count = c.ads_count,
+            finished_ts = r.updated_ts,
+            last_error = CASE WHEN c.ads_count > 0 THEN NULL ELSE 'No results recorded' END
+        FROM (
+            SELECT r2.request_id, COUNT(a.ad_id)::int AS ads_count
+            FROM avito_requests r2
+            LEFT JOIN avito_analytics_ads a ON a.avito_request_id = r2.request_id
+            GROUP BY r2.request_id
+        ) c
+        WHERE r.request_id = c.request_id AND r.status = 'queued';
+    END IF;
+END $$;
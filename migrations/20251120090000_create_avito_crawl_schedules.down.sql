@@ -0,0 +1,8 @@
+-- Drop avito_crawl_schedules table
+DROP TRIGGER IF EXISTS avito_analytics_ads_set_schedule ON avito_analytics_ads;
+DROP FUNCTION IF EXISTS avito_analytics_ads_set_schedule();
+DROP INDEX IF EXISTS idx_avito_analytics_ads_schedule;
+ALTER TABLE avito_analytics_ads DROP COLUMN IF EXISTS schedule_id;
+DROP INDEX IF EXISTS idx_avito_requests_schedule;
+ALTER TABLE avito_requests DROP COLUMN IF EXISTS schedule_id;
+DROP TABLE IF EXISTS avito_crawl_schedules;
@@ -0,0 +1,85 @@
+-- Saved search definitions crawled on a fixed interval
+CREATE TABLE IF NOT EXISTS avito_crawl_schedules (
+    schedule_id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL,
+    name VARCHAR(255),
+    request VARCHAR NOT NULL,
+    city VARCHAR NOT NULL DEFAULT '',
+    coords VARCHAR NOT NULL DEFAULT '',
+    radius VARCHAR NOT NULL DEFAULT '',
+    district VARCHAR NOT NULL DEFAULT '',
+    interval_hours INTEGER NOT NULL DEFAULT 24 CHECK (interval_hours BETWEEN 1 AND 720),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_ts TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_run_ts TIMESTAMP WITH TIME ZONE,
+    last_request_id UUID,
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_avito_crawl_schedules_user ON avito_crawl_schedules(user_id, COALESCE(created_ts, 'epoch'::timestamptz), schedule_id);
+CREATE INDEX IF NOT EXISTS idx_avito_crawl_schedules_due ON avito_crawl_schedules(next_run_ts) WHERE is_active;
+
+-- Every scheduled run is an avito_requests row pointing at its definition
+ALTER TABLE avito_requests
+    ADD COLUMN IF NOT EXISTS schedule_id UUID REFERENCES avito_crawl_schedules(schedule_id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_avito_requests_schedule ON avito_requests(schedule_id, COALESCE(created_ts, 'epoch'::timestamptz), request_id)
+    WHERE schedule_id IS NOT NULL;
+
+-- avito_analytics_ads predates these migrations; create it on fresh databases
+CREATE TABLE IF NOT EXISTS avito_analytics_ads (
+    ad_id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    my_ad VARCHAR NOT NULL,
+    run_date TIMESTAMP WITH TIME ZONE NOT NULL,
+    city_query VARCHAR NOT NULL,
+    search_query VARCHAR NOT NULL,
+    position INTEGER NOT NULL,
+    views VARCHAR NOT NULL,
+    views_today VARCHAR NOT NULL,
+    promotion VARCHAR NOT NULL,
+    delivery VARCHAR NOT NULL,
+    ad_date VARCHAR NOT NULL,
+    avito_ad_id VARCHAR NOT NULL,
+    title VARCHAR NOT NULL,
+    price VARCHAR NOT NULL,
+    link VARCHAR NOT NULL,
+    categories VARCHAR NOT NULL,
+    seller_id VARCHAR NOT NULL,
+    seller_name VARCHAR NOT NULL,
+    seller_type VARCHAR NOT NULL,
+    register_date VARCHAR NOT NULL,
+    answer_time VARCHAR NOT NULL,
+    rating VARCHAR NOT NULL,
+    reviews_count VARCHAR NOT NULL,
+    ads_count VARCHAR NOT NULL,
+    closed_ads_count VARCHAR NOT NULL,
+    photo_count VARCHAR NOT NULL,
+    address VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+    avito_request_id UUID NOT NULL,
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_avito_analytics_ads_request_position ON avito_analytics_ads(avito_request_id, position, ad_id);
+
+-- Crawled ads also point at the definition of their run; the crawler only knows the run
+ALTER TABLE avito_analytics_ads
+    ADD COLUMN IF NOT EXISTS schedule_id UUID REFERENCES avito_crawl_schedules(schedule_id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_avito_analytics_ads_schedule ON avito_analytics_ads(schedule_id, run_date)
+    WHERE schedule_id IS NOT NULL;
+
+CREATE OR REPLACE FUNCTION avito_analytics_ads_set_schedule() RETURNS trigger AS $$
+BEGIN
+    IF NEW.schedule_id IS NULL THEN
+        SELECT schedule_id INTO NEW.schedule_id
+        FROM avito_requests
+        WHERE request_id = NEW.avito_request_id;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS avito_analytics_ads_set_schedule ON avito_analytics_ads;
+CREATE TRIGGER avito_analytics_ads_set_schedule
+    BEFORE INSERT ON avito_analytics_ads
+    FOR EACH ROW EXECUTE FUNCTION avito_analytics_ads_set_schedule();
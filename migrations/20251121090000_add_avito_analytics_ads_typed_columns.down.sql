@@ -0,0 +1,17 @@
+-- Drop typed crawler columns
+DROP TRIGGER IF EXISTS avito_analytics_ads_normalize ON avito_analytics_ads;
+DROP FUNCTION IF EXISTS avito_analytics_ads_normalize();
+DROP FUNCTION IF EXISTS avito_parse_ad_date(TEXT, TIMESTAMP WITH TIME ZONE);
+DROP FUNCTION IF EXISTS avito_parse_decimal(TEXT);
+DROP FUNCTION IF EXISTS avito_parse_count(TEXT);
+DROP INDEX IF EXISTS idx_avito_analytics_ads_request_ad_ts;
+DROP INDEX IF EXISTS idx_avito_analytics_ads_request_price;
+ALTER TABLE avito_analytics_ads
+    DROP COLUMN IF EXISTS ad_ts,
+    DROP COLUMN IF EXISTS photo_count_value,
+    DROP COLUMN IF EXISTS ads_count_value,
+    DROP COLUMN IF EXISTS reviews_count_value,
+    DROP COLUMN IF EXISTS rating_value,
+    DROP COLUMN IF EXISTS price_value,
+    DROP COLUMN IF EXISTS views_today_value,
+    DROP COLUMN IF EXISTS views_value;
@@ -0,0 +1,142 @@
+-- Typed copies of the values the crawler stores as displayed on the site; the raw strings stay as they are
+ALTER TABLE avito_analytics_ads
+    ADD COLUMN IF NOT EXISTS views_value INTEGER,
+    ADD COLUMN IF NOT EXISTS views_today_value INTEGER,
+    ADD COLUMN IF NOT EXISTS price_value NUMERIC(14, 2),
+    ADD COLUMN IF NOT EXISTS rating_value NUMERIC(3, 2),
+    ADD COLUMN IF NOT EXISTS reviews_count_value INTEGER,
+    ADD COLUMN IF NOT EXISTS ads_count_value INTEGER,
+    ADD COLUMN IF NOT EXISTS photo_count_value INTEGER,
+    ADD COLUMN IF NOT EXISTS ad_ts TIMESTAMP WITH TIME ZONE;
+
+-- First number in the text: "1 234" -> 1234, "+5 сегодня" -> 5, "12 отзывов" -> 12
+CREATE OR REPLACE FUNCTION avito_parse_count(value TEXT) RETURNS INTEGER AS $$
+DECLARE
+    digits TEXT := regexp_replace(substring(value FROM '\d[\d\s\u00a0\u202f]*'), '[\s\u00a0\u202f]', '', 'g');
+BEGIN
+    IF digits IS NULL OR digits = '' OR length(digits) > 9 THEN
+        RETURN NULL;
+    END IF;
+    RETURN digits::INTEGER;
+EXCEPTION
+    WHEN others THEN
+        -- A value that can't be stored must not fail the crawler's insert
+        RETURN NULL;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+-- First decimal in the text: "12 500 ₽" -> 12500, "1 200,50 ₽/мес." -> 1200.50, "4,8" -> 4.8.
+-- Rounded to price_value's NUMERIC(14, 2), so at most 12 integer digits
+CREATE OR REPLACE FUNCTION avito_parse_decimal(value TEXT) RETURNS NUMERIC AS $$
+DECLARE
+    number TEXT := replace(
+        regexp_replace(substring(value FROM '\d[\d\s\u00a0\u202f]*(?:[.,]\d+)?'), '[\s\u00a0\u202f]', '', 'g'),
+        ',', '.'
+    );
+BEGIN
+    IF number IS NULL OR number = '' OR length(split_part(number, '.', 1)) > 12 THEN
+        RETURN NULL;
+    END IF;
+    RETURN number::NUMERIC(14, 2);
+EXCEPTION
+    WHEN others THEN
+        RETURN NULL;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+-- Ad dates are shown in Moscow time relative to the crawl: "24.10.2025 в 15:57",
+-- "сегодня в 15:57", "вчера в 09:10", "24 октября в 15:57", "3 марта 2024"
+CREATE OR REPLACE FUNCTION avito_parse_ad_date(value TEXT, run_ts TIMESTAMP WITH TIME ZONE) RETURNS TIMESTAMP WITH TIME ZONE AS $$
+DECLARE
+    text_value TEXT := lower(trim(coalesce(value, '')));
+    run_day DATE := (coalesce(run_ts, NOW()) AT TIME ZONE 'Europe/Moscow')::DATE;
+    months TEXT[] := ARRAY['янв', 'фев', 'мар', 'апр', 'ма', 'июн', 'июл', 'авг', 'сен', 'окт', 'ноя', 'дек'];
+    parts TEXT[];
+    day DATE;
+    month_index INTEGER;
+    time_of_day TIME := '00:00';
+BEGIN
+    parts := regexp_match(text_value, '(\d{1,2}):(\d{2})');
+    IF parts IS NOT NULL THEN
+        time_of_day := make_time(parts[1]::INTEGER, parts[2]::INTEGER, 0);
+    END IF;
+
+    IF text_value LIKE 'сегодня%' THEN
+        day := run_day;
+    ELSIF text_value LIKE 'позавчера%' THEN
+        day := run_day - 2;
+    ELSIF text_value LIKE 'вчера%' THEN
+        day := run_day - 1;
+    ELSE
+        parts := regexp_match(text_value, '^(\d{1,2})\.(\d{1,2})\.(\d{4})');
+        IF parts IS NOT NULL THEN
+            day := make_date(parts[3]::INTEGER, parts[2]::INTEGER, parts[1]::INTEGER);
+        ELSE
+            parts := regexp_match(text_value, '^(\d{1,2})\s+([а-я]+)(?:\s+(\d{4}))?');
+            IF parts IS NULL THEN
+                RETURN NULL;
+            END IF;
+            SELECT i INTO month_index
+            FROM generate_subscripts(months, 1) AS i
+            WHERE parts[2] LIKE months[i] || '%'
+            ORDER BY length(months[i]) DESC
+            LIMIT 1;
+            IF month_index IS NULL THEN
+                RETURN NULL;
+            END IF;
+            IF parts[3] IS NOT NULL THEN
+                day := make_date(parts[3]::INTEGER, month_index, parts[1]::INTEGER);
+            ELSE
+                -- No year means the last such date on or before the crawl
+                day := make_date(extract(YEAR FROM run_day)::INTEGER, month_index, parts[1]::INTEGER);
+                IF day > run_day THEN
+                    day := make_date(extract(YEAR FROM run_day)::INTEGER - 1, month_index, parts[1]::INTEGER);
+                END IF;
+            END IF;
+        END IF;
+    END IF;
+
+    RETURN (day + time_of_day) AT TIME ZONE 'Europe/Moscow';
+EXCEPTION
+    WHEN others THEN
+        -- "31 февраля" and the like
+        RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+-- The crawler writes rows directly, so normalisation happens on every insert and update
+CREATE OR REPLACE FUNCTION avito_analytics_ads_normalize() RETURNS trigger AS $$
+BEGIN
+    NEW.views_value := avito_parse_count(NEW.views);
+    NEW.views_today_value := avito_parse_count(NEW.views_today);
+    NEW.price_value := avito_parse_decimal(NEW.price);
+    NEW.rating_value := CASE WHEN avito_parse_decimal(NEW.rating) <= 5 THEN avito_parse_decimal(NEW.rating) END;
+    NEW.reviews_count_value := avito_parse_count(NEW.reviews_count);
+    NEW.ads_count_value := avito_parse_count(NEW.ads_count);
+    NEW.photo_count_value := avito_parse_count(NEW.photo_count);
+    NEW.ad_ts := avito_parse_ad_date(NEW.ad_date, NEW.run_date);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS avito_analytics_ads_normalize ON avito_analytics_ads;
+CREATE TRIGGER avito_analytics_ads_normalize
+    BEFORE INSERT OR UPDATE OF views, views_today, price, rating, reviews_count, ads_count, photo_count, ad_date, run_date
+    ON avito_analytics_ads
+    FOR EACH ROW EXECUTE FUNCTION avito_analytics_ads_normalize();
+
+-- Backfill the rows crawled so far
+UPDATE avito_analytics_ads
+SET views_value = avito_parse_count(views),
+    views_today_value = avito_parse_count(views_today),
+    price_value = avito_parse_decimal(price),
+    rating_value = CASE WHEN avito_parse_decimal(rating) <= 5 THEN avito_parse_decimal(rating) END,
+    reviews_count_value = avito_parse_count(reviews_count),
+    ads_count_value = avito_parse_count(ads_count),
+    photo_count_value = avito_parse_count(photo_count),
+    ad_ts = avito_parse_ad_date(ad_date, run_date);
+
+CREATE INDEX IF NOT EXISTS idx_avito_analytics_ads_request_price ON avito_analytics_ads(avito_request_id, price_value)
+    WHERE price_value IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_avito_analytics_ads_request_ad_ts ON avito_analytics_ads(avito_request_id, ad_ts)
+    WHERE ad_ts IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Drop competitor sellers
+DROP FUNCTION IF EXISTS avito_sync_request_sellers(UUID);
+DROP TABLE IF EXISTS avito_seller_snapshots;
+DROP TABLE IF EXISTS avito_sellers;
@@ -0,0 +1,128 @@
+-- Competitor sellers seen in crawl results, with their latest profile values
+CREATE TABLE IF NOT EXISTS avito_sellers (
+    seller_id VARCHAR NOT NULL PRIMARY KEY,
+    seller_name VARCHAR NOT NULL DEFAULT '',
+    seller_type VARCHAR NOT NULL DEFAULT '',
+    register_date VARCHAR NOT NULL DEFAULT '',
+    answer_time VARCHAR NOT NULL DEFAULT '',
+    rating NUMERIC(3, 2),
+    reviews_count INTEGER,
+    ads_count INTEGER,
+    closed_ads_count INTEGER,
+    first_seen_ts TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_seen_ts TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_request_id UUID,
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- One row per seller and crawl run: the profile as shown then and the seller's ads in that run
+CREATE TABLE IF NOT EXISTS avito_seller_snapshots (
+    seller_id VARCHAR NOT NULL REFERENCES avito_sellers(seller_id) ON DELETE CASCADE,
+    avito_request_id UUID NOT NULL REFERENCES avito_requests(request_id) ON DELETE CASCADE,
+    run_date TIMESTAMP WITH TIME ZONE NOT NULL,
+    seller_name VARCHAR NOT NULL DEFAULT '',
+    rating NUMERIC(3, 2),
+    reviews_count INTEGER,
+    ads_count INTEGER,
+    closed_ads_count INTEGER,
+    ads_in_run INTEGER NOT NULL,
+    promoted_ads INTEGER NOT NULL,
+    own_ads INTEGER NOT NULL DEFAULT 0,
+    avg_price NUMERIC(14, 2),
+    best_position INTEGER NOT NULL,
+    created_ts TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (seller_id, avito_request_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_avito_seller_snapshots_request ON avito_seller_snapshots(avito_request_id, ads_in_run DESC, best_position);
+
+-- Upserts the sellers of one run and its snapshots; safe to run again for the same run.
+-- Profiles only move forward: an older run never overwrites values from a newer one.
+CREATE OR REPLACE FUNCTION avito_sync_request_sellers(p_request_id UUID) RETURNS INTEGER AS $$
+DECLARE
+    synced INTEGER;
+BEGIN
+    WITH ads AS (
+        SELECT DISTINCT ON (seller_id, COALESCE(NULLIF(avito_ad_id, ''), ad_id::text)) *
+        FROM avito_analytics_ads
+        WHERE avito_request_id = p_request_id AND trim(seller_id) <> ''
+        ORDER BY seller_id, COALESCE(NULLIF(avito_ad_id, ''), ad_id::text), position
+    ),
+    profiles AS (
+        SELECT DISTINCT ON (seller_id)
+            seller_id, seller_name, seller_type, register_date, answer_time, run_date,
+            rating_value AS rating, reviews_count_value AS reviews_count,
+            ads_count_value AS ads_count, avito_parse_count(closed_ads_count) AS closed_ads_count
+        FROM ads
+        ORDER BY seller_id, position
+    )
+    INSERT INTO avito_sellers (
+        seller_id, seller_name, seller_type, register_date, answer_time,
+        rating, reviews_count, ads_count, closed_ads_count,
+        first_seen_ts, last_seen_ts, last_request_id
+    )
+    SELECT seller_id, seller_name, seller_type, register_date, answer_time,
+        rating, reviews_count, ads_count, closed_ads_count,
+        run_date, run_date, p_request_id
+    FROM profiles
+    ON CONFLICT (seller_id) DO UPDATE SET
+        seller_name = CASE WHEN EXCLUDED.last_seen_ts >= avito_sellers.last_seen_ts THEN EXCLUDED.seller_name ELSE avito_sellers.seller_name END,
+        seller_type = CASE WHEN EXCLUDED.last_seen_ts >= avito_sellers.last_seen_ts THEN EXCLUDED.seller_type ELSE avito_sellers.seller_type END,
+        register_date = CASE WHEN EXCLUDED.last_seen_ts >= avito_sellers.last_seen_ts THEN EXCLUDED.register_date ELSE avito_sellers.register_date END,
+        answer_time = CASE WHEN EXCLUDED.last_seen_ts >= avito_sellers.last_seen_ts THEN EXCLUDED.answer_time ELSE avito_sellers.answer_time END,
+        rating = CASE WHEN EXCLUDED.last_seen_ts >= avito_sellers.last_seen_ts THEN COALESCE(EXCLUDED.rating, avito_sellers.rating) ELSE avito_sellers.rating END,
+        reviews_count = CASE WHEN EXCLUDED.last_seen_ts >= avito_sellers.last_seen_ts THEN COALESCE(EXCLUDED.reviews_count, avito_sellers.reviews_count) ELSE avito_sellers.reviews_count END,
+        ads_count = CASE WHEN EXCLUDED.last_seen_ts >= avito_sellers.last_seen_ts THEN COALESCE(EXCLUDED.ads_count, avito_sellers.ads_count) ELSE avito_sellers.ads_count END,
+        closed_ads_count = CASE WHEN EXCLUDED.last_seen_ts >= avito_sellers.last_seen_ts THEN COALESCE(EXCLUDED.closed_ads_count, avito_sellers.closed_ads_count) ELSE avito_sellers.closed_ads_count END,
+        last_request_id = CASE WHEN EXCLUDED.last_seen_ts >= avito_sellers.last_seen_ts THEN EXCLUDED.last_request_id ELSE avito_sellers.last_request_id END,
+        first_seen_ts = LEAST(avito_sellers.first_seen_ts, EXCLUDED.first_seen_ts),
+        last_seen_ts = GREATEST(avito_sellers.last_seen_ts, EXCLUDED.last_seen_ts),
+        updated_ts = NOW();
+
+    WITH ads AS (
+        SELECT DISTINCT ON (seller_id, COALESCE(NULLIF(avito_ad_id, ''), ad_id::text)) *
+        FROM avito_analytics_ads
+        WHERE avito_request_id = p_request_id AND trim(seller_id) <> ''
+        ORDER BY seller_id, COALESCE(NULLIF(avito_ad_id, ''), ad_id::text), position
+    )
+    INSERT INTO avito_seller_snapshots (
+        seller_id, avito_request_id, run_date, seller_name, rating, reviews_count, ads_count,
+        closed_ads_count, ads_in_run, promoted_ads, own_ads, avg_price, best_position
+    )
+    SELECT seller_id, p_request_id, MIN(run_date),
+        (array_agg(seller_name ORDER BY position))[1],
+        (array_agg(rating_value ORDER BY position))[1],
+        (array_agg(reviews_count_value ORDER BY position))[1],
+        (array_agg(ads_count_value ORDER BY position))[1],
+        (array_agg(avito_parse_count(closed_ads_count) ORDER BY position))[1],
+        COUNT(*),
+        COUNT(*) FILTER (WHERE trim(promotion) <> ''),
+        COUNT(*) FILTER (WHERE lower(trim(my_ad)) IN ('1', 'true', 't', 'yes', 'y', 'да', '+')),
+        round(AVG(price_value) FILTER (WHERE price_value > 0), 2),
+        MIN(position)
+    FROM ads
+    GROUP BY seller_id
+    ON CONFLICT (seller_id, avito_request_id) DO UPDATE SET
+        run_date = EXCLUDED.run_date,
+        seller_name = EXCLUDED.seller_name,
+        rating = EXCLUDED.rating,
+        reviews_count = EXCLUDED.reviews_count,
+        ads_count = EXCLUDED.ads_count,
+        closed_ads_count = EXCLUDED.closed_ads_count,
+        ads_in_run = EXCLUDED.ads_in_run,
+        promoted_ads = EXCLUDED.promoted_ads,
+        own_ads = EXCLUDED.own_ads,
+        avg_price = EXCLUDED.avg_price,
+        best_position = EXCLUDED.best_position;
+
+    GET DIAGNOSTICS synced = ROW_COUNT;
+    RETURN synced;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Backfill from the runs crawled so far, oldest first
+SELECT avito_sync_request_sellers(r.request_id)
+FROM avito_requests r
+WHERE EXISTS (SELECT 1 FROM avito_analytics_ads a WHERE a.avito_request_id = r.request_id)
+ORDER BY COALESCE(r.created_ts, 'epoch'::timestamptz), r.request_id;
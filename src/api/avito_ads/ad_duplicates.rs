@@ -0,0 +1,111 @@
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::api::avito_ads::load_ad_fields;
+use crate::models::{ApiError, DuplicateClusterAd};
+use crate::utils::ad_duplicates::DuplicateCandidate;
+
+// Ads compared against a new ad, picked by title match from the search index
+const SIMILAR_ADS_LIMIT: i64 = 200;
+
+// Non-archived ads of the given feeds
+pub async fn load_feed_ad_ids(
+	db: &Pool<Postgres>,
+	feed_ids: &[Uuid],
+) -> Result<Vec<Uuid>, ApiError> {
+	sqlx::query_scalar::<_, Uuid>(
+		r#"
+        SELECT ad_id
+        FROM avito_ads
+        WHERE feed_id = ANY($1) AND status <> 'archived'
+        "#,
+	)
+	.bind(feed_ids)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))
+}
+
+// Fields and checked image hashes of the given ads
+pub async fn load_duplicate_candidates(
+	db: &Pool<Postgres>,
+	ad_ids: &[Uuid],
+) -> Result<Vec<DuplicateCandidate>, ApiError> {
+	let fields = load_ad_fields(db, ad_ids).await?;
+
+	let mut hashes: HashMap<Uuid, Vec<i64>> = HashMap::new();
+	let rows = sqlx::query_as::<_, (Uuid, i64)>(
+		r#"
+        SELECT ad_id, phash
+        FROM avito_ad_image_checks
+        WHERE ad_id = ANY($1) AND phash IS NOT NULL
+        "#,
+	)
+	.bind(ad_ids)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch image hashes: {}", e)))?;
+	for (ad_id, phash) in rows {
+		hashes.entry(ad_id).or_default().push(phash);
+	}
+
+	Ok(fields
+		.iter()
+		.map(|(ad_id, fields)| {
+			DuplicateCandidate::new(
+				*ad_id,
+				fields,
+				hashes.get(ad_id).map(Vec::as_slice).unwrap_or(&[]),
+			)
+		})
+		.collect())
+}
+
+// Non-archived ads of the account sharing any title word, best matches first
+pub async fn find_similar_ad_ids(
+	db: &Pool<Postgres>,
+	account_id: Uuid,
+	title_words: &[String],
+) -> Result<Vec<Uuid>, ApiError> {
+	if title_words.is_empty() {
+		return Ok(Vec::new());
+	}
+
+	let query = title_words.join(" or ");
+	sqlx::query_scalar::<_, Uuid>(
+		r#"
+        SELECT s.ad_id
+        FROM avito_ad_search s
+        WHERE s.account_id = $1
+            AND s.status <> 'archived'
+            AND s.document @@ websearch_to_tsquery('russian', $2)
+        ORDER BY ts_rank(s.document, websearch_to_tsquery('russian', $2)) DESC, s.ad_id
+        LIMIT $3
+        "#,
+	)
+	.bind(account_id)
+	.bind(query)
+	.bind(SIMILAR_ADS_LIMIT)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to search similar ads: {}", e)))
+}
+
+pub async fn load_duplicate_cluster_ads(
+	db: &Pool<Postgres>,
+	ad_ids: &[Uuid],
+) -> Result<Vec<DuplicateClusterAd>, ApiError> {
+	sqlx::query_as::<_, DuplicateClusterAd>(
+		r#"
+        SELECT a.ad_id, a.feed_id, a.parsed_id, a.status, s.title, s.price::text AS price
+        FROM avito_ads a
+        LEFT JOIN avito_ad_search s ON s.ad_id = a.ad_id
+        WHERE a.ad_id = ANY($1)
+        "#,
+	)
+	.bind(ad_ids)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))
+}
@@ -0,0 +1,157 @@
+use sqlx::{Pool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+	AdState, AdStateChange, AdStateChangeRequest, AdStateTransitionResult, ApiError,
+	AvitoAdStateHistory, TransitionSource,
+};
+use crate::utils::ad_lifecycle::is_transition_allowed;
+
+// Apply the allowed state changes and record them in the ad history; the ads are
+// locked first so concurrent changes can't skip a transition check
+pub async fn transition_ad_states(
+	tx: &mut Transaction<'_, Postgres>,
+	requests: &[AdStateChangeRequest],
+	source: TransitionSource,
+	report_id: Option<i64>,
+) -> Result<AdStateTransitionResult, ApiError> {
+	let mut result = AdStateTransitionResult::default();
+	if requests.is_empty() {
+		return Ok(result);
+	}
+
+	let ad_ids: Vec<Uuid> = requests.iter().map(|request| request.ad_id).collect();
+	let rows = sqlx::query_as::<_, (Uuid, String)>(
+		r#"
+        SELECT ad_id, status
+        FROM avito_ads
+        WHERE ad_id = ANY($1)
+        ORDER BY ad_id
+        FOR UPDATE
+        "#,
+	)
+	.bind(&ad_ids)
+	.fetch_all(&mut **tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to lock ads: {}", e)))?;
+
+	let mut current: HashMap<Uuid, AdState> = rows
+		.into_iter()
+		.filter_map(|(ad_id, status)| AdState::parse(&status).map(|state| (ad_id, state)))
+		.collect();
+
+	let mut changed_ids = Vec::new();
+	let mut from_statuses = Vec::new();
+	let mut to_statuses = Vec::new();
+	let mut reasons = Vec::new();
+	for request in requests {
+		let from = match current.get(&request.ad_id) {
+			Some(state) => *state,
+			None => {
+				result.missing.push(request.ad_id);
+				continue;
+			}
+		};
+		// Repeated reports of the current state are not changes
+		if from == request.to {
+			continue;
+		}
+		let change = AdStateChange {
+			ad_id: request.ad_id,
+			from,
+			to: request.to,
+		};
+		if !is_transition_allowed(from, request.to, source) {
+			result.rejected.push(change);
+			continue;
+		}
+
+		changed_ids.push(request.ad_id);
+		from_statuses.push(from.as_str().to_string());
+		to_statuses.push(request.to.as_str().to_string());
+		reasons.push(request.reason.clone());
+		current.insert(request.ad_id, request.to);
+		result.changed.push(change);
+	}
+
+	if changed_ids.is_empty() {
+		return Ok(result);
+	}
+
+	// Expired and archived ads are no longer shown on Avito
+	sqlx::query(
+		r#"
+        UPDATE avito_ads a
+        SET status = c.status, is_active = c.status NOT IN ('archived', 'expired')
+        FROM (SELECT DISTINCT ON (ad_id) ad_id, status, ord
+              FROM UNNEST($1::uuid[], $2::varchar[]) WITH ORDINALITY AS u(ad_id, status, ord)
+              ORDER BY ad_id, ord DESC) c
+        WHERE a.ad_id = c.ad_id
+        "#,
+	)
+	.bind(&changed_ids)
+	.bind(&to_statuses)
+	.execute(&mut **tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to update ad status: {}", e)))?;
+
+	sqlx::query(
+		r#"
+        INSERT INTO avito_ad_state_history (ad_id, from_status, to_status, source, reason, report_id)
+        SELECT ad_id, from_status, to_status, $5, reason, $6
+        FROM UNNEST($1::uuid[], $2::varchar[], $3::varchar[], $4::text[])
+            AS u(ad_id, from_status, to_status, reason)
+        "#,
+	)
+	.bind(&changed_ids)
+	.bind(&from_statuses)
+	.bind(&to_statuses)
+	.bind(&reasons)
+	.bind(source.as_str())
+	.bind(report_id)
+	.execute(&mut **tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to record ad history: {}", e)))?;
+
+	Ok(result)
+}
+
+// Ads among `ad_ids` that belong to feeds of the account
+pub async fn filter_account_ads(
+	db: &Pool<Postgres>,
+	ad_ids: &[Uuid],
+	account_id: Uuid,
+) -> Result<Vec<Uuid>, ApiError> {
+	sqlx::query_scalar::<_, Uuid>(
+		r#"
+        SELECT a.ad_id
+        FROM avito_ads a
+        JOIN avito_feeds f ON f.feed_id = a.feed_id
+        WHERE a.ad_id = ANY($1) AND f.account_id = $2
+        "#,
+	)
+	.bind(ad_ids)
+	.bind(account_id)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))
+}
+
+pub async fn load_ad_state_history(
+	db: &Pool<Postgres>,
+	ad_id: Uuid,
+) -> Result<Vec<AvitoAdStateHistory>, ApiError> {
+	sqlx::query_as::<_, AvitoAdStateHistory>(
+		r#"
+        SELECT history_id, ad_id, from_status, to_status, source, reason, report_id, created_ts
+        FROM avito_ad_state_history
+        WHERE ad_id = $1
+        ORDER BY created_ts, history_id
+        "#,
+	)
+	.bind(ad_id)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ad history: {}", e)))
+}
@@ -0,0 +1,234 @@
+use sqlx::{Pool, Postgres, QueryBuilder};
+
+use crate::{
+	api::shared::{Keyset, Page, PageRequest, SortKey},
+	models::{AdSearchFacetCount, AdSearchFilters, AdSearchHit, AdSearchSort, ApiError},
+};
+
+// Values returned per facet, most frequent first
+const FACET_LIMIT: i64 = 50;
+
+// Which filter to leave out, so a facet counts every value still reachable by changing it
+#[derive(Clone, Copy, PartialEq)]
+enum SkipFilter<'a> {
+	None,
+	Status,
+	Feed,
+	Account,
+	Tag(&'a str),
+}
+
+fn has_query(filters: &AdSearchFilters) -> bool {
+	filters
+		.q
+		.as_ref()
+		.map(|q| !q.trim().is_empty())
+		.unwrap_or(false)
+}
+
+// Sort actually applied: relevance needs a text query
+pub fn effective_sort(filters: &AdSearchFilters, sort: AdSearchSort) -> AdSearchSort {
+	if sort == AdSearchSort::Relevance && !has_query(filters) {
+		AdSearchSort::CreatedDesc
+	} else {
+		sort
+	}
+}
+
+fn push_ts_query<'a>(builder: &mut QueryBuilder<'a, Postgres>, filters: &'a AdSearchFilters) {
+	builder.push("websearch_to_tsquery('russian', ");
+	builder.push_bind(filters.q.as_deref().unwrap_or("").trim());
+	builder.push(")");
+}
+
+// Search results are keyset-paged over a CTE of the matching ads, so the sort
+// expressions only see plain columns and the query is bound once
+pub static AD_SEARCH_KEYSET: Keyset = Keyset {
+	from: "hits",
+	id_expr: "ad_id",
+	id_type: "uuid",
+	sort_keys: &[
+		SortKey {
+			name: "relevance",
+			expr: "COALESCE(rank, 0)",
+			sql_type: "float4",
+		},
+		SortKey {
+			name: "created_desc",
+			expr: "COALESCE(created_ts, 'epoch'::timestamptz)",
+			sql_type: "timestamptz",
+		},
+		SortKey {
+			name: "price_asc",
+			expr: "COALESCE(price, 'Infinity'::float8)",
+			sql_type: "float8",
+		},
+		SortKey {
+			name: "price_desc",
+			expr: "COALESCE(price, '-Infinity'::float8)",
+			sql_type: "float8",
+		},
+		SortKey {
+			name: "title",
+			expr: "COALESCE(title, '')",
+			sql_type: "text",
+		},
+	],
+	default_descending: true,
+	default_limit: 20,
+	max_limit: 100,
+};
+
+// Keyset sort key and direction for a search sort
+pub fn search_sort_key(sort: AdSearchSort) -> (&'static str, bool) {
+	match sort {
+		AdSearchSort::Relevance => ("relevance", true),
+		AdSearchSort::CreatedDesc => ("created_desc", true),
+		AdSearchSort::PriceAsc => ("price_asc", false),
+		AdSearchSort::PriceDesc => ("price_desc", true),
+		AdSearchSort::Title => ("title", false),
+	}
+}
+
+fn push_filters<'a>(
+	builder: &mut QueryBuilder<'a, Postgres>,
+	filters: &'a AdSearchFilters,
+	skip: SkipFilter<'a>,
+) {
+	builder.push(" WHERE TRUE");
+
+	if let Some(account_id) = filters.account_id {
+		if skip != SkipFilter::Account {
+			builder.push(" AND s.account_id = ");
+			builder.push_bind(account_id);
+		}
+	}
+	if let Some(feed_ids) = &filters.feed_ids {
+		if skip != SkipFilter::Feed && !feed_ids.is_empty() {
+			builder.push(" AND s.feed_id = ANY(");
+			builder.push_bind(feed_ids);
+			builder.push(")");
+		}
+	}
+	if let Some(statuses) = &filters.statuses {
+		if skip != SkipFilter::Status && !statuses.is_empty() {
+			builder.push(" AND s.status = ANY(");
+			builder.push_bind(statuses);
+			builder.push(")");
+		}
+	}
+	if has_query(filters) {
+		builder.push(" AND s.document @@ ");
+		push_ts_query(builder, filters);
+	}
+	if let Some(price_min) = filters.price_min {
+		builder.push(" AND s.price >= ");
+		builder.push_bind(price_min);
+		builder.push("::numeric");
+	}
+	if let Some(price_max) = filters.price_max {
+		builder.push(" AND s.price <= ");
+		builder.push_bind(price_max);
+		builder.push("::numeric");
+	}
+	for (tag, values) in &filters.tags {
+		if values.is_empty() || skip == SkipFilter::Tag(tag.as_str()) {
+			continue;
+		}
+		builder.push(
+			" AND EXISTS (SELECT 1 FROM avito_ad_fields tf JOIN avito_ad_field_values tv ON tv.field_id = tf.field_id WHERE tf.ad_id = s.ad_id AND tf.tag = ",
+		);
+		builder.push_bind(tag);
+		builder.push(" AND tv.value = ANY(");
+		builder.push_bind(values);
+		builder.push("))");
+	}
+}
+
+// One page of matching ads, with the total when the request asks for it
+pub async fn search_ads(
+	db: &Pool<Postgres>,
+	filters: &AdSearchFilters,
+	request: &PageRequest,
+) -> Result<Page<AdSearchHit>, ApiError> {
+	let with_query = has_query(filters);
+
+	AD_SEARCH_KEYSET
+		.fetch_with::<AdSearchHit, _, _>(
+			db,
+			request,
+			"ad_id, feed_id, account_id, parsed_id, avito_ad_id, status, category, title, price, snippet, rank, created_ts",
+			|builder| {
+				builder.push(
+					"WITH hits AS (SELECT s.ad_id, s.feed_id, s.account_id, a.parsed_id, a.avito_ad_id, s.status, s.category, s.title, s.price::float8 AS price, ",
+				);
+				if with_query {
+					builder.push("ts_headline('russian', COALESCE(s.description, ''), ");
+					push_ts_query(builder, filters);
+					builder.push(
+						", 'MaxFragments=2, MaxWords=20, MinWords=5') AS snippet, ts_rank(s.document, ",
+					);
+					push_ts_query(builder, filters);
+					builder.push(") AS rank, ");
+				} else {
+					builder.push("left(s.description, 200) AS snippet, NULL::float4 AS rank, ");
+				}
+				builder.push("a.created_ts FROM avito_ad_search s JOIN avito_ads a ON a.ad_id = s.ad_id");
+				push_filters(builder, filters, SkipFilter::None);
+				builder.push(") ");
+			},
+			|_| {},
+		)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to search ads: {}", e)))
+}
+
+// Ad counts per value of a facet: "status", "feed_id", "account_id" or any tag
+pub async fn count_search_facet(
+	db: &Pool<Postgres>,
+	filters: &AdSearchFilters,
+	facet: &str,
+) -> Result<Vec<AdSearchFacetCount>, ApiError> {
+	let mut builder = QueryBuilder::<Postgres>::new("");
+	let skip = match facet {
+		"status" => {
+			builder.push(
+				"SELECT COALESCE(s.status, '') AS value, COUNT(*) AS count FROM avito_ad_search s",
+			);
+			SkipFilter::Status
+		}
+		"feed_id" => {
+			builder
+				.push("SELECT s.feed_id::text AS value, COUNT(*) AS count FROM avito_ad_search s");
+			SkipFilter::Feed
+		}
+		"account_id" => {
+			builder.push(
+				"SELECT s.account_id::text AS value, COUNT(*) AS count FROM avito_ad_search s",
+			);
+			SkipFilter::Account
+		}
+		tag => {
+			builder.push(
+				"SELECT fv.value AS value, COUNT(DISTINCT s.ad_id) AS count FROM avito_ad_search s JOIN avito_ad_fields ff ON ff.ad_id = s.ad_id AND ff.tag = ",
+			);
+			builder.push_bind(tag);
+			builder.push(" JOIN avito_ad_field_values fv ON fv.field_id = ff.field_id");
+			SkipFilter::Tag(tag)
+		}
+	};
+	push_filters(&mut builder, filters, skip);
+	if let SkipFilter::Tag(_) = skip {
+		builder.push(" AND fv.value IS NOT NULL");
+	}
+	builder.push(" GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT ");
+	builder.push_bind(FACET_LIMIT);
+
+	builder
+		.build_query_as::<AdSearchFacetCount>()
+		.fetch_all(db)
+		.await
+		.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to count facet {}: {}", facet, e))
+		})
+}
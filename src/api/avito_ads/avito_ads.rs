@@ -0,0 +1,225 @@
+use sqlx::{Pool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::ApiError;
+
+// Load tag -> value maps for the given ads from the EAV tables
+pub async fn load_ad_fields(
+	db: &Pool<Postgres>,
+	ad_ids: &[Uuid],
+) -> Result<HashMap<Uuid, HashMap<String, String>>, ApiError> {
+	let mut ads_fields: HashMap<Uuid, HashMap<String, String>> = HashMap::new();
+	if ad_ids.is_empty() {
+		return Ok(ads_fields);
+	}
+
+	let rows = sqlx::query_as::<_, (Uuid, String, Option<String>)>(
+		r#"
+        SELECT f.ad_id, f.tag, v.value
+        FROM avito_ad_fields f
+        LEFT JOIN avito_ad_field_values v ON f.field_id = v.field_id
+        WHERE f.ad_id = ANY($1)
+        ORDER BY f.created_ts, v.created_ts
+        "#,
+	)
+	.bind(ad_ids)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ad fields: {}", e)))?;
+
+	for (ad_id, tag, value) in rows {
+		ads_fields
+			.entry(ad_id)
+			.or_default()
+			.insert(tag, value.unwrap_or_default());
+	}
+
+	Ok(ads_fields)
+}
+
+// Batch insert (ad_id, tag, value) rows into avito_ad_fields / avito_ad_field_values
+pub async fn insert_ad_fields(
+	tx: &mut Transaction<'_, Postgres>,
+	fields: &[(Uuid, String, String)],
+) -> Result<(), ApiError> {
+	if fields.is_empty() {
+		return Ok(());
+	}
+
+	let mut field_ids = Vec::new();
+	let mut field_ad_ids = Vec::new();
+	let mut field_tags = Vec::new();
+	let mut field_data_types = Vec::new();
+	let mut field_field_types = Vec::new();
+
+	let mut field_value_ids = Vec::new();
+	let mut field_values = Vec::new();
+
+	for (ad_id, tag, value) in fields {
+		field_ids.push(Uuid::new_v4());
+		field_ad_ids.push(*ad_id);
+		field_tags.push(tag.clone());
+		field_data_types.push("string".to_string());
+		field_field_types.push("attribute".to_string());
+
+		field_value_ids.push(Uuid::new_v4());
+		field_values.push(value.clone());
+	}
+
+	sqlx::query(
+		r#"
+        INSERT INTO avito_ad_fields (field_id, ad_id, tag, data_type, field_type)
+        SELECT * FROM UNNEST(
+            $1::uuid[],
+            $2::uuid[],
+            $3::varchar[],
+            $4::varchar[],
+            $5::varchar[]
+        )
+        "#,
+	)
+	.bind(&field_ids)
+	.bind(&field_ad_ids)
+	.bind(&field_tags)
+	.bind(&field_data_types)
+	.bind(&field_field_types)
+	.execute(&mut **tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to batch insert fields: {}", e)))?;
+
+	sqlx::query(
+		r#"
+        INSERT INTO avito_ad_field_values (field_value_id, field_id, value)
+        SELECT * FROM UNNEST(
+            $1::uuid[],
+            $2::uuid[],
+            $3::varchar[]
+        )
+        "#,
+	)
+	.bind(&field_value_ids)
+	.bind(&field_ids)
+	.bind(&field_values)
+	.execute(&mut **tx)
+	.await
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to batch insert field values: {}", e))
+	})?;
+
+	Ok(())
+}
+
+// Delete (ad_id, tag) pairs from the EAV tables (values are removed by ON DELETE CASCADE)
+pub async fn delete_ad_fields(
+	tx: &mut Transaction<'_, Postgres>,
+	fields: &[(Uuid, String)],
+) -> Result<(), ApiError> {
+	if fields.is_empty() {
+		return Ok(());
+	}
+
+	let ad_ids: Vec<Uuid> = fields.iter().map(|(ad_id, _)| *ad_id).collect();
+	let tags: Vec<String> = fields.iter().map(|(_, tag)| tag.clone()).collect();
+
+	sqlx::query(
+		r#"
+        DELETE FROM avito_ad_fields f
+        USING UNNEST($1::uuid[], $2::varchar[]) AS d(ad_id, tag)
+        WHERE f.ad_id = d.ad_id AND f.tag = d.tag
+        "#,
+	)
+	.bind(&ad_ids)
+	.bind(&tags)
+	.execute(&mut **tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to delete fields: {}", e)))?;
+
+	Ok(())
+}
+
+pub async fn feed_belongs_to_account(
+	db: &Pool<Postgres>,
+	feed_id: Uuid,
+	account_id: Uuid,
+) -> Result<bool, ApiError> {
+	let row = sqlx::query("SELECT feed_id FROM avito_feeds WHERE feed_id = $1 AND account_id = $2")
+		.bind(feed_id)
+		.bind(account_id)
+		.fetch_optional(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch feed: {}", e)))?;
+
+	Ok(row.is_some())
+}
+
+// Latest MANUAL_CREATE feed of the account, created on first use
+pub async fn get_or_create_manual_feed(
+	tx: &mut Transaction<'_, Postgres>,
+	account_id: Uuid,
+) -> Result<Uuid, ApiError> {
+	let existing = sqlx::query_scalar::<_, Uuid>(
+		r#"
+        SELECT feed_id
+        FROM avito_feeds
+        WHERE account_id = $1 AND category = 'MANUAL_CREATE'
+        ORDER BY created_ts DESC
+        LIMIT 1
+        "#,
+	)
+	.bind(account_id)
+	.fetch_optional(&mut **tx)
+	.await
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to check for existing feed: {}", e))
+	})?;
+
+	if let Some(feed_id) = existing {
+		return Ok(feed_id);
+	}
+
+	let feed_id = Uuid::new_v4();
+	sqlx::query(
+		"INSERT INTO avito_feeds (feed_id, account_id, category) VALUES ($1, $2, 'MANUAL_CREATE')",
+	)
+	.bind(feed_id)
+	.bind(account_id)
+	.execute(&mut **tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to create feed: {}", e)))?;
+
+	Ok(feed_id)
+}
+
+// Insert a new ad with its fields. Like batch_process_ads, the Id tag is stored
+// only as parsed_id and not among the fields.
+pub async fn insert_ad(
+	tx: &mut Transaction<'_, Postgres>,
+	feed_id: Uuid,
+	parsed_id: &str,
+	fields: &HashMap<String, String>,
+) -> Result<Uuid, ApiError> {
+	let ad_id = Uuid::new_v4();
+
+	sqlx::query(
+		r#"
+        INSERT INTO avito_ads (ad_id, feed_id, parsed_id, is_active, status)
+        VALUES ($1, $2, $3, true, 'draft')
+        "#,
+	)
+	.bind(ad_id)
+	.bind(feed_id)
+	.bind(parsed_id)
+	.execute(&mut **tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to create ad: {}", e)))?;
+
+	let rows: Vec<(Uuid, String, String)> = fields
+		.iter()
+		.filter(|(tag, _)| tag.as_str() != "Id")
+		.map(|(tag, value)| (ad_id, tag.clone(), value.clone()))
+		.collect();
+	insert_ad_fields(tx, &rows).await?;
+
+	Ok(ad_id)
+}
@@ -0,0 +1,9 @@
+pub mod ad_duplicates;
+pub mod ad_lifecycle;
+pub mod ad_search;
+pub mod avito_ads;
+
+pub use self::ad_duplicates::*;
+pub use self::ad_lifecycle::*;
+pub use self::ad_search::*;
+pub use self::avito_ads::*;
@@ -0,0 +1,256 @@
+use sqlx::{Pool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{ApiError, AvitoAdImage, AvitoAdImageCheck, AvitoImage, NewAdImageCheck};
+use crate::utils::images::ImageInfo;
+
+#[derive(sqlx::FromRow)]
+struct MirroredSourceRow {
+	source_url: String,
+	#[sqlx(flatten)]
+	image: AvitoImage,
+}
+
+// Already mirrored source URLs with their images
+pub async fn find_mirrored_sources(
+	db: &Pool<Postgres>,
+	urls: &[String],
+) -> Result<HashMap<String, AvitoImage>, ApiError> {
+	if urls.is_empty() {
+		return Ok(HashMap::new());
+	}
+
+	let rows = sqlx::query_as::<_, MirroredSourceRow>(
+		r#"
+        SELECT s.source_url, i.image_id, i.content_hash, i.format, i.width, i.height,
+            i.size_bytes, i.file_name, i.created_ts
+        FROM avito_image_sources s
+        JOIN avito_images i ON i.image_id = s.image_id
+        WHERE s.source_url = ANY($1) AND s.status = 'mirrored'
+        "#,
+	)
+	.bind(urls)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch image sources: {}", e)))?;
+
+	Ok(rows
+		.into_iter()
+		.map(|row| (row.source_url, row.image))
+		.collect())
+}
+
+// Insert image metadata; identical content returns the existing row
+pub async fn insert_image(db: &Pool<Postgres>, info: &ImageInfo) -> Result<AvitoImage, ApiError> {
+	sqlx::query_as::<_, AvitoImage>(
+		r#"
+        INSERT INTO avito_images (content_hash, format, width, height, size_bytes, file_name)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (content_hash) DO UPDATE SET content_hash = EXCLUDED.content_hash
+        RETURNING image_id, content_hash, format, width, height, size_bytes, file_name, created_ts
+        "#,
+	)
+	.bind(&info.content_hash)
+	.bind(&info.format)
+	.bind(info.width as i32)
+	.bind(info.height as i32)
+	.bind(info.size_bytes as i64)
+	.bind(info.file_name())
+	.fetch_one(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to save image: {}", e)))
+}
+
+// Remember the outcome of fetching a URL; failed sources are retried on the next run
+pub async fn upsert_image_source(
+	db: &Pool<Postgres>,
+	source_url: &str,
+	image_id: Option<Uuid>,
+	error: Option<&str>,
+) -> Result<(), ApiError> {
+	let status = if image_id.is_some() {
+		"mirrored"
+	} else {
+		"failed"
+	};
+
+	sqlx::query(
+		r#"
+        INSERT INTO avito_image_sources (source_url, image_id, status, error, fetched_ts)
+        VALUES ($1, $2, $3, $4, NOW())
+        ON CONFLICT (source_url) DO UPDATE
+        SET image_id = EXCLUDED.image_id, status = EXCLUDED.status,
+            error = EXCLUDED.error, fetched_ts = EXCLUDED.fetched_ts
+        "#,
+	)
+	.bind(source_url)
+	.bind(image_id)
+	.bind(status)
+	.bind(error)
+	.execute(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to save image source: {}", e)))?;
+
+	Ok(())
+}
+
+// Replace the image list of ads: (ad_id, position, source_url, image_id)
+pub async fn replace_ad_images(
+	tx: &mut Transaction<'_, Postgres>,
+	ad_ids: &[Uuid],
+	images: &[(Uuid, i32, String, Option<Uuid>)],
+) -> Result<(), ApiError> {
+	if ad_ids.is_empty() {
+		return Ok(());
+	}
+
+	sqlx::query("DELETE FROM avito_ad_images WHERE ad_id = ANY($1)")
+		.bind(ad_ids)
+		.execute(&mut **tx)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to delete ad images: {}", e)))?;
+
+	if images.is_empty() {
+		return Ok(());
+	}
+
+	let image_ad_ids: Vec<Uuid> = images.iter().map(|i| i.0).collect();
+	let positions: Vec<i32> = images.iter().map(|i| i.1).collect();
+	let source_urls: Vec<String> = images.iter().map(|i| i.2.clone()).collect();
+	let image_ids: Vec<Option<Uuid>> = images.iter().map(|i| i.3).collect();
+
+	sqlx::query(
+		r#"
+        INSERT INTO avito_ad_images (ad_id, position, source_url, image_id)
+        SELECT * FROM UNNEST($1::uuid[], $2::int[], $3::text[], $4::uuid[])
+        "#,
+	)
+	.bind(&image_ad_ids)
+	.bind(&positions)
+	.bind(&source_urls)
+	.bind(&image_ids)
+	.execute(&mut **tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to insert ad images: {}", e)))?;
+
+	Ok(())
+}
+
+pub async fn load_ad_images(
+	db: &Pool<Postgres>,
+	ad_id: Uuid,
+) -> Result<Vec<AvitoAdImage>, ApiError> {
+	sqlx::query_as::<_, AvitoAdImage>(
+		r#"
+        SELECT a.position, a.source_url, a.image_id, i.format, i.width, i.height,
+            i.size_bytes, i.file_name
+        FROM avito_ad_images a
+        LEFT JOIN avito_images i ON i.image_id = a.image_id
+        WHERE a.ad_id = $1
+        ORDER BY a.position
+        "#,
+	)
+	.bind(ad_id)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ad images: {}", e)))
+}
+
+// Perceptual hashes of already checked images in the given feeds: (feed_id, ad_id, position, phash)
+pub async fn load_feed_image_hashes(
+	db: &Pool<Postgres>,
+	feed_ids: &[Uuid],
+) -> Result<Vec<(Uuid, Uuid, i32, i64)>, ApiError> {
+	sqlx::query_as::<_, (Uuid, Uuid, i32, i64)>(
+		r#"
+        SELECT a.feed_id, c.ad_id, c.position, c.phash
+        FROM avito_ad_image_checks c
+        JOIN avito_ads a ON a.ad_id = c.ad_id
+        WHERE a.feed_id = ANY($1) AND c.phash IS NOT NULL
+        "#,
+	)
+	.bind(feed_ids)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch image hashes: {}", e)))
+}
+
+// Replace check results of the given ads
+pub async fn replace_ad_image_checks(
+	db: &Pool<Postgres>,
+	ad_ids: &[Uuid],
+	checks: &[NewAdImageCheck],
+) -> Result<(), ApiError> {
+	let mut tx = db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	sqlx::query("DELETE FROM avito_ad_image_checks WHERE ad_id = ANY($1)")
+		.bind(ad_ids)
+		.execute(&mut *tx)
+		.await
+		.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to delete image checks: {}", e))
+		})?;
+
+	if !checks.is_empty() {
+		sqlx::query(
+			r#"
+            INSERT INTO avito_ad_image_checks
+                (ad_id, position, url, status, issues, format, width, height, size_bytes, phash)
+            SELECT * FROM UNNEST(
+                $1::uuid[],
+                $2::int[],
+                $3::text[],
+                $4::varchar[],
+                $5::jsonb[],
+                $6::varchar[],
+                $7::int[],
+                $8::int[],
+                $9::bigint[],
+                $10::bigint[]
+            )
+            "#,
+		)
+		.bind(checks.iter().map(|c| c.ad_id).collect::<Vec<_>>())
+		.bind(checks.iter().map(|c| c.position).collect::<Vec<_>>())
+		.bind(checks.iter().map(|c| c.url.clone()).collect::<Vec<_>>())
+		.bind(checks.iter().map(|c| c.status.clone()).collect::<Vec<_>>())
+		.bind(checks.iter().map(|c| c.issues.clone()).collect::<Vec<_>>())
+		.bind(checks.iter().map(|c| c.format.clone()).collect::<Vec<_>>())
+		.bind(checks.iter().map(|c| c.width).collect::<Vec<_>>())
+		.bind(checks.iter().map(|c| c.height).collect::<Vec<_>>())
+		.bind(checks.iter().map(|c| c.size_bytes).collect::<Vec<_>>())
+		.bind(checks.iter().map(|c| c.phash).collect::<Vec<_>>())
+		.execute(&mut *tx)
+		.await
+		.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to insert image checks: {}", e))
+		})?;
+	}
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	Ok(())
+}
+
+pub async fn load_ad_image_checks(
+	db: &Pool<Postgres>,
+	ad_id: Uuid,
+) -> Result<Vec<AvitoAdImageCheck>, ApiError> {
+	sqlx::query_as::<_, AvitoAdImageCheck>(
+		r#"
+        SELECT position, url, status, issues, format, width, height, size_bytes, checked_ts
+        FROM avito_ad_image_checks
+        WHERE ad_id = $1
+        ORDER BY position
+        "#,
+	)
+	.bind(ad_id)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch image checks: {}", e)))
+}
@@ -0,0 +1,3 @@
+pub mod avito_images;
+
+pub use self::avito_images::*;
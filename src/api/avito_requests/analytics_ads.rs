@@ -0,0 +1,49 @@
+use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::api::avito_requests::OWN_AD_CONDITION;
+use crate::models::{AdRecord, ApiError};
+
+// Columns of an AdRecord; NUMERIC values are read as float8
+pub const AD_RECORD_COLUMNS: &str = "ad_id, my_ad, run_date, city_query, search_query, position, views, views_today,
+	promotion, delivery, ad_date, avito_ad_id, title, price, link, categories,
+	seller_id, seller_name, seller_type, register_date, answer_time,
+	rating, reviews_count, ads_count, closed_ads_count, photo_count,
+	address, description, avito_request_id, created_ts,
+	views_value, views_today_value, price_value::float8 AS price_value, rating_value::float8 AS rating_value,
+	reviews_count_value, ads_count_value, photo_count_value, ad_ts";
+
+// All crawled ads of one run, by position
+pub async fn load_request_ads(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+) -> Result<Vec<AdRecord>, ApiError> {
+	sqlx::query_as::<_, AdRecord>(&format!(
+		"SELECT {} FROM avito_analytics_ads WHERE avito_request_id = $1 ORDER BY position, ad_id",
+		AD_RECORD_COLUMNS
+	))
+	.bind(request_id)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch crawled ads: {}", e)))
+}
+
+// Avito ids the crawler marked as our own in any of the runs
+pub async fn load_own_ad_ids(
+	db: &Pool<Postgres>,
+	request_ids: &[Uuid],
+) -> Result<HashSet<String>, ApiError> {
+	let query = format!(
+		"SELECT DISTINCT a.avito_ad_id FROM avito_analytics_ads a WHERE a.avito_request_id = ANY($1) AND {}",
+		OWN_AD_CONDITION
+	);
+
+	Ok(sqlx::query_scalar::<_, String>(&query)
+		.bind(request_ids)
+		.fetch_all(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch own ads: {}", e)))?
+		.into_iter()
+		.collect())
+}
@@ -1,29 +1,132 @@
 use sqlx::{Pool, Postgres};
 use uuid::Uuid;
 
-use crate::{api::CustomError, models::AvitoRequest};
+use crate::{
+	api::{
+		avito_requests::sync_request_sellers,
+		shared::{Keyset, Page, PageRequest, SortKey},
+		CustomError,
+	},
+	models::{AvitoRequest, CrawlProgress, CrawlStatus},
+};
+
+const AVITO_REQUESTS: Keyset = Keyset {
+	from: "avito_requests",
+	id_expr: "request_id",
+	id_type: "uuid",
+	sort_keys: &[
+		SortKey {
+			name: "created_ts",
+			expr: "COALESCE(created_ts, 'epoch'::timestamptz)",
+			sql_type: "timestamptz",
+		},
+		SortKey {
+			name: "request",
+			expr: "COALESCE(request, '')",
+			sql_type: "text",
+		},
+		SortKey {
+			name: "city",
+			expr: "COALESCE(city, '')",
+			sql_type: "text",
+		},
+	],
+	default_descending: true,
+	default_limit: 10,
+	max_limit: 100,
+};
+
+pub static AVITO_REQUESTS_KEYSET: Keyset = AVITO_REQUESTS;
+
+// The admin listing of one user's requests has always been oldest first
+pub static USER_AVITO_REQUESTS_KEYSET: Keyset = Keyset {
+	default_descending: false,
+	..AVITO_REQUESTS
+};
+
+// A user's own requests, newest first, 20 per page by default
+pub static OWN_AVITO_REQUESTS_KEYSET: Keyset = Keyset {
+	default_limit: 20,
+	..AVITO_REQUESTS
+};
 
 impl AvitoRequest {
 	pub async fn get_avito_requests_by_user(
 		db: &Pool<Postgres>,
 		user_id: &Uuid,
-		limit: i64,
-		offset: i64,
-	) -> Result<Vec<Self>, CustomError> {
-		let avito_requests_query_result = sqlx::query_as!(
-			AvitoRequest,
-			"SELECT * FROM avito_requests WHERE user_id = $1 ORDER by created_ts LIMIT $2 OFFSET $3",
-			&user_id,
-			&limit,
-			&offset
-		)
-		.fetch_all(db)
-		.await;
+		request: &PageRequest,
+	) -> Result<Page<Self>, CustomError> {
+		let user_id = *user_id;
+		let avito_requests_query_result = AVITO_REQUESTS_KEYSET
+			.fetch::<AvitoRequest, _>(db, request, "*", |builder| {
+				builder.push(" AND user_id = ");
+				builder.push_bind(user_id);
+			})
+			.await;
 
 		if avito_requests_query_result.is_err() {
 			println!("Что-то пошло не так во время запроса get_avito_requests");
 		}
 
-		Ok(avito_requests_query_result.unwrap_or(Vec::new()))
+		Ok(avito_requests_query_result?)
+	}
+
+	pub async fn get_avito_request_by_id(
+		db: &Pool<Postgres>,
+		request_id: Uuid,
+	) -> Result<Option<Self>, CustomError> {
+		Ok(
+			sqlx::query_as::<_, AvitoRequest>("SELECT * FROM avito_requests WHERE request_id = $1")
+				.bind(request_id)
+				.fetch_optional(db)
+				.await?,
+		)
+	}
+
+	// Record a crawler progress message; finished jobs are left as they are.
+	// Returns the new status, or None when the job was unknown or already finished
+	pub async fn apply_crawl_progress(
+		db: &Pool<Postgres>,
+		progress: &CrawlProgress,
+	) -> Result<Option<CrawlStatus>, CustomError> {
+		let status = sqlx::query_scalar::<_, String>(
+			r#"
+            UPDATE avito_requests
+            SET status = $2,
+                started_ts = CASE WHEN $2 <> 'queued' THEN COALESCE(started_ts, NOW()) ELSE started_ts END,
+                finished_ts = CASE WHEN $2 IN ('done', 'failed', 'cancelled') THEN NOW() ELSE finished_ts END,
+                progress_current = COALESCE($3, progress_current),
+                progress_total = COALESCE($4, progress_total),
+                ads_count = COALESCE(
+                    $5,
+                    CASE WHEN $2 = 'done' THEN (
+                        SELECT COUNT(*)::int FROM avito_analytics_ads WHERE avito_request_id = $1
+                    ) END,
+                    ads_count
+                ),
+                last_error = CASE WHEN $2 = 'failed' THEN COALESCE($6, 'Crawl failed') ELSE last_error END,
+                updated_ts = NOW()
+            WHERE request_id = $1 AND status IN ('queued', 'running')
+            RETURNING status
+            "#,
+		)
+		.bind(progress.request_id)
+		.bind(progress.status.as_str())
+		.bind(progress.progress_current)
+		.bind(progress.progress_total)
+		.bind(progress.ads_count)
+		.bind(&progress.error)
+		.fetch_optional(db)
+		.await?;
+		let status = status.as_deref().and_then(CrawlStatus::parse);
+
+		// Seller profiles are taken from whatever the run found, even when it failed midway
+		if status.is_some_and(|status| status.is_finished()) {
+			if let Err(e) = sync_request_sellers(db, progress.request_id).await {
+				eprintln!("Failed to sync sellers of {}: {:?}", progress.request_id, e);
+			}
+		}
+
+		Ok(status)
 	}
 }
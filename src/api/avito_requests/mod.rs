@@ -1 +1,13 @@
+pub mod analytics_ads;
 pub mod avito_requests;
+pub mod positions;
+pub mod price_stats;
+pub mod promotions;
+pub mod sellers;
+
+pub use self::analytics_ads::*;
+pub use self::avito_requests::*;
+pub use self::positions::*;
+pub use self::price_stats::*;
+pub use self::promotions::*;
+pub use self::sellers::*;
@@ -0,0 +1,121 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::models::{ApiError, OwnAdPosition, PositionRun};
+
+// The crawler marks our own ads in my_ad with a yes-like value
+pub const OWN_AD_CONDITION: &str =
+	"lower(trim(a.my_ad)) IN ('1', 'true', 't', 'yes', 'y', 'да', '+')";
+
+// CTEs `base`, `same_search` and `runs`: every run of the same search as request $1, and the
+// latest $2 of them with results. Runs of one saved search match by schedule, one-off runs
+// by query, city, radius and district.
+pub const SAME_SEARCH_RUNS: &str = r#"base AS (
+    SELECT user_id, schedule_id, request, city, radius, district
+    FROM avito_requests
+    WHERE request_id = $1
+),
+same_search AS (
+    SELECT r.request_id, r.created_ts, r.status
+    FROM avito_requests r, base b
+    WHERE r.user_id = b.user_id
+        AND (
+            r.schedule_id = b.schedule_id
+            OR (
+                lower(trim(COALESCE(r.request, ''))) = lower(trim(COALESCE(b.request, '')))
+                AND lower(trim(COALESCE(r.city, ''))) = lower(trim(COALESCE(b.city, '')))
+                AND COALESCE(r.radius, '') = COALESCE(b.radius, '')
+                AND COALESCE(r.district, '') = COALESCE(b.district, '')
+            )
+        )
+),
+runs AS (
+    SELECT r.request_id, r.created_ts, r.status
+    FROM same_search r
+    WHERE EXISTS (SELECT 1 FROM avito_analytics_ads a WHERE a.avito_request_id = r.request_id)
+    ORDER BY COALESCE(r.created_ts, 'epoch'::timestamptz) DESC, r.request_id DESC
+    LIMIT $2
+)"#;
+
+// Whether `other_id` is a run of the same search as `request_id`, with or without results
+pub async fn is_same_search(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+	other_id: Uuid,
+) -> Result<bool, ApiError> {
+	let query = format!(
+		"WITH {runs} SELECT EXISTS (SELECT 1 FROM same_search WHERE request_id = $3)",
+		runs = SAME_SEARCH_RUNS
+	);
+
+	sqlx::query_scalar::<_, bool>(&query)
+		.bind(request_id)
+		.bind(None::<i64>)
+		.bind(other_id)
+		.fetch_one(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to compare runs: {}", e)))
+}
+
+// Latest `limit` runs with results of the same search as `request_id`, oldest first
+pub async fn load_position_runs(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+	limit: i64,
+	top: i32,
+) -> Result<Vec<PositionRun>, ApiError> {
+	let query = format!(
+		r#"
+        WITH {runs},
+        counts AS (
+            SELECT a.avito_request_id AS request_id,
+                COUNT(*) AS ads_total,
+                COUNT(*) FILTER (WHERE a.position <= $3) AS top_slots,
+                COUNT(*) FILTER (WHERE a.position <= $3 AND {own}) AS own_top_slots,
+                COUNT(DISTINCT a.avito_ad_id) FILTER (WHERE {own}) AS own_ads
+            FROM avito_analytics_ads a
+            WHERE a.avito_request_id IN (SELECT request_id FROM runs)
+            GROUP BY a.avito_request_id
+        )
+        SELECT runs.request_id, runs.created_ts, runs.status,
+            counts.ads_total, counts.top_slots, counts.own_top_slots, counts.own_ads,
+            counts.own_top_slots::float8 / NULLIF(counts.top_slots, 0) AS top_share
+        FROM runs
+        JOIN counts ON counts.request_id = runs.request_id
+        ORDER BY COALESCE(runs.created_ts, 'epoch'::timestamptz), runs.request_id
+        "#,
+		runs = SAME_SEARCH_RUNS,
+		own = OWN_AD_CONDITION
+	);
+
+	sqlx::query_as::<_, PositionRun>(&query)
+		.bind(request_id)
+		.bind(limit)
+		.bind(top)
+		.fetch_all(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch crawl runs: {}", e)))
+}
+
+// Best position of each of our ads in each of the runs
+pub async fn load_own_ad_positions(
+	db: &Pool<Postgres>,
+	request_ids: &[Uuid],
+) -> Result<Vec<OwnAdPosition>, ApiError> {
+	let query = format!(
+		r#"
+        SELECT DISTINCT ON (a.avito_request_id, a.avito_ad_id)
+            a.avito_request_id AS request_id, a.avito_ad_id, a.title, a.link, a.position
+        FROM avito_analytics_ads a
+        WHERE a.avito_request_id = ANY($1) AND {own}
+        ORDER BY a.avito_request_id, a.avito_ad_id, a.position
+        "#,
+		own = OWN_AD_CONDITION
+	);
+
+	sqlx::query_as::<_, OwnAdPosition>(&query)
+		.bind(request_ids)
+		.fetch_all(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ad positions: {}", e)))
+}
@@ -0,0 +1,213 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::api::avito_requests::SAME_SEARCH_RUNS;
+use crate::models::{
+	ApiError, MarketPriceStats, PriceBucket, PriceStats, PriceStatsGroup, PriceStatsRun,
+};
+
+// Ads of the runs in `{runs_filter}`, one row per Avito ad with its best position;
+// zero prices are "price on request" and are left out of the aggregates
+const RUN_ADS: &str = r#"
+    SELECT DISTINCT ON (avito_request_id, COALESCE(NULLIF(avito_ad_id, ''), ad_id::text))
+        avito_request_id, seller_type, promotion, delivery,
+        CASE WHEN price_value > 0 THEN price_value::float8 END AS price
+    FROM avito_analytics_ads
+    WHERE {runs_filter}
+    ORDER BY avito_request_id, COALESCE(NULLIF(avito_ad_id, ''), ad_id::text), position
+"#;
+
+const PRICE_STATS_COLUMNS: &str = r#"
+    COUNT(*) AS ads_count,
+    COUNT(price) AS priced_count,
+    MIN(price) AS min_price,
+    round(percentile_cont(0.1) WITHIN GROUP (ORDER BY price)::numeric, 2)::float8 AS p10,
+    round(percentile_cont(0.25) WITHIN GROUP (ORDER BY price)::numeric, 2)::float8 AS p25,
+    round(percentile_cont(0.5) WITHIN GROUP (ORDER BY price)::numeric, 2)::float8 AS median,
+    round(percentile_cont(0.75) WITHIN GROUP (ORDER BY price)::numeric, 2)::float8 AS p75,
+    round(percentile_cont(0.9) WITHIN GROUP (ORDER BY price)::numeric, 2)::float8 AS p90,
+    round(AVG(price)::numeric, 2)::float8 AS mean_price,
+    MAX(price) AS max_price
+"#;
+
+const SELLER_TYPE_GROUP: &str = "COALESCE(NULLIF(trim(seller_type), ''), 'unknown')";
+const PROMOTION_GROUP: &str = "CASE WHEN trim(promotion) = '' THEN 'regular' ELSE 'promoted' END";
+const DELIVERY_GROUP: &str = "CASE WHEN lower(trim(delivery)) IN ('', '0', 'false', 'no', 'нет') \
+	THEN 'without_delivery' ELSE 'with_delivery' END";
+
+#[derive(sqlx::FromRow)]
+struct PriceBucketRow {
+	bucket: i32,
+	count: i64,
+	low: f64,
+	high: f64,
+	min_price: f64,
+	max_price: f64,
+}
+
+// Whole-run aggregates plus the breakdowns, computed in one pass with grouping sets
+async fn load_price_groups(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+) -> Result<Vec<PriceStatsGroup>, ApiError> {
+	let query = format!(
+		r#"
+        WITH ads AS ({ads})
+        SELECT
+            CASE
+                WHEN GROUPING({seller}) = 0 THEN 'seller_type'
+                WHEN GROUPING({promotion}) = 0 THEN 'promotion'
+                WHEN GROUPING({delivery}) = 0 THEN 'delivery'
+                ELSE 'all'
+            END AS dimension,
+            COALESCE({seller}, {promotion}, {delivery}, 'all') AS "group",
+            {columns}
+        FROM ads
+        GROUP BY GROUPING SETS ((), ({seller}), ({promotion}), ({delivery}))
+        ORDER BY dimension, COUNT(price) DESC, "group"
+        "#,
+		ads = RUN_ADS.replace("{runs_filter}", "avito_request_id = $1"),
+		seller = SELLER_TYPE_GROUP,
+		promotion = PROMOTION_GROUP,
+		delivery = DELIVERY_GROUP,
+		columns = PRICE_STATS_COLUMNS
+	);
+
+	sqlx::query_as::<_, PriceStatsGroup>(&query)
+		.bind(request_id)
+		.fetch_all(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch price stats: {}", e)))
+}
+
+// Equal-width buckets between the 5th and 95th percentile, outliers clamped into the outer ones
+async fn load_price_buckets(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+	buckets: i32,
+) -> Result<Vec<PriceBucket>, ApiError> {
+	let query = format!(
+		r#"
+        WITH ads AS ({ads}),
+        bounds AS (
+            SELECT percentile_cont(0.05) WITHIN GROUP (ORDER BY price) AS low,
+                percentile_cont(0.95) WITHIN GROUP (ORDER BY price) AS high,
+                MIN(price) AS min_price,
+                MAX(price) AS max_price
+            FROM ads
+        )
+        SELECT
+            CASE
+                WHEN b.high <= b.low THEN 1
+                ELSE LEAST(GREATEST(width_bucket(a.price, b.low, b.high, $2), 1), $2)
+            END AS bucket,
+            COUNT(*) AS count,
+            b.low, b.high, b.min_price, b.max_price
+        FROM ads a, bounds b
+        WHERE a.price IS NOT NULL
+        GROUP BY 1, b.low, b.high, b.min_price, b.max_price
+        ORDER BY 1
+        "#,
+		ads = RUN_ADS.replace("{runs_filter}", "avito_request_id = $1")
+	);
+
+	let rows = sqlx::query_as::<_, PriceBucketRow>(&query)
+		.bind(request_id)
+		.bind(buckets)
+		.fetch_all(db)
+		.await
+		.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to fetch price buckets: {}", e))
+		})?;
+
+	let Some(first) = rows.first() else {
+		return Ok(Vec::new());
+	};
+	if first.high <= first.low {
+		return Ok(vec![PriceBucket {
+			from_price: first.min_price,
+			to_price: first.max_price,
+			count: first.count,
+		}]);
+	}
+
+	let width = (first.high - first.low) / buckets as f64;
+	let round = |value: f64| (value * 100.0).round() / 100.0;
+	Ok((1..=buckets)
+		.map(|bucket| PriceBucket {
+			from_price: if bucket == 1 {
+				first.min_price
+			} else {
+				round(first.low + width * (bucket - 1) as f64)
+			},
+			to_price: if bucket == buckets {
+				first.max_price
+			} else {
+				round(first.low + width * bucket as f64)
+			},
+			count: rows
+				.iter()
+				.find(|row| row.bucket == bucket)
+				.map_or(0, |row| row.count),
+		})
+		.collect())
+}
+
+pub async fn load_market_price_stats(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+	buckets: i32,
+) -> Result<MarketPriceStats, ApiError> {
+	let groups = load_price_groups(db, request_id).await?;
+	let breakdown = |dimension: &str| -> Vec<PriceStatsGroup> {
+		groups
+			.iter()
+			.filter(|group| group.dimension == dimension)
+			.cloned()
+			.collect()
+	};
+
+	Ok(MarketPriceStats {
+		overall: groups
+			.iter()
+			.find(|group| group.dimension == "all")
+			.map(|group| group.stats.clone())
+			.unwrap_or_default(),
+		buckets: load_price_buckets(db, request_id, buckets).await?,
+		by_seller_type: breakdown("seller_type"),
+		by_promotion: breakdown("promotion"),
+		by_delivery: breakdown("delivery"),
+	})
+}
+
+// Overall price aggregates of the latest `limit` runs of the same search, oldest first
+pub async fn load_price_history(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+	limit: i64,
+) -> Result<Vec<PriceStatsRun>, ApiError> {
+	let query = format!(
+		r#"
+        WITH {runs},
+        ads AS ({ads})
+        SELECT runs.request_id, runs.created_ts, runs.status, {columns}
+        FROM runs
+        JOIN ads ON ads.avito_request_id = runs.request_id
+        GROUP BY runs.request_id, runs.created_ts, runs.status
+        ORDER BY COALESCE(runs.created_ts, 'epoch'::timestamptz), runs.request_id
+        "#,
+		runs = SAME_SEARCH_RUNS,
+		ads = RUN_ADS.replace(
+			"{runs_filter}",
+			"avito_request_id IN (SELECT request_id FROM runs)"
+		),
+		columns = PRICE_STATS_COLUMNS
+	);
+
+	sqlx::query_as::<_, PriceStatsRun>(&query)
+		.bind(request_id)
+		.bind(limit)
+		.fetch_all(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch price history: {}", e)))
+}
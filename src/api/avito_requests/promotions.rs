@@ -0,0 +1,93 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::api::avito_requests::{OWN_AD_CONDITION, SAME_SEARCH_RUNS};
+use crate::models::{ApiError, PromotionRun, PromotionTypeStats};
+
+// The crawler leaves promotion empty for ads without paid services
+pub const PROMOTED_CONDITION: &str = "trim(a.promotion) <> ''";
+
+// Promotion usage in the latest `limit` runs of the same search as `request_id`, oldest first
+pub async fn load_promotion_runs(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+	limit: i64,
+	top: i32,
+) -> Result<Vec<PromotionRun>, ApiError> {
+	let query = format!(
+		r#"
+        WITH {runs},
+        counts AS (
+            SELECT a.avito_request_id AS request_id,
+                COUNT(*) AS ads_total,
+                COUNT(*) FILTER (WHERE {promoted}) AS promoted_ads,
+                COUNT(*) FILTER (WHERE a.position <= $3) AS top_slots,
+                COUNT(*) FILTER (WHERE a.position <= $3 AND {promoted}) AS promoted_top_slots,
+                round(AVG(a.position) FILTER (WHERE {promoted}), 1)::float8 AS avg_position_promoted,
+                round(AVG(a.position) FILTER (WHERE NOT {promoted}), 1)::float8 AS avg_position_regular,
+                round(corr(a.position, CASE WHEN {promoted} THEN 1 ELSE 0 END)::numeric, 3)::float8
+                    AS position_correlation,
+                COUNT(*) FILTER (WHERE {promoted} AND {own}) AS own_promoted_ads
+            FROM avito_analytics_ads a
+            WHERE a.avito_request_id IN (SELECT request_id FROM runs)
+            GROUP BY a.avito_request_id
+        )
+        SELECT runs.request_id, runs.created_ts, runs.status,
+            counts.ads_total, counts.promoted_ads,
+            counts.promoted_ads::float8 / NULLIF(counts.ads_total, 0) AS promoted_share,
+            counts.top_slots, counts.promoted_top_slots,
+            counts.promoted_top_slots::float8 / NULLIF(counts.top_slots, 0) AS promoted_top_share,
+            counts.avg_position_promoted, counts.avg_position_regular,
+            counts.position_correlation, counts.own_promoted_ads
+        FROM runs
+        JOIN counts ON counts.request_id = runs.request_id
+        ORDER BY COALESCE(runs.created_ts, 'epoch'::timestamptz), runs.request_id
+        "#,
+		runs = SAME_SEARCH_RUNS,
+		promoted = PROMOTED_CONDITION,
+		own = OWN_AD_CONDITION
+	);
+
+	sqlx::query_as::<_, PromotionRun>(&query)
+		.bind(request_id)
+		.bind(limit)
+		.bind(top)
+		.fetch_all(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch crawl runs: {}", e)))
+}
+
+// Positions held by each kind of promotion in one run, best placed first
+pub async fn load_promotion_types(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+	top: i32,
+) -> Result<Vec<PromotionTypeStats>, ApiError> {
+	let query = format!(
+		r#"
+        SELECT COALESCE(NULLIF(lower(trim(a.promotion)), ''), 'none') AS promotion,
+            COUNT(*) AS ads,
+            MIN(a.position) AS best_position,
+            round(AVG(a.position), 1)::float8 AS avg_position,
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY a.position) AS median_position,
+            COUNT(*) FILTER (WHERE a.position <= $2) AS top_slots,
+            COUNT(*) FILTER (WHERE a.position <= $2)::float8
+                / NULLIF(SUM(COUNT(*) FILTER (WHERE a.position <= $2)) OVER (), 0) AS top_share,
+            COUNT(*) FILTER (WHERE {own}) AS own_ads
+        FROM avito_analytics_ads a
+        WHERE a.avito_request_id = $1
+        GROUP BY 1
+        ORDER BY avg_position, promotion
+        "#,
+		own = OWN_AD_CONDITION
+	);
+
+	sqlx::query_as::<_, PromotionTypeStats>(&query)
+		.bind(request_id)
+		.bind(top)
+		.fetch_all(db)
+		.await
+		.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to fetch promotion stats: {}", e))
+		})
+}
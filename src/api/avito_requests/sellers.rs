@@ -0,0 +1,96 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::api::avito_requests::SAME_SEARCH_RUNS;
+use crate::models::{ApiError, Competitor, CompetitorProfile, SellerSnapshot};
+
+// Upserts the sellers found by a run and their snapshots for it; returns the number of sellers
+pub async fn sync_request_sellers(db: &Pool<Postgres>, request_id: Uuid) -> Result<i32, ApiError> {
+	sqlx::query_scalar::<_, i32>("SELECT avito_sync_request_sellers($1)")
+		.bind(request_id)
+		.fetch_one(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to sync sellers: {}", e)))
+}
+
+// Sellers of a run other than us, by presence in the results, with their rating trend
+// over the latest `runs` runs of the same search
+pub async fn load_competitors(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+	limit: i64,
+	runs: i64,
+) -> Result<Vec<CompetitorProfile>, ApiError> {
+	let competitors = sqlx::query_as::<_, Competitor>(
+		r#"
+        SELECT s.seller_id, s.seller_name, p.seller_type, p.register_date,
+            s.ads_in_run, s.promoted_ads,
+            s.promoted_ads::float8 / NULLIF(s.ads_in_run, 0) AS promotion_share,
+            s.avg_price::float8 AS avg_price, s.best_position,
+            s.rating::float8 AS rating, s.reviews_count, s.ads_count, s.closed_ads_count,
+            p.first_seen_ts
+        FROM avito_seller_snapshots s
+        JOIN avito_sellers p ON p.seller_id = s.seller_id
+        WHERE s.avito_request_id = $1 AND s.own_ads = 0
+        ORDER BY s.ads_in_run DESC, s.best_position, s.seller_id
+        LIMIT $2
+        "#,
+	)
+	.bind(request_id)
+	.bind(limit)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch competitors: {}", e)))?;
+
+	let seller_ids: Vec<String> = competitors
+		.iter()
+		.map(|competitor| competitor.seller_id.clone())
+		.collect();
+	let query = format!(
+		r#"
+        WITH {runs}
+        SELECT s.seller_id, runs.request_id, runs.created_ts,
+            s.rating::float8 AS rating, s.reviews_count, s.ads_in_run, s.avg_price::float8 AS avg_price
+        FROM runs
+        JOIN avito_seller_snapshots s ON s.avito_request_id = runs.request_id
+        WHERE s.seller_id = ANY($3)
+        ORDER BY COALESCE(runs.created_ts, 'epoch'::timestamptz), runs.request_id
+        "#,
+		runs = SAME_SEARCH_RUNS
+	);
+	let snapshots = sqlx::query_as::<_, SellerSnapshot>(&query)
+		.bind(request_id)
+		.bind(runs)
+		.bind(&seller_ids)
+		.fetch_all(db)
+		.await
+		.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to fetch seller history: {}", e))
+		})?;
+
+	Ok(competitors
+		.into_iter()
+		.map(|seller| {
+			let trend: Vec<SellerSnapshot> = snapshots
+				.iter()
+				.filter(|snapshot| snapshot.seller_id == seller.seller_id)
+				.cloned()
+				.collect();
+			let ratings: Vec<f64> = trend
+				.iter()
+				.filter_map(|snapshot| snapshot.rating)
+				.collect();
+			let rating_change = match (ratings.first(), ratings.last()) {
+				(Some(first), Some(last)) if ratings.len() > 1 => {
+					Some(((last - first) * 100.0).round() / 100.0)
+				}
+				_ => None,
+			};
+			CompetitorProfile {
+				seller,
+				rating_change,
+				trend,
+			}
+		})
+		.collect())
+}
@@ -1,3 +1,5 @@
+pub mod avito_ads;
+pub mod avito_images;
 pub mod avito_requests;
 pub mod error;
 pub mod shared;
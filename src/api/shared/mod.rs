@@ -1 +1,3 @@
-pub mod shared;
+pub mod pagination;
+
+pub use self::pagination::*;
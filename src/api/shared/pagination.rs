@@ -0,0 +1,462 @@
+use serde_json::json;
+use sqlx::{postgres::PgRow, FromRow, Pool, Postgres, QueryBuilder, Row};
+
+use crate::{
+	models::{FilterOptions, PageCursor},
+	utils::cursor::{decode_cursor, encode_cursor},
+};
+
+// A column or expression a listing can be sorted by; it must never be NULL,
+// so nullable columns are wrapped in COALESCE
+pub struct SortKey {
+	pub name: &'static str,
+	pub expr: &'static str,
+	/// Postgres type the cursor text is cast back to
+	pub sql_type: &'static str,
+}
+
+// Keyset-paginated listing: rows are ordered by (sort key, id) and each page
+// continues strictly after the last row of the previous one
+pub struct Keyset {
+	pub from: &'static str,
+	/// Unique column that breaks ties between equal sort keys
+	pub id_expr: &'static str,
+	pub id_type: &'static str,
+	/// The first key is the default sort
+	pub sort_keys: &'static [SortKey],
+	pub default_descending: bool,
+	pub default_limit: usize,
+	pub max_limit: usize,
+}
+
+pub struct PageRequest {
+	sort: &'static SortKey,
+	descending: bool,
+	after: Option<(String, String)>,
+	page: Option<usize>,
+	limit: usize,
+	with_total: bool,
+}
+
+pub struct Page<T> {
+	pub items: Vec<T>,
+	pub next_cursor: Option<String>,
+	pub total: Option<i64>,
+	sort: &'static str,
+	descending: bool,
+	page: Option<usize>,
+	limit: usize,
+}
+
+struct Keyed<T> {
+	item: T,
+	sort_key: String,
+	row_id: String,
+}
+
+impl<'r, T: FromRow<'r, PgRow>> FromRow<'r, PgRow> for Keyed<T> {
+	fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+		Ok(Self {
+			item: T::from_row(row)?,
+			sort_key: row.try_get("page_sort_key")?,
+			row_id: row.try_get("page_row_id")?,
+		})
+	}
+}
+
+impl Keyset {
+	fn sort_key(&self, name: &str) -> Option<&'static SortKey> {
+		self.sort_keys.iter().find(|key| key.name == name)
+	}
+
+	// Validate query options; the error is a message for the client
+	pub fn request(&'static self, opts: &FilterOptions) -> Result<PageRequest, String> {
+		let limit = opts
+			.limit
+			.unwrap_or(self.default_limit)
+			.clamp(1, self.max_limit);
+
+		if let Some(cursor) = &opts.cursor {
+			let position = decode_cursor::<PageCursor>(cursor).ok_or("Invalid cursor")?;
+			let sort = self.sort_key(&position.sort).ok_or("Invalid cursor")?;
+			// A key that doesn't cast back would fail inside Postgres instead
+			if !fits_sql_type(&position.key, sort.sql_type)
+				|| !fits_sql_type(&position.id, self.id_type)
+			{
+				return Err("Invalid cursor".to_string());
+			}
+			return Ok(PageRequest {
+				sort,
+				descending: position.descending,
+				after: Some((position.key, position.id)),
+				page: None,
+				limit,
+				with_total: opts.with_total.unwrap_or(false),
+			});
+		}
+
+		let sort = match &opts.sort {
+			Some(name) => self.sort_key(name).ok_or_else(|| {
+				let names: Vec<&str> = self.sort_keys.iter().map(|key| key.name).collect();
+				format!(
+					"Unknown sort '{}', expected one of: {}",
+					name,
+					names.join(", ")
+				)
+			})?,
+			None => &self.sort_keys[0],
+		};
+		let descending = match opts.order.as_deref() {
+			Some("asc") => false,
+			Some("desc") => true,
+			Some(order) => return Err(format!("Unknown order '{}', expected asc or desc", order)),
+			None => std::ptr::eq(sort, &self.sort_keys[0]) && self.default_descending,
+		};
+
+		Ok(PageRequest {
+			sort,
+			descending,
+			after: None,
+			// Without a cursor the listing is paged as before: page 1 with a total
+			page: Some(opts.page.unwrap_or(1)),
+			limit,
+			with_total: opts.with_total.unwrap_or(true),
+		})
+	}
+
+	// Fetch one page of `columns`; `filters` appends " AND ..." conditions and is
+	// applied to both the page and the optional total
+	pub async fn fetch<'args, T, F>(
+		&self,
+		db: &Pool<Postgres>,
+		request: &PageRequest,
+		columns: &str,
+		filters: F,
+	) -> Result<Page<T>, sqlx::Error>
+	where
+		T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+		F: Fn(&mut QueryBuilder<'args, Postgres>),
+	{
+		self.fetch_with(db, request, columns, |_| {}, filters).await
+	}
+
+	// Same as fetch, with `with` pushing a "WITH ... " prefix before both queries, for
+	// listings whose `from` is a CTE that needs bound values (e.g. a search query)
+	pub async fn fetch_with<'args, T, W, F>(
+		&self,
+		db: &Pool<Postgres>,
+		request: &PageRequest,
+		columns: &str,
+		with: W,
+		filters: F,
+	) -> Result<Page<T>, sqlx::Error>
+	where
+		T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+		W: Fn(&mut QueryBuilder<'args, Postgres>),
+		F: Fn(&mut QueryBuilder<'args, Postgres>),
+	{
+		let sort = request.sort;
+		let direction = if request.descending { "DESC" } else { "ASC" };
+
+		let mut builder = QueryBuilder::<Postgres>::new("");
+		with(&mut builder);
+		builder.push(format!(
+			"SELECT {}, ({})::text AS page_sort_key, ({})::text AS page_row_id FROM {} WHERE TRUE",
+			columns, sort.expr, self.id_expr, self.from
+		));
+		filters(&mut builder);
+		if let Some((key, id)) = &request.after {
+			builder.push(format!(
+				" AND ({}, {}) {} (",
+				sort.expr,
+				self.id_expr,
+				if request.descending { "<" } else { ">" }
+			));
+			builder.push_bind(key.clone());
+			builder.push(format!("::{}, ", sort.sql_type));
+			builder.push_bind(id.clone());
+			builder.push(format!("::{})", self.id_type));
+		}
+		builder.push(format!(
+			" ORDER BY {} {}, {} {} LIMIT ",
+			sort.expr, direction, self.id_expr, direction
+		));
+		// One extra row tells whether there is a next page
+		builder.push_bind(request.limit as i64 + 1);
+		if let (None, Some(page)) = (&request.after, request.page) {
+			builder.push(" OFFSET ");
+			builder.push_bind((page.max(1) - 1) as i64 * request.limit as i64);
+		}
+
+		let mut rows = builder.build_query_as::<Keyed<T>>().fetch_all(db).await?;
+		let has_more = rows.len() > request.limit;
+		rows.truncate(request.limit);
+
+		let next_cursor = match rows.last() {
+			Some(last) if has_more => Some(encode_cursor(&PageCursor {
+				sort: sort.name.to_string(),
+				descending: request.descending,
+				key: last.sort_key.clone(),
+				id: last.row_id.clone(),
+			})),
+			_ => None,
+		};
+
+		let total = if request.with_total {
+			let mut count = QueryBuilder::<Postgres>::new("");
+			with(&mut count);
+			count.push(format!("SELECT COUNT(*) FROM {} WHERE TRUE", self.from));
+			filters(&mut count);
+			Some(count.build_query_scalar::<i64>().fetch_one(db).await?)
+		} else {
+			None
+		};
+
+		Ok(Page {
+			items: rows.into_iter().map(|row| row.item).collect(),
+			next_cursor,
+			total,
+			sort: sort.name,
+			descending: request.descending,
+			page: request.page.filter(|_| request.after.is_none()),
+			limit: request.limit,
+		})
+	}
+}
+
+// Whether Postgres text output of `sql_type` could look like `value`; unknown types pass
+fn fits_sql_type(value: &str, sql_type: &str) -> bool {
+	match sql_type {
+		"uuid" => uuid::Uuid::parse_str(value).is_ok(),
+		"int4" => value.parse::<i32>().is_ok(),
+		// Rust also reads Infinity, -Infinity and NaN the way Postgres prints them
+		"float4" | "float8" | "numeric" => value.parse::<f64>().is_ok(),
+		"timestamptz" => fits_timestamptz(value),
+		_ => true,
+	}
+}
+
+// Postgres prints timestamptz as "2025-11-23 12:30:05.123456+03", the offset possibly
+// with minutes and seconds
+fn fits_timestamptz(value: &str) -> bool {
+	if value == "infinity" || value == "-infinity" {
+		return true;
+	}
+	let Some(split) = value
+		.char_indices()
+		.skip(19)
+		.find(|(_, c)| *c == '+' || *c == '-')
+		.map(|(i, _)| i)
+	else {
+		return false;
+	};
+	let (datetime, offset) = value.split_at(split);
+	let offset: Vec<&str> = offset[1..].split(':').collect();
+	chrono::NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M:%S%.f").is_ok()
+		&& offset.len() <= 3
+		&& offset
+			.iter()
+			.all(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl<T> Page<T> {
+	// A page for a listing whose parent is missing, with the usual pagination shape
+	pub fn empty(request: &PageRequest) -> Self {
+		Page {
+			items: Vec::new(),
+			next_cursor: None,
+			total: Some(0),
+			sort: request.sort.name,
+			descending: request.descending,
+			page: request.page.filter(|_| request.after.is_none()),
+			limit: request.limit,
+		}
+	}
+
+	pub fn pagination(&self) -> serde_json::Value {
+		let mut pagination = json!({
+			"limit": self.limit,
+			"sort": self.sort,
+			"order": if self.descending { "desc" } else { "asc" },
+			"next_cursor": self.next_cursor,
+			"total": self.total
+		});
+		if let (Some(page), Some(total)) = (self.page, self.total) {
+			pagination["page"] = json!(page);
+			pagination["pages"] = json!((total as f64 / self.limit as f64).ceil() as i64);
+		}
+		pagination
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	static LISTING: Keyset = Keyset {
+		from: "items",
+		id_expr: "item_id",
+		id_type: "uuid",
+		sort_keys: &[
+			SortKey {
+				name: "created",
+				expr: "created_ts",
+				sql_type: "timestamptz",
+			},
+			SortKey {
+				name: "name",
+				expr: "name",
+				sql_type: "text",
+			},
+			SortKey {
+				name: "price",
+				expr: "COALESCE(price, 'Infinity'::float8)",
+				sql_type: "float8",
+			},
+		],
+		default_descending: true,
+		default_limit: 10,
+		max_limit: 50,
+	};
+
+	const ID: &str = "6f1c2a9e-3b1d-4c55-9d3e-0a4b5c6d7e8f";
+
+	fn options() -> FilterOptions {
+		FilterOptions {
+			page: None,
+			limit: None,
+			cursor: None,
+			sort: None,
+			order: None,
+			with_total: None,
+		}
+	}
+
+	fn cursor(sort: &str, key: &str, id: &str) -> Option<String> {
+		Some(encode_cursor(&PageCursor {
+			sort: sort.to_string(),
+			descending: false,
+			key: key.to_string(),
+			id: id.to_string(),
+		}))
+	}
+
+	fn cursor_error(sort: &str, key: &str, id: &str) -> Option<String> {
+		LISTING
+			.request(&FilterOptions {
+				cursor: cursor(sort, key, id),
+				..options()
+			})
+			.err()
+	}
+
+	#[test]
+	fn first_page_uses_the_default_sort() {
+		let request = LISTING.request(&options()).unwrap();
+		assert_eq!(request.sort.name, "created");
+		assert!(request.descending);
+		assert_eq!(request.page, Some(1));
+		assert_eq!(request.limit, 10);
+		assert!(request.with_total);
+		assert!(request.after.is_none());
+
+		let request = LISTING
+			.request(&FilterOptions {
+				limit: Some(500),
+				..options()
+			})
+			.unwrap();
+		assert_eq!(request.limit, 50);
+	}
+
+	#[test]
+	fn other_sorts_default_to_ascending() {
+		let request = LISTING
+			.request(&FilterOptions {
+				sort: Some("name".to_string()),
+				..options()
+			})
+			.unwrap();
+		assert!(!request.descending);
+
+		let request = LISTING
+			.request(&FilterOptions {
+				sort: Some("name".to_string()),
+				order: Some("desc".to_string()),
+				..options()
+			})
+			.unwrap();
+		assert!(request.descending);
+	}
+
+	#[test]
+	fn unknown_sort_and_order_are_rejected() {
+		let sort = LISTING.request(&FilterOptions {
+			sort: Some("views".to_string()),
+			..options()
+		});
+		assert_eq!(
+			sort.err().as_deref(),
+			Some("Unknown sort 'views', expected one of: created, name, price")
+		);
+
+		let order = LISTING.request(&FilterOptions {
+			order: Some("up".to_string()),
+			..options()
+		});
+		assert_eq!(
+			order.err().as_deref(),
+			Some("Unknown order 'up', expected asc or desc")
+		);
+	}
+
+	#[test]
+	fn cursor_continues_its_own_sort() {
+		let request = LISTING
+			.request(&FilterOptions {
+				cursor: cursor("price", "Infinity", ID),
+				sort: Some("name".to_string()),
+				page: Some(3),
+				..options()
+			})
+			.unwrap();
+		assert_eq!(request.sort.name, "price");
+		assert!(!request.descending);
+		assert_eq!(
+			request.after,
+			Some(("Infinity".to_string(), ID.to_string()))
+		);
+		assert_eq!(request.page, None);
+		assert!(!request.with_total);
+	}
+
+	#[test]
+	fn cursor_keys_must_cast_to_the_sort_type() {
+		assert_eq!(
+			cursor_error("created", "2025-11-23 12:30:05.123456+03", ID),
+			None
+		);
+		assert_eq!(
+			cursor_error("created", "2025-11-23 12:30:05+05:30", ID),
+			None
+		);
+		assert_eq!(cursor_error("name", "", ID), None);
+		assert_eq!(cursor_error("price", "-12.5", ID), None);
+
+		let invalid = Some("Invalid cursor".to_string());
+		assert_eq!(cursor_error("created", "abc", ID), invalid);
+		assert_eq!(cursor_error("created", "2025-11-23+03", ID), invalid);
+		assert_eq!(cursor_error("price", "cheap", ID), invalid);
+		assert_eq!(cursor_error("name", "a", "42"), invalid);
+		assert_eq!(cursor_error("views", "1", ID), invalid);
+		assert_eq!(
+			LISTING
+				.request(&FilterOptions {
+					cursor: Some("zz".to_string()),
+					..options()
+				})
+				.err(),
+			invalid
+		);
+	}
+}
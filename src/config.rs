@@ -4,6 +4,10 @@ pub struct Config {
 	pub jwt_secret: String,
 	pub rabbitmq_url: String,
 	pub secure_cookies: bool,
+	pub image_storage_dir: String,
+	pub public_base_url: String,
+	pub ad_scheduler_interval_secs: u64,
+	pub crawl_scheduler_interval_secs: u64,
 }
 
 impl Config {
@@ -15,12 +19,33 @@ impl Config {
 			.unwrap_or_else(|_| "false".to_string()) // Default to false for development
 			.parse()
 			.expect("SECURE_COOKIES must be a boolean value (true/false)");
+		let image_storage_dir =
+			std::env::var("IMAGE_STORAGE_DIR").unwrap_or_else(|_| "storage/images".to_string());
+		// Used to build links to mirrored images, must be reachable by Avito
+		let public_base_url = std::env::var("PUBLIC_BASE_URL")
+			.unwrap_or_else(|_| "http://localhost:8081".to_string());
+		// How often scheduled publish/unpublish dates are checked; a zero interval would panic
+		let ad_scheduler_interval_secs = std::env::var("AD_SCHEDULER_INTERVAL_SECS")
+			.unwrap_or_else(|_| "60".to_string())
+			.parse::<u64>()
+			.expect("AD_SCHEDULER_INTERVAL_SECS must be a number of seconds")
+			.max(1);
+		// How often saved search schedules are checked for due crawls, at least once a second
+		let crawl_scheduler_interval_secs = std::env::var("CRAWL_SCHEDULER_INTERVAL_SECS")
+			.unwrap_or_else(|_| "60".to_string())
+			.parse::<u64>()
+			.expect("CRAWL_SCHEDULER_INTERVAL_SECS must be a number of seconds")
+			.max(1);
 
 		Config {
 			database_url,
 			jwt_secret,
 			rabbitmq_url,
 			secure_cookies,
+			image_storage_dir,
+			public_base_url,
+			ad_scheduler_interval_secs,
+			crawl_scheduler_interval_secs,
 		}
 	}
 }
@@ -1,10 +1,12 @@
-use crate::{controllers::websocket::WebSocketConnections, jwt_auth::JwtMiddleware, AppState};
+use crate::{
+	api::avito_requests::AD_RECORD_COLUMNS, controllers::websocket::WebSocketConnections,
+	jwt_auth::JwtMiddleware, AppState,
+};
 use actix_web::{
 	post,
 	web::{self},
 	HttpResponse, Responder,
 };
-use chrono::{Datelike, NaiveDate, Utc};
 use futures::StreamExt;
 use lapin::{options::*, types::FieldTable, Channel};
 use serde::{Deserialize, Serialize};
@@ -43,28 +45,6 @@ pub struct AiTitleProcessingRequest {
 	pub category: String,
 }
 
-// Helper function to parse and check if the ad date is not today
-fn is_date_not_today(ad_date_str: &str) -> bool {
-	// Parse the date string which might be in format "DD.MM.YYYY в HH:MM" or similar
-	let today = Utc::now().date_naive();
-
-	// Extract the date part from the string (before " в " if present)
-	let date_part = if let Some(pos) = ad_date_str.find(" в ") {
-		&ad_date_str[..pos].trim()
-	} else {
-		ad_date_str
-	};
-
-	// Parse the date in DD.MM.YYYY format
-	if let Ok(parsed_date) = NaiveDate::parse_from_str(date_part, "%d.%m.%Y") {
-		// Compare with today's date
-		parsed_date != today
-	} else {
-		// If parsing fails, return true (consider it as not today)
-		true
-	}
-}
-
 // Create AI title processing task handler
 #[post("/ai_title_processing")]
 pub async fn create_ai_title_processing_handler(
@@ -87,7 +67,9 @@ pub async fn create_ai_title_processing_handler(
 	// Precondition: Find requests matching the category and get related ads
 	// SELECT from postgres all the requests WHERE request LIKE '%{category}%', take the newest one
 	let requests_query = "
-        SELECT request_id, user_id, request, city, coords, radius, district, created_ts, updated_ts
+        SELECT request_id, user_id, request, city, coords, radius, district, created_ts, updated_ts,
+            status, started_ts, finished_ts, progress_current, progress_total, ads_count, last_error,
+            schedule_id
         FROM avito_requests
         WHERE request LIKE $1
         ORDER BY created_ts DESC
@@ -105,38 +87,31 @@ pub async fn create_ai_title_processing_handler(
 	let title = if let Ok(Some(request)) = found_request_result {
 		let request_id = request.request_id;
 
-		// Query to find ads with the specified conditions for the specific request
-		let ads_query = "
-            SELECT *
-            FROM avito_analytics_ads
-            WHERE avito_request_id = $1
-            AND promotion = ''
-            AND ad_date != $2
-            ORDER BY position
-            LIMIT 1
-        ";
-
-		// Get today's date in DD.MM.YYYY format to compare with ad_date
-		let today = Utc::now();
-		let today_formatted = format!("{:02}.{:02}.{}", today.day(), today.month(), today.year());
+		// Query to find ads with the specified conditions for the specific request;
+		// ads published today (Moscow time, as shown on the site) are skipped
+		let ads_query = format!(
+			"SELECT {}
+			FROM avito_analytics_ads
+			WHERE avito_request_id = $1
+			AND promotion = ''
+			AND (ad_ts IS NULL
+				OR (ad_ts AT TIME ZONE 'Europe/Moscow')::date <> (NOW() AT TIME ZONE 'Europe/Moscow')::date)
+			ORDER BY position
+			LIMIT 1",
+			AD_RECORD_COLUMNS
+		);
 
 		let ad_result = sqlx::query_as::<_, crate::models::avito_requests::AdRecord>(&ads_query)
 			.bind(request_id)
-			.bind(&today_formatted)
 			.fetch_optional(&data.db)
 			.await;
 
 		if let Ok(Some(ad)) = ad_result {
-			// Check if the ad date is not today (since ad_date is in string format like "24.10.2025 в 15:57")
-			if is_date_not_today(&ad.ad_date) {
-				// Print the ad (as requested in the task)
-				println!("Found ad: ID={}, Title={}", ad.avito_ad_id, ad.title);
-
-				// Return the title from the ad as the best title
-				Some(ad.title)
-			} else {
-				None
-			}
+			// Print the ad (as requested in the task)
+			println!("Found ad: ID={}, Title={}", ad.avito_ad_id, ad.title);
+
+			// Return the title from the ad as the best title
+			Some(ad.title)
 		} else {
 			None
 		}
@@ -0,0 +1,228 @@
+use crate::{
+	controllers::avito_ads::create_ad_from_fields,
+	jwt_auth::JwtMiddleware,
+	models::{
+		AdTemplatesQuery, ApiError, AvitoAdTemplate, CreateAdFromSourceSchema,
+		CreateAdTemplateSchema, UpdateAdTemplateSchema,
+	},
+	utils::ad_fields::fields_from_json,
+	AppState,
+};
+use actix_web::{
+	delete, get, post, put,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+// GET all ad templates of an account
+#[get("/avito/ad-templates")]
+pub async fn get_ad_templates_handler(
+	opts: web::Query<AdTemplatesQuery>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let templates = sqlx::query_as::<_, AvitoAdTemplate>(
+		r#"
+        SELECT template_id, account_id, name, category, fields, created_ts, updated_ts
+        FROM avito_ad_templates
+        WHERE account_id = $1
+        ORDER BY created_ts DESC
+        "#,
+	)
+	.bind(opts.account_id)
+	.fetch_all(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ad templates: {}", e)))?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"ad_templates": templates
+		})
+	})))
+}
+
+// GET ad template by id
+#[get("/avito/ad-templates/{template_id}")]
+pub async fn get_ad_template_handler(
+	path: Path<Uuid>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let template_id = path.into_inner();
+
+	match fetch_ad_template(&data, template_id).await? {
+		Some(template) => Ok(HttpResponse::Ok().json(json!({
+			"status": "success",
+			"data": json!({
+				"ad_template": template
+			})
+		}))),
+		None => Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Ad template not found"
+		}))),
+	}
+}
+
+// Create ad template
+#[post("/avito/ad-templates")]
+pub async fn create_ad_template_handler(
+	body: web::Json<CreateAdTemplateSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let mut fields = fields_from_json(&body.fields);
+	// Id is generated for every ad created from the template
+	fields.remove("Id");
+
+	let template = sqlx::query_as::<_, AvitoAdTemplate>(
+		r#"
+        INSERT INTO avito_ad_templates (account_id, name, category, fields)
+        VALUES ($1, $2, $3, $4)
+        RETURNING template_id, account_id, name, category, fields, created_ts, updated_ts
+        "#,
+	)
+	.bind(body.account_id)
+	.bind(&body.name)
+	.bind(&body.category)
+	.bind(Json(&fields))
+	.fetch_one(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to create ad template: {}", e)))?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"ad_template": template
+		})
+	})))
+}
+
+// Update ad template
+#[put("/avito/ad-templates/{template_id}")]
+pub async fn update_ad_template_handler(
+	path: Path<Uuid>,
+	body: web::Json<UpdateAdTemplateSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let template_id = path.into_inner();
+	let fields = body.fields.as_ref().map(|fields| {
+		let mut fields = fields_from_json(fields);
+		fields.remove("Id");
+		fields
+	});
+
+	let template = sqlx::query_as::<_, AvitoAdTemplate>(
+		r#"
+        UPDATE avito_ad_templates
+        SET name = COALESCE($2, name),
+            category = COALESCE($3, category),
+            fields = COALESCE($4, fields),
+            updated_ts = NOW()
+        WHERE template_id = $1
+        RETURNING template_id, account_id, name, category, fields, created_ts, updated_ts
+        "#,
+	)
+	.bind(template_id)
+	.bind(&body.name)
+	.bind(&body.category)
+	.bind(fields.as_ref().map(Json))
+	.fetch_optional(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to update ad template: {}", e)))?;
+
+	match template {
+		Some(template) => Ok(HttpResponse::Ok().json(json!({
+			"status": "success",
+			"data": json!({
+				"ad_template": template
+			})
+		}))),
+		None => Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Ad template not found"
+		}))),
+	}
+}
+
+// Delete ad template
+#[delete("/avito/ad-templates/{template_id}")]
+pub async fn delete_ad_template_handler(
+	path: Path<Uuid>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let template_id = path.into_inner();
+
+	let result = sqlx::query("DELETE FROM avito_ad_templates WHERE template_id = $1")
+		.bind(template_id)
+		.execute(&data.db)
+		.await
+		.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to delete ad template: {}", e))
+		})?;
+
+	if result.rows_affected() == 0 {
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Ad template not found"
+		})));
+	}
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"message": "Ad template deleted successfully"
+	})))
+}
+
+// Create a new ad from the template's fields plus overrides
+#[post("/avito/ad-templates/{template_id}/create-ad")]
+pub async fn create_ad_from_template_handler(
+	path: Path<Uuid>,
+	body: web::Json<CreateAdFromSourceSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let template_id = path.into_inner();
+
+	let template = match fetch_ad_template(&data, template_id).await? {
+		Some(template) if template.account_id == body.account_id => template,
+		_ => {
+			return Ok(HttpResponse::NotFound().json(json!({
+				"status": "error",
+				"message": "Ad template not found or does not belong to the specified account"
+			})));
+		}
+	};
+
+	let mut fields = template.fields.0.clone();
+	if let Some(category) = &template.category {
+		fields
+			.entry("Category".to_string())
+			.or_insert_with(|| category.clone());
+	}
+
+	create_ad_from_fields(&data, &body, body.feed_id, None, fields).await
+}
+
+pub async fn fetch_ad_template(
+	data: &web::Data<AppState>,
+	template_id: Uuid,
+) -> Result<Option<AvitoAdTemplate>, ApiError> {
+	sqlx::query_as::<_, AvitoAdTemplate>(
+		r#"
+        SELECT template_id, account_id, name, category, fields, created_ts, updated_ts
+        FROM avito_ad_templates
+        WHERE template_id = $1
+        "#,
+	)
+	.bind(template_id)
+	.fetch_optional(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ad template: {}", e)))
+}
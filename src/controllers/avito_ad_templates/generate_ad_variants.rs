@@ -0,0 +1,143 @@
+use crate::{
+	api::avito_ads::{feed_belongs_to_account, get_or_create_manual_feed},
+	controllers::{
+		avito_ad_templates::fetch_ad_template, avito_feeds::batch_process_ads,
+		avito_images::spawn_feed_image_checks,
+	},
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, GenerateAdVariantsSchema, XmlAd},
+	utils::{
+		ad_fields::field_value_to_string,
+		ad_variants::{generate_variants, DEFAULT_SIMILARITY_THRESHOLD},
+	},
+	AppState,
+};
+use actix_web::{
+	post,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Create one ad per variable row from a template. {name} placeholders are filled
+// from the row, {a|b|c} spintax in Title and Description picks one option.
+#[post("/avito/ad-templates/{template_id}/generate")]
+pub async fn generate_ad_variants_handler(
+	path: Path<Uuid>,
+	body: web::Json<GenerateAdVariantsSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let template_id = path.into_inner();
+
+	let template = match fetch_ad_template(&data, template_id).await? {
+		Some(template) if template.account_id == body.account_id => template,
+		_ => {
+			return Ok(HttpResponse::NotFound().json(json!({
+				"status": "error",
+				"message": "Ad template not found or does not belong to the specified account"
+			})));
+		}
+	};
+
+	if let Some(feed_id) = body.feed_id {
+		if !feed_belongs_to_account(&data.db, feed_id, body.account_id).await? {
+			return Ok(HttpResponse::NotFound().json(json!({
+				"status": "error",
+				"message": "Feed not found or does not belong to the specified account"
+			})));
+		}
+	}
+
+	let threshold = body
+		.similarity_threshold
+		.unwrap_or(DEFAULT_SIMILARITY_THRESHOLD);
+	if !(0.0..=1.0).contains(&threshold) {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "similarity_threshold must be between 0 and 1"
+		})));
+	}
+
+	let mut template_fields = template.fields.0.clone();
+	if let Some(category) = &template.category {
+		template_fields
+			.entry("Category".to_string())
+			.or_insert_with(|| category.clone());
+	}
+
+	// Rows are numbered from 1 in the report
+	let rows: Vec<(usize, HashMap<String, String>)> = body
+		.rows
+		.iter()
+		.enumerate()
+		.map(|(i, row)| {
+			let vars = row
+				.iter()
+				.map(|(name, value)| (name.clone(), field_value_to_string(value)))
+				.collect();
+			(i + 1, vars)
+		})
+		.collect();
+
+	let seed = body
+		.seed
+		.unwrap_or_else(|| chrono::Utc::now().timestamp() as u64);
+	let (variants, errors) = generate_variants(&template_fields, &rows, threshold, seed);
+	println!(
+		"Generated {} variants from {} rows, {} rejected",
+		variants.len(),
+		rows.len(),
+		errors.len()
+	);
+
+	let ads: Vec<XmlAd> = variants
+		.iter()
+		.map(|variant| {
+			let id = Uuid::new_v4().to_string();
+			let mut fields = variant.fields.clone();
+			fields.insert("Id".to_string(), id.clone());
+			XmlAd { id, fields }
+		})
+		.collect();
+
+	if body.dry_run.unwrap_or(false) || ads.is_empty() {
+		return Ok(HttpResponse::Ok().json(json!({
+			"status": "success",
+			"feed_id": body.feed_id,
+			"seed": seed,
+			"rows_total": rows.len(),
+			"ads_created": 0,
+			"variants": ads.iter().map(|ad| &ad.fields).collect::<Vec<_>>(),
+			"errors": errors
+		})));
+	}
+
+	let mut tx = data.db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	let feed_id = match body.feed_id {
+		Some(feed_id) => feed_id,
+		None => get_or_create_manual_feed(&mut tx, body.account_id).await?,
+	};
+	batch_process_ads(&mut tx, &feed_id, &ads).await?;
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	spawn_feed_image_checks(data.clone(), feed_id);
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"feed_id": feed_id,
+		"seed": seed,
+		"rows_total": rows.len(),
+		"ads_created": ads.len(),
+		"variants": ads.iter().map(|ad| &ad.fields).collect::<Vec<_>>(),
+		"errors": errors
+	})))
+}
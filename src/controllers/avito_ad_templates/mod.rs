@@ -0,0 +1,5 @@
+pub mod avito_ad_templates;
+pub mod generate_ad_variants;
+
+pub use self::avito_ad_templates::*;
+pub use self::generate_ad_variants::*;
@@ -0,0 +1,270 @@
+use crate::{
+	api::avito_ads::{
+		delete_ad_fields, filter_account_ads, find_similar_ad_ids, insert_ad_fields,
+		load_ad_fields, load_duplicate_candidates, load_duplicate_cluster_ads, load_feed_ad_ids,
+		transition_ad_states,
+	},
+	jwt_auth::JwtMiddleware,
+	models::{
+		AdState, AdStateChangeRequest, ApiError, DuplicateAction, DuplicateCluster,
+		DuplicateClusterAd, FindDuplicatesSchema, ResolveDuplicatesSchema, TransitionSource,
+	},
+	utils::ad_duplicates::{
+		cluster_pairs, find_duplicate_pairs, find_matches, merge_duplicate_fields,
+		DuplicateCandidate, DEFAULT_DUPLICATE_THRESHOLD,
+	},
+	AppState,
+};
+use actix_web::{
+	post,
+	web::{self},
+	HttpResponse,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Group near-duplicate ads of the account (or of the given feeds) into scored clusters
+#[post("/avito/duplicates")]
+pub async fn find_avito_duplicates(
+	body: web::Json<FindDuplicatesSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let threshold = body.threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD);
+	if !(0.0..=1.0).contains(&threshold) {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "threshold must be between 0 and 1"
+		})));
+	}
+
+	let mut requested_feeds = body.feed_ids.clone();
+	requested_feeds.sort();
+	requested_feeds.dedup();
+
+	let feed_ids = sqlx::query_scalar::<_, Uuid>(
+		r#"
+        SELECT feed_id
+        FROM avito_feeds
+        WHERE account_id = $1 AND (cardinality($2::uuid[]) = 0 OR feed_id = ANY($2))
+        "#,
+	)
+	.bind(body.account_id)
+	.bind(&requested_feeds)
+	.fetch_all(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch feeds: {}", e)))?;
+
+	if feed_ids.is_empty() || feed_ids.len() < requested_feeds.len() {
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Feeds not found or do not belong to the specified account"
+		})));
+	}
+
+	let ad_ids = load_feed_ad_ids(&data.db, &feed_ids).await?;
+	let candidates = load_duplicate_candidates(&data.db, &ad_ids).await?;
+	let pairs = find_duplicate_pairs(&candidates, threshold);
+	let clusters = cluster_pairs(&pairs);
+
+	let clustered_ids: Vec<Uuid> = clusters.iter().flatten().copied().collect();
+	let ads: HashMap<Uuid, DuplicateClusterAd> =
+		load_duplicate_cluster_ads(&data.db, &clustered_ids)
+			.await?
+			.into_iter()
+			.map(|ad| (ad.ad_id, ad))
+			.collect();
+
+	let mut cluster_of: HashMap<Uuid, usize> = HashMap::new();
+	for (index, members) in clusters.iter().enumerate() {
+		for ad_id in members {
+			cluster_of.insert(*ad_id, index);
+		}
+	}
+	let mut cluster_pairs_by_index: Vec<Vec<_>> = clusters.iter().map(|_| Vec::new()).collect();
+	for pair in pairs {
+		if let Some(index) = cluster_of.get(&pair.ad_id) {
+			cluster_pairs_by_index[*index].push(pair);
+		}
+	}
+
+	let result: Vec<DuplicateCluster> = clusters
+		.iter()
+		.zip(cluster_pairs_by_index)
+		.map(|(members, pairs)| DuplicateCluster {
+			ads: members
+				.iter()
+				.filter_map(|ad_id| ads.get(ad_id).cloned())
+				.collect(),
+			score: pairs
+				.iter()
+				.map(|pair| pair.score.score)
+				.fold(0.0, f64::max),
+			pairs,
+		})
+		.collect();
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"ads_compared": candidates.len(),
+		"threshold": threshold,
+		"clusters": result
+	})))
+}
+
+// Keep one ad of a cluster and merge the others into it or just archive them
+#[post("/avito/duplicates/resolve")]
+pub async fn resolve_avito_duplicates(
+	body: web::Json<ResolveDuplicatesSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let mut requested: Vec<Uuid> = body
+		.ad_ids
+		.iter()
+		.copied()
+		.filter(|ad_id| *ad_id != body.keep_ad_id)
+		.collect();
+	requested.sort();
+	requested.dedup();
+	if requested.is_empty() {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "No duplicate ads given besides the kept one"
+		})));
+	}
+
+	let mut lookup = requested.clone();
+	lookup.push(body.keep_ad_id);
+	let owned = filter_account_ads(&data.db, &lookup, body.account_id).await?;
+	if !owned.contains(&body.keep_ad_id) {
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Kept ad not found or does not belong to the specified account"
+		})));
+	}
+	let duplicate_ids: Vec<Uuid> = requested
+		.into_iter()
+		.filter(|ad_id| owned.contains(ad_id))
+		.collect();
+	if duplicate_ids.is_empty() {
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Ads not found or do not belong to the specified account"
+		})));
+	}
+
+	let mut merged_tags = Vec::new();
+	let mut new_fields = Vec::new();
+	if body.action == DuplicateAction::Merge {
+		let mut fields = load_ad_fields(&data.db, &lookup).await?;
+		let keep = fields.remove(&body.keep_ad_id).unwrap_or_default();
+		let duplicates: Vec<HashMap<String, String>> = duplicate_ids
+			.iter()
+			.filter_map(|ad_id| fields.remove(ad_id))
+			.collect();
+
+		let merged = merge_duplicate_fields(&keep, &duplicates);
+		for (tag, value) in merged {
+			if keep.get(&tag) != Some(&value) {
+				merged_tags.push(tag.clone());
+				new_fields.push((body.keep_ad_id, tag, value));
+			}
+		}
+	}
+
+	let mut tx = data.db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	let removed_fields: Vec<(Uuid, String)> = merged_tags
+		.iter()
+		.map(|tag| (body.keep_ad_id, tag.clone()))
+		.collect();
+	delete_ad_fields(&mut tx, &removed_fields).await?;
+	insert_ad_fields(&mut tx, &new_fields).await?;
+
+	// Duplicates are archived like any other ad, so they keep their history
+	let reason = format!("duplicate of {}", body.keep_ad_id);
+	let requests: Vec<AdStateChangeRequest> = duplicate_ids
+		.iter()
+		.map(|ad_id| AdStateChangeRequest {
+			ad_id: *ad_id,
+			to: AdState::Archived,
+			reason: Some(reason.clone()),
+		})
+		.collect();
+	let transitions =
+		transition_ad_states(&mut tx, &requests, TransitionSource::User, None).await?;
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	let archived_ids: Vec<Uuid> = transitions
+		.changed
+		.iter()
+		.map(|change| change.ad_id)
+		.collect();
+	println!(
+		"Resolved duplicates of ad {}: {} ads archived, {} fields merged",
+		body.keep_ad_id,
+		archived_ids.len(),
+		merged_tags.len()
+	);
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"kept_ad_id": body.keep_ad_id,
+		"archived_ads": archived_ids,
+		"merged_fields": merged_tags
+	})))
+}
+
+// Conflict response when the fields of a new ad are too close to an existing ad of the account.
+// `source_ad_id` is the ad being copied, which a copy is expected to match
+pub async fn reject_duplicate_ad(
+	db: &Pool<Postgres>,
+	account_id: Uuid,
+	fields: &HashMap<String, String>,
+	source_ad_id: Option<Uuid>,
+) -> Result<Option<HttpResponse>, ApiError> {
+	// find_matches skips candidates with the ad's own id
+	let ad = DuplicateCandidate::new(source_ad_id.unwrap_or_else(Uuid::nil), fields, &[]);
+	let similar_ids = find_similar_ad_ids(db, account_id, &ad.title_words()).await?;
+	if similar_ids.is_empty() {
+		return Ok(None);
+	}
+
+	let candidates = load_duplicate_candidates(db, &similar_ids).await?;
+	let matches = find_matches(&ad, &candidates, DEFAULT_DUPLICATE_THRESHOLD);
+	if matches.is_empty() {
+		return Ok(None);
+	}
+
+	let match_ids: Vec<Uuid> = matches.iter().map(|m| m.duplicate_ad_id).collect();
+	let ads: HashMap<Uuid, DuplicateClusterAd> = load_duplicate_cluster_ads(db, &match_ids)
+		.await?
+		.into_iter()
+		.map(|ad| (ad.ad_id, ad))
+		.collect();
+	let duplicates: Vec<_> = matches
+		.iter()
+		.filter_map(|m| {
+			ads.get(&m.duplicate_ad_id).map(|ad| {
+				json!({
+					"ad": ad,
+					"score": m.score
+				})
+			})
+		})
+		.collect();
+
+	Ok(Some(HttpResponse::Conflict().json(json!({
+		"status": "error",
+		"message": "A similar ad already exists; repeat with \"force\": true to create it anyway",
+		"duplicates": duplicates
+	}))))
+}
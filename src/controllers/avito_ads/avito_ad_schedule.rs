@@ -0,0 +1,258 @@
+use crate::{
+	api::avito_ads::{
+		delete_ad_fields, filter_account_ads, insert_ad_fields, transition_ad_states,
+	},
+	controllers::websocket::WebSocketConnections,
+	jwt_auth::JwtMiddleware,
+	models::{
+		AdState, AdStateChange, AdStateChangeRequest, ApiError, ScheduleAdsSchema, TransitionSource,
+	},
+	utils::ad_schedule::{avito_date, validate_schedule, DATE_BEGIN_TAG, DATE_END_TAG},
+	AppState,
+};
+use actix_web::{
+	post,
+	web::{self},
+	HttpResponse,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+// Due ads handled per scheduler transaction
+const SCHEDULER_BATCH_SIZE: i64 = 500;
+
+// Set or clear publish/unpublish dates; DateBegin/DateEnd tags follow the dates
+#[post("/avito/ads/schedule")]
+pub async fn schedule_avito_ads(
+	body: web::Json<ScheduleAdsSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	if body.ad_ids.is_empty() {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "No ads given"
+		})));
+	}
+	if let Err(message) = validate_schedule(body.publish_at, body.unpublish_at) {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": message
+		})));
+	}
+
+	let ad_ids = filter_account_ads(&data.db, &body.ad_ids, body.account_id).await?;
+	if ad_ids.is_empty() {
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Ads not found or do not belong to the specified account"
+		})));
+	}
+
+	let mut removed_fields = Vec::new();
+	let mut new_fields = Vec::new();
+	for ad_id in &ad_ids {
+		removed_fields.push((*ad_id, DATE_BEGIN_TAG.to_string()));
+		removed_fields.push((*ad_id, DATE_END_TAG.to_string()));
+		if let Some(publish_at) = &body.publish_at {
+			new_fields.push((*ad_id, DATE_BEGIN_TAG.to_string(), avito_date(publish_at)));
+		}
+		if let Some(unpublish_at) = &body.unpublish_at {
+			new_fields.push((*ad_id, DATE_END_TAG.to_string(), avito_date(unpublish_at)));
+		}
+	}
+
+	let mut tx = data.db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	sqlx::query(
+		r#"
+        UPDATE avito_ads
+        SET publish_at = $2, unpublish_at = $3
+        WHERE ad_id = ANY($1)
+        "#,
+	)
+	.bind(&ad_ids)
+	.bind(body.publish_at)
+	.bind(body.unpublish_at)
+	.execute(&mut *tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to schedule ads: {}", e)))?;
+
+	delete_ad_fields(&mut tx, &removed_fields).await?;
+	insert_ad_fields(&mut tx, &new_fields).await?;
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"ads_scheduled": ad_ids.len(),
+		"publish_at": body.publish_at,
+		"unpublish_at": body.unpublish_at
+	})))
+}
+
+// Background task: apply due publish/unpublish dates every `interval`
+pub async fn run_ad_scheduler(
+	db: Pool<Postgres>,
+	websocket_connections: WebSocketConnections,
+	interval: Duration,
+) {
+	let mut ticker = tokio::time::interval(interval);
+	loop {
+		ticker.tick().await;
+		loop {
+			match apply_due_schedules(&db, &websocket_connections).await {
+				// A full batch means more ads may be due
+				Ok(processed) if processed == SCHEDULER_BATCH_SIZE as usize => continue,
+				Ok(_) => break,
+				Err(e) => {
+					eprintln!("Ad scheduler failed: {}", e);
+					break;
+				}
+			}
+		}
+	}
+}
+
+// Claims one batch of due ads, moves them to published/expired and notifies their owners
+async fn apply_due_schedules(
+	db: &Pool<Postgres>,
+	websocket_connections: &WebSocketConnections,
+) -> Result<usize, ApiError> {
+	let mut tx = db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	// Due dates are cleared as they are claimed, so every date fires once;
+	// SKIP LOCKED lets several instances share the work
+	let due = sqlx::query_as::<_, (Uuid, bool, bool)>(
+		r#"
+        WITH due AS (
+            SELECT ad_id,
+                COALESCE(publish_at <= NOW(), false) AS publish_due,
+                COALESCE(unpublish_at <= NOW(), false) AS unpublish_due
+            FROM avito_ads
+            WHERE publish_at <= NOW() OR unpublish_at <= NOW()
+            ORDER BY ad_id
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE avito_ads a
+        SET publish_at = CASE WHEN due.publish_due THEN NULL ELSE a.publish_at END,
+            unpublish_at = CASE WHEN due.unpublish_due THEN NULL ELSE a.unpublish_at END
+        FROM due
+        WHERE a.ad_id = due.ad_id
+        RETURNING a.ad_id, due.publish_due, due.unpublish_due
+        "#,
+	)
+	.bind(SCHEDULER_BATCH_SIZE)
+	.fetch_all(&mut *tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to claim scheduled ads: {}", e)))?;
+
+	if due.is_empty() {
+		return Ok(0);
+	}
+
+	// Publish before unpublish, so an ad whose whole window has passed ends up expired
+	let mut requests = Vec::new();
+	for (ad_id, publish_due, _) in &due {
+		if *publish_due {
+			requests.push(AdStateChangeRequest {
+				ad_id: *ad_id,
+				to: AdState::Published,
+				reason: Some("Scheduled publish".to_string()),
+			});
+		}
+	}
+	for (ad_id, _, unpublish_due) in &due {
+		if *unpublish_due {
+			requests.push(AdStateChangeRequest {
+				ad_id: *ad_id,
+				to: AdState::Expired,
+				reason: Some("Scheduled unpublish".to_string()),
+			});
+		}
+	}
+
+	let result = transition_ad_states(&mut tx, &requests, TransitionSource::System, None).await?;
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	println!(
+		"Ad scheduler changed the state of {} ads, {} transitions rejected",
+		result.changed.len(),
+		result.rejected.len()
+	);
+
+	notify_schedule_owners(db, websocket_connections, &result.changed, &result.rejected).await?;
+
+	Ok(due.len())
+}
+
+async fn notify_schedule_owners(
+	db: &Pool<Postgres>,
+	websocket_connections: &WebSocketConnections,
+	changed: &[AdStateChange],
+	rejected: &[AdStateChange],
+) -> Result<(), ApiError> {
+	let ad_ids: Vec<Uuid> = changed
+		.iter()
+		.chain(rejected.iter())
+		.map(|change| change.ad_id)
+		.collect();
+	if ad_ids.is_empty() {
+		return Ok(());
+	}
+
+	let owners: HashMap<Uuid, String> = sqlx::query_as::<_, (Uuid, String)>(
+		r#"
+        SELECT a.ad_id, acc.user_id
+        FROM avito_ads a
+        JOIN avito_feeds f ON f.feed_id = a.feed_id
+        JOIN avito_accounts acc ON acc.account_id = f.account_id
+        WHERE a.ad_id = ANY($1)
+        "#,
+	)
+	.bind(&ad_ids)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ad owners: {}", e)))?
+	.into_iter()
+	.collect();
+
+	let mut by_user: HashMap<&str, (Vec<&AdStateChange>, Vec<&AdStateChange>)> = HashMap::new();
+	for change in changed {
+		if let Some(user_id) = owners.get(&change.ad_id) {
+			by_user.entry(user_id.as_str()).or_default().0.push(change);
+		}
+	}
+	for change in rejected {
+		if let Some(user_id) = owners.get(&change.ad_id) {
+			by_user.entry(user_id.as_str()).or_default().1.push(change);
+		}
+	}
+
+	for (user_id, (changed, rejected)) in by_user {
+		let message = json!({
+			"type": "ad_schedule",
+			"user_id": user_id,
+			"changed": changed,
+			"rejected": rejected
+		});
+		websocket_connections
+			.broadcast_message_to_user(user_id, &message.to_string())
+			.await;
+	}
+
+	Ok(())
+}
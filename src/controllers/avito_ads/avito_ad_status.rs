@@ -0,0 +1,99 @@
+use crate::{
+	api::avito_ads::{filter_account_ads, load_ad_state_history, transition_ad_states},
+	jwt_auth::JwtMiddleware,
+	models::{AdStateChangeRequest, ApiError, ChangeAdsStatusSchema, TransitionSource},
+	AppState,
+};
+use actix_web::{
+	get, post,
+	web::{self},
+	HttpResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct AdStatusPath {
+	pub ad_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct AdStatusHistoryQuery {
+	/// Account the ad must belong to
+	pub account_id: Uuid,
+}
+
+// Move ads to another lifecycle state; transitions not allowed from an ad's current state are reported, not applied
+#[post("/avito/ads/status")]
+pub async fn change_avito_ads_status(
+	body: web::Json<ChangeAdsStatusSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	if body.ad_ids.is_empty() {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "No ads given"
+		})));
+	}
+
+	// Ads of other accounts are reported as missing
+	let owned = filter_account_ads(&data.db, &body.ad_ids, body.account_id).await?;
+	let requests: Vec<AdStateChangeRequest> = owned
+		.iter()
+		.map(|ad_id| AdStateChangeRequest {
+			ad_id: *ad_id,
+			to: body.status,
+			reason: body.reason.clone(),
+		})
+		.collect();
+
+	let mut tx = data.db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	let mut result = transition_ad_states(&mut tx, &requests, TransitionSource::User, None).await?;
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	result.missing.extend(
+		body.ad_ids
+			.iter()
+			.filter(|ad_id| !owned.contains(ad_id))
+			.copied(),
+	);
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": result
+	})))
+}
+
+#[get("/avito/ads/{ad_id}/status-history")]
+pub async fn get_avito_ad_status_history(
+	path: web::Path<AdStatusPath>,
+	query: web::Query<AdStatusHistoryQuery>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	// Same ownership rule as status changes: ads of other accounts are not found
+	if filter_account_ads(&data.db, &[path.ad_id], query.account_id)
+		.await?
+		.is_empty()
+	{
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Ad not found"
+		})));
+	}
+
+	let history = load_ad_state_history(&data.db, path.ad_id).await?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": history
+	})))
+}
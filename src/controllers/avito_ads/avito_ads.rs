@@ -1,9 +1,12 @@
 use crate::controllers::auth::Role;
 use crate::{
+	api::avito_ads::transition_ad_states,
 	jwt_auth::JwtMiddleware,
 	models::{
-		ApiError, AvitoReport, AvitoReportItem, AvitoReportItemsResponse, AvitoReportsResponse,
+		AdStateChangeRequest, AdStateTransitionResult, ApiError, AvitoReport, AvitoReportItem,
+		AvitoReportItemsResponse, AvitoReportsResponse, TransitionSource,
 	},
+	utils::ad_lifecycle::state_for_avito_status,
 	AppState,
 };
 use actix_web::{
@@ -17,11 +20,15 @@ use reqwest::{
 	Client,
 };
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
+use uuid::Uuid;
 
 #[derive(Debug, Deserialize)]
 pub struct AvitoTokenParams {
 	pub avito_token: String,
+	/// Account the token belongs to; report statuses only move its own ads
+	pub account_id: Uuid,
 }
 
 // Function to fetch Avito reports
@@ -169,6 +176,87 @@ async fn update_avito_ads_table(
 	Ok(())
 }
 
+// Move the account's ads to the lifecycle state matching their avito_status in the report;
+// parsed ids are only unique within an account
+async fn apply_report_statuses(
+	data: &web::Data<AppState>,
+	account_id: Uuid,
+	report_id: i64,
+	items: &[AvitoReportItem],
+) -> Result<AdStateTransitionResult, ApiError> {
+	let parsed_ids: Vec<String> = items.iter().map(|item| item.ad_id.clone()).collect();
+	let ads = sqlx::query_as::<_, (Uuid, String)>(
+		r#"
+        SELECT a.ad_id, a.parsed_id
+        FROM avito_ads a
+        JOIN avito_feeds f ON f.feed_id = a.feed_id
+        WHERE f.account_id = $1 AND a.parsed_id = ANY($2)
+        "#,
+	)
+	.bind(account_id)
+	.bind(&parsed_ids)
+	.fetch_all(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))?;
+
+	let mut ads_by_parsed_id: HashMap<String, Vec<Uuid>> = HashMap::new();
+	for (ad_id, parsed_id) in ads {
+		ads_by_parsed_id.entry(parsed_id).or_default().push(ad_id);
+	}
+
+	let mut requests = Vec::new();
+	for item in items {
+		let state = match state_for_avito_status(&item.avito_status) {
+			Some(state) => state,
+			None => continue,
+		};
+		// Moderation messages explain rejections and blocks
+		let reason = item
+			.messages
+			.iter()
+			.map(|message| message.title.clone())
+			.collect::<Vec<String>>()
+			.join("; ");
+		let reason = if reason.is_empty() {
+			format!("avito_status: {}", item.avito_status)
+		} else {
+			format!("avito_status: {} ({})", item.avito_status, reason)
+		};
+		for ad_id in ads_by_parsed_id.get(&item.ad_id).into_iter().flatten() {
+			requests.push(AdStateChangeRequest {
+				ad_id: *ad_id,
+				to: state,
+				reason: Some(reason.clone()),
+			});
+		}
+	}
+
+	let mut tx = data.db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	let result = transition_ad_states(
+		&mut tx,
+		&requests,
+		TransitionSource::Autoload,
+		Some(report_id),
+	)
+	.await?;
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	println!(
+		"Autoload report {} changed the state of {} ads, {} transitions rejected",
+		report_id,
+		result.changed.len(),
+		result.rejected.len()
+	);
+
+	Ok(result)
+}
+
 #[post("/avito/fetch-and-update-ads")]
 #[has_any_role("Role::Admin", type = "Role")]
 pub async fn fetch_and_update_avito_ads(
@@ -178,6 +266,20 @@ pub async fn fetch_and_update_avito_ads(
 ) -> Result<HttpResponse, ApiError> {
 	let avito_token = opts.avito_token.clone();
 
+	let account_exists = sqlx::query_scalar::<_, bool>(
+		"SELECT EXISTS(SELECT 1 FROM avito_accounts WHERE account_id = $1)",
+	)
+	.bind(opts.account_id)
+	.fetch_one(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch account: {}", e)))?;
+	if !account_exists {
+		return Ok(HttpResponse::NotFound().json(serde_json::json!({
+			"status": "error",
+			"message": "Avito account not found"
+		})));
+	}
+
 	// Fetch reports
 	let reports_response = fetch_avito_reports(&avito_token).await?;
 
@@ -192,11 +294,15 @@ pub async fn fetch_and_update_avito_ads(
 
 	// Update the avito_ads table
 	update_avito_ads_table(&data, &all_items).await?;
+	let transitions =
+		apply_report_statuses(&data, opts.account_id, latest_report.id, &all_items).await?;
 
 	Ok(HttpResponse::Ok().json(serde_json::json!({
 		"status": "success",
 		"message": "Avito ads updated successfully",
 		"report_id": latest_report.id,
-		"items_processed": &all_items.len()
+		"items_processed": &all_items.len(),
+		"status_changes": transitions.changed.len(),
+		"status_changes_rejected": transitions.rejected.len()
 	})))
 }
@@ -0,0 +1,128 @@
+use crate::{
+	api::avito_ads::{
+		feed_belongs_to_account, get_or_create_manual_feed, insert_ad, load_ad_fields,
+	},
+	controllers::{avito_ads::reject_duplicate_ad, avito_images::spawn_image_checks},
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, CreateAdFromSourceSchema},
+	utils::ad_fields::apply_field_overrides,
+	AppState,
+};
+use actix_web::{
+	post,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Copy an existing ad's fields into a new ad in the same or another feed
+#[post("/avito/ads/{ad_id}/clone")]
+pub async fn clone_avito_ad(
+	path: Path<Uuid>,
+	body: web::Json<CreateAdFromSourceSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let ad_id = path.into_inner();
+
+	let source = sqlx::query_as::<_, (Uuid,)>(
+		r#"
+        SELECT a.feed_id
+        FROM avito_ads a
+        JOIN avito_feeds f ON a.feed_id = f.feed_id
+        WHERE a.ad_id = $1 AND f.account_id = $2
+        "#,
+	)
+	.bind(ad_id)
+	.bind(body.account_id)
+	.fetch_optional(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ad: {}", e)))?;
+
+	let source_feed_id = match source {
+		Some((feed_id,)) => feed_id,
+		None => {
+			return Ok(HttpResponse::NotFound().json(json!({
+				"status": "error",
+				"message": "Ad not found or does not belong to the specified account"
+			})));
+		}
+	};
+
+	let fields = load_ad_fields(&data.db, &[ad_id])
+		.await?
+		.remove(&ad_id)
+		.unwrap_or_default();
+
+	// Copies stay next to the original unless another feed is given
+	let target_feed_id = body.feed_id.unwrap_or(source_feed_id);
+	create_ad_from_fields(&data, &body, Some(target_feed_id), Some(ad_id), fields).await
+}
+
+// Create an ad from prepared fields plus request overrides. The new ad always gets
+// a freshly generated Id unless the overrides set one explicitly. A copy is not
+// rejected as a duplicate of `source_ad_id`, the ad it was copied from.
+pub async fn create_ad_from_fields(
+	data: &web::Data<AppState>,
+	body: &CreateAdFromSourceSchema,
+	feed_id: Option<Uuid>,
+	source_ad_id: Option<Uuid>,
+	mut fields: HashMap<String, String>,
+) -> Result<HttpResponse, ApiError> {
+	if let Some(feed_id) = feed_id {
+		if !feed_belongs_to_account(&data.db, feed_id, body.account_id).await? {
+			return Ok(HttpResponse::NotFound().json(json!({
+				"status": "error",
+				"message": "Feed not found or does not belong to the specified account"
+			})));
+		}
+	}
+
+	fields.remove("Id");
+	apply_field_overrides(&mut fields, &body.overrides);
+	let parsed_id = fields
+		.get("Id")
+		.cloned()
+		.unwrap_or_else(|| Uuid::new_v4().to_string());
+	fields.insert("Id".to_string(), parsed_id.clone());
+
+	if !body.force {
+		if let Some(response) =
+			reject_duplicate_ad(&data.db, body.account_id, &fields, source_ad_id).await?
+		{
+			return Ok(response);
+		}
+	}
+
+	let mut tx = data.db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	let feed_id = match feed_id {
+		Some(feed_id) => feed_id,
+		None => get_or_create_manual_feed(&mut tx, body.account_id).await?,
+	};
+	let ad_id = insert_ad(&mut tx, feed_id, &parsed_id, &fields).await?;
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	println!(
+		"Created ad {} in feed {} with Id {}",
+		ad_id, feed_id, parsed_id
+	);
+	spawn_image_checks(data.clone(), vec![ad_id]);
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"ad_id": ad_id,
+			"feed_id": feed_id,
+			"parsed_id": parsed_id,
+			"fields": fields
+		})
+	})))
+}
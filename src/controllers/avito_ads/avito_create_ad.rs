@@ -1,4 +1,11 @@
-use crate::{jwt_auth::JwtMiddleware, models::ApiError, AppState};
+use crate::{
+	api::avito_ads::get_or_create_manual_feed,
+	controllers::{avito_ads::reject_duplicate_ad, avito_images::spawn_image_checks},
+	jwt_auth::JwtMiddleware,
+	models::ApiError,
+	utils::ad_fields::fields_from_json,
+	AppState,
+};
 use actix_web::{
 	post,
 	web::{self},
@@ -12,6 +19,9 @@ use uuid::Uuid;
 pub struct CreateAdRequest {
 	pub fields: HashMap<String, serde_json::Value>,
 	pub account_id: Option<Uuid>,
+	/// Create even when a near-duplicate ad exists
+	#[serde(default)]
+	pub force: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -34,52 +44,20 @@ pub async fn avito_create_ad(
 
 	dbg!(&account_id);
 
+	if !request.force {
+		let fields = fields_from_json(&request.fields);
+		if let Some(response) = reject_duplicate_ad(&data.db, account_id, &fields, None).await? {
+			return Ok(response);
+		}
+	}
+
 	// Start transaction
 	let mut tx = data.db.begin().await.map_err(|e| {
 		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
 	})?;
 
-	// Check if a feed with category "MANUAL_CREATE" already exists for this account
-	let existing_feed = sqlx::query!(
-		r#"
-	       SELECT feed_id
-	       FROM avito_feeds
-	       WHERE account_id = $1 AND category = $2
-	       ORDER BY created_ts DESC
-	       LIMIT 1
-	       "#,
-		account_id,
-		"MANUAL_CREATE"
-	)
-	.fetch_optional(&mut *tx)
-	.await
-	.map_err(|e| {
-		ApiError::InternalServerError(format!("Failed to check for existing feed: {}", e))
-	})?;
-
-	let feed_id = if let Some(feed) = existing_feed {
-		// Use existing feed
-		println!("Using existing feed with ID: {}", feed.feed_id);
-		feed.feed_id
-	} else {
-		// Create a new feed
-		let new_feed_id = Uuid::new_v4();
-		println!("Creating new feed with ID: {}", new_feed_id);
-		sqlx::query!(
-			r#"
-	           INSERT INTO avito_feeds (feed_id, account_id, category)
-	           VALUES ($1, $2, $3)
-	           "#,
-			new_feed_id,
-			account_id,
-			"MANUAL_CREATE"
-		)
-		.execute(&mut *tx)
-		.await
-		.map_err(|e| ApiError::InternalServerError(format!("Failed to create feed: {}", e)))?;
-		println!("Feed created successfully with ID: {}", new_feed_id);
-		new_feed_id
-	};
+	let feed_id = get_or_create_manual_feed(&mut tx, account_id).await?;
+	println!("Using feed with ID: {}", feed_id);
 
 	// Insert the ad record
 	println!("Creating ad with ID: {} for feed ID: {}", ad_id, feed_id);
@@ -90,8 +68,8 @@ pub async fn avito_create_ad(
         "#,
 		ad_id,
 		feed_id,
-		true,      // is_active
-		"created"  // status
+		true,    // is_active
+		"draft"  // status
 	)
 	.execute(&mut *tx)
 	.await
@@ -239,6 +217,8 @@ pub async fn avito_create_ad(
 		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
 	})?;
 
+	spawn_image_checks(data.clone(), vec![ad_id]);
+
 	Ok(HttpResponse::Ok().json(CreateAdResponse {
 		ad_id,
 		message: "Ad created successfully".to_string(),
@@ -1,4 +1,7 @@
-use crate::{jwt_auth::JwtMiddleware, models::ApiError, AppState};
+use crate::{
+	controllers::avito_images::spawn_image_checks, jwt_auth::JwtMiddleware, models::ApiError,
+	AppState,
+};
 use actix_web::{
 	post,
 	web::{self},
@@ -229,6 +232,8 @@ pub async fn avito_update_ad(
 		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
 	})?;
 
+	spawn_image_checks(data.clone(), vec![ad_id]);
+
 	Ok(HttpResponse::Ok().json(UpdateAdResponse {
 		ad_id,
 		message: "Ad updated successfully".to_string(),
@@ -443,6 +448,8 @@ pub async fn avito_batch_update_ads(
 		updated_ads.push(ad_id);
 	}
 
+	spawn_image_checks(data.clone(), updated_ads.clone());
+
 	Ok(HttpResponse::Ok().json(BatchUpdateAdResponse {
 		updated_ads: updated_ads.clone(),
 		message: format!("Successfully updated {} ads", updated_ads.len()),
@@ -1,9 +1,19 @@
+pub mod avito_ad_duplicates;
+pub mod avito_ad_schedule;
+pub mod avito_ad_status;
 pub mod avito_ads;
+pub mod avito_clone_ad;
 pub mod avito_create_ad;
 pub mod avito_delete_ad;
 pub mod avito_update_ad;
+pub mod search_avito_ads;
 
+pub use self::avito_ad_duplicates::*;
+pub use self::avito_ad_schedule::*;
+pub use self::avito_ad_status::*;
 pub use self::avito_ads::*;
+pub use self::avito_clone_ad::*;
 pub use self::avito_create_ad::*;
 pub use self::avito_delete_ad::*;
 pub use self::avito_update_ad::*;
+pub use self::search_avito_ads::*;
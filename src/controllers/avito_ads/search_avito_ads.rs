@@ -0,0 +1,85 @@
+use crate::{
+	api::avito_ads::{
+		count_search_facet, effective_sort, search_ads, search_sort_key, AD_SEARCH_KEYSET,
+	},
+	jwt_auth::JwtMiddleware,
+	models::{AdSearchSchema, ApiError, FilterOptions, PageCursor},
+	utils::cursor::decode_cursor,
+	AppState,
+};
+use actix_web::{
+	post,
+	web::{self},
+	HttpResponse,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+const MAX_FACETS: usize = 10;
+
+// Full-text search over ad text with tag/status/feed/account filters and facet counts
+#[post("/avito/ads/search")]
+pub async fn search_avito_ads_handler(
+	body: web::Json<AdSearchSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let (sort, descending) = search_sort_key(effective_sort(&body.filters, body.sort));
+
+	if body.facets.len() > MAX_FACETS {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": format!("At most {} facets can be requested", MAX_FACETS)
+		})));
+	}
+
+	// A cursor only continues the search it was taken from
+	if let Some(cursor) = &body.cursor {
+		match decode_cursor::<PageCursor>(cursor) {
+			Some(position) if position.sort == sort && position.descending == descending => {}
+			_ => {
+				return Ok(HttpResponse::BadRequest().json(json!({
+					"status": "error",
+					"message": "Invalid cursor for this search"
+				})));
+			}
+		}
+	}
+	let first_page = body.cursor.is_none();
+
+	let opts = FilterOptions {
+		page: None,
+		limit: body.limit.map(|limit| limit.max(1) as usize),
+		cursor: body.cursor.clone(),
+		sort: Some(sort.to_string()),
+		order: Some(if descending { "desc" } else { "asc" }.to_string()),
+		// Totals and facets don't change between pages, so only the first page computes them
+		with_total: Some(first_page),
+	};
+	let request = match AD_SEARCH_KEYSET.request(&opts) {
+		Ok(request) => request,
+		Err(message) => {
+			return Ok(HttpResponse::BadRequest().json(json!({
+				"status": "error",
+				"message": message
+			})));
+		}
+	};
+
+	let page = search_ads(&data.db, &body.filters, &request).await?;
+
+	let mut facets = HashMap::new();
+	if first_page {
+		for facet in &body.facets {
+			let counts = count_search_facet(&data.db, &body.filters, facet).await?;
+			facets.insert(facet.clone(), counts);
+		}
+	}
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": page.items,
+		"facets": facets,
+		"pagination": page.pagination()
+	})))
+}
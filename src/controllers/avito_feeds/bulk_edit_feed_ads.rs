@@ -0,0 +1,194 @@
+use crate::{
+	api::avito_ads::{delete_ad_fields, feed_belongs_to_account, insert_ad_fields, load_ad_fields},
+	controllers::avito_images::spawn_image_checks,
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, BulkEditOperation, BulkEditSchema, FieldChange},
+	utils::bulk_edit::{apply_operations, diff_fields, matches_filter},
+	AppState,
+};
+use actix_web::{
+	post,
+	web::{self},
+	HttpResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+// Changed ads listed in a response; counts always cover every matched ad
+const PREVIEW_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+pub struct BulkEditPath {
+	pub feed_id: Uuid,
+}
+
+struct AdChanges {
+	ad_id: Uuid,
+	parsed_id: String,
+	changes: Vec<FieldChange>,
+}
+
+// Show which ads a bulk edit would touch and how, without saving anything
+#[post("/avito/feeds/{feed_id}/bulk-edit/preview")]
+pub async fn preview_bulk_edit_handler(
+	path: web::Path<BulkEditPath>,
+	body: web::Json<BulkEditSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	run_bulk_edit(&data, path.feed_id, &body, false).await
+}
+
+#[post("/avito/feeds/{feed_id}/bulk-edit")]
+pub async fn bulk_edit_handler(
+	path: web::Path<BulkEditPath>,
+	body: web::Json<BulkEditSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	run_bulk_edit(&data, path.feed_id, &body, true).await
+}
+
+async fn run_bulk_edit(
+	data: &web::Data<AppState>,
+	feed_id: Uuid,
+	body: &BulkEditSchema,
+	commit: bool,
+) -> Result<HttpResponse, ApiError> {
+	if body.operations.is_empty() {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "No operations given"
+		})));
+	}
+	// Id is the ad's identity in Avito autoload and is not edited in bulk
+	let touches_id = body.operations.iter().any(|op| match op {
+		BulkEditOperation::Set { tag, .. } | BulkEditOperation::Delete { tag } => tag == "Id",
+		BulkEditOperation::Replace { tags, .. } => tags
+			.as_ref()
+			.map(|tags| tags.iter().any(|tag| tag == "Id"))
+			.unwrap_or(false),
+		_ => false,
+	});
+	if touches_id {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "The Id tag can't be changed in bulk"
+		})));
+	}
+
+	let invalid_factor = body.operations.iter().any(|op| match op {
+		BulkEditOperation::MultiplyPrice { factor, .. } => !(factor.is_finite() && *factor > 0.0),
+		_ => false,
+	});
+	if invalid_factor {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "Price factor must be greater than zero"
+		})));
+	}
+
+	if !feed_belongs_to_account(&data.db, feed_id, body.account_id).await? {
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Feed not found or does not belong to the specified account"
+		})));
+	}
+
+	let ads = sqlx::query_as::<_, (Uuid, String, String)>(
+		r#"
+        SELECT ad_id, COALESCE(parsed_id, ''), COALESCE(status, '')
+        FROM avito_ads
+        WHERE feed_id = $1
+        ORDER BY created_ts, ad_id
+        "#,
+	)
+	.bind(feed_id)
+	.fetch_all(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))?;
+
+	let ad_ids: Vec<Uuid> = ads.iter().map(|(ad_id, _, _)| *ad_id).collect();
+	let ads_fields = load_ad_fields(&data.db, &ad_ids).await?;
+
+	let mut matched = 0;
+	let mut changed: Vec<AdChanges> = Vec::new();
+	for (ad_id, parsed_id, status) in &ads {
+		let fields = ads_fields.get(ad_id).cloned().unwrap_or_default();
+		if !matches_filter(&fields, status, &body.filter) {
+			continue;
+		}
+		matched += 1;
+
+		let new_fields = apply_operations(&fields, &body.operations);
+		let changes = diff_fields(&fields, &new_fields);
+		if !changes.is_empty() {
+			changed.push(AdChanges {
+				ad_id: *ad_id,
+				parsed_id: parsed_id.clone(),
+				changes,
+			});
+		}
+	}
+
+	if commit && !changed.is_empty() {
+		let mut removed_fields = Vec::new();
+		let mut new_fields = Vec::new();
+		for ad in &changed {
+			for change in &ad.changes {
+				removed_fields.push((ad.ad_id, change.tag.clone()));
+				if let Some(value) = &change.new {
+					new_fields.push((ad.ad_id, change.tag.clone(), value.clone()));
+				}
+			}
+		}
+
+		let mut tx = data.db.begin().await.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+		})?;
+
+		delete_ad_fields(&mut tx, &removed_fields).await?;
+		insert_ad_fields(&mut tx, &new_fields).await?;
+
+		tx.commit().await.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+		})?;
+
+		println!(
+			"Bulk edit updated {} ads in feed {}",
+			changed.len(),
+			feed_id
+		);
+
+		let images_changed: Vec<Uuid> = changed
+			.iter()
+			.filter(|ad| ad.changes.iter().any(|change| change.tag == "Images"))
+			.map(|ad| ad.ad_id)
+			.collect();
+		if !images_changed.is_empty() {
+			spawn_image_checks(data.clone(), images_changed);
+		}
+	}
+
+	let preview: Vec<serde_json::Value> = changed
+		.iter()
+		.take(PREVIEW_LIMIT)
+		.map(|ad| {
+			json!({
+				"ad_id": ad.ad_id,
+				"parsed_id": ad.parsed_id,
+				"changes": ad.changes
+			})
+		})
+		.collect();
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"committed": commit,
+		"ads_total": ads.len(),
+		"ads_matched": matched,
+		"ads_changed": changed.len(),
+		"changes": preview
+	})))
+}
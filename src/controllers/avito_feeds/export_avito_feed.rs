@@ -0,0 +1,290 @@
+use crate::{
+	api::avito_ads::{delete_ad_fields, insert_ad_fields, load_ad_fields},
+	controllers::avito_images::spawn_image_checks,
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, ImportRowError},
+	utils::{
+		multipart::read_multipart_form,
+		spreadsheet::{read_sheet_rows, write_csv, write_xlsx},
+	},
+	AppState,
+};
+use actix_multipart::Multipart;
+use actix_web::{
+	get, post,
+	web::{self},
+	HttpResponse,
+};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use uuid::Uuid;
+
+// Column with our internal ad id, used to match rows back on re-import
+const AD_ID_COLUMN: &str = "ad_id";
+
+// Main Avito tags go first in this order, the rest follow alphabetically
+const LEADING_TAGS: [&str; 10] = [
+	"Id",
+	"Category",
+	"GoodsType",
+	"Title",
+	"Description",
+	"Price",
+	"Images",
+	"Address",
+	"ContactPhone",
+	"Condition",
+];
+
+#[derive(Deserialize)]
+pub struct FeedExportPath {
+	pub feed_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct FeedExportQuery {
+	pub format: Option<String>,
+}
+
+#[get("/avito/feeds/{feed_id}/export")]
+pub async fn export_avito_feed(
+	path: web::Path<FeedExportPath>,
+	opts: web::Query<FeedExportQuery>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let feed_id = path.feed_id;
+	let format = opts.format.clone().unwrap_or_else(|| "csv".to_string());
+	if format != "csv" && format != "xlsx" {
+		return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+			"status": "error",
+			"message": "Unsupported format, expected csv or xlsx"
+		})));
+	}
+
+	let ad_rows = sqlx::query!(
+		r#"SELECT ad_id, COALESCE(parsed_id, '') as parsed_id
+        FROM avito_ads
+        WHERE feed_id = $1
+        ORDER BY created_ts, ad_id"#,
+		feed_id
+	)
+	.fetch_all(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))?;
+
+	let ad_ids: Vec<Uuid> = ad_rows.iter().map(|row| row.ad_id).collect();
+	let mut ads_fields = load_ad_fields(&data.db, &ad_ids).await?;
+
+	// Id lives in avito_ads.parsed_id rather than in the EAV tables
+	for row in &ad_rows {
+		let parsed_id = row.parsed_id.clone().unwrap_or_default();
+		if !parsed_id.is_empty() {
+			ads_fields
+				.entry(row.ad_id)
+				.or_default()
+				.insert("Id".to_string(), parsed_id);
+		}
+	}
+
+	let tags = feed_export_columns(ads_fields.values());
+	let mut headers = vec![AD_ID_COLUMN.to_string()];
+	headers.extend(tags.iter().cloned());
+
+	let rows: Vec<Vec<String>> = ad_rows
+		.iter()
+		.map(|row| {
+			let fields = ads_fields.get(&row.ad_id);
+			let mut values = vec![row.ad_id.to_string()];
+			values.extend(
+				tags.iter()
+					.map(|tag| fields.and_then(|f| f.get(tag)).cloned().unwrap_or_default()),
+			);
+			values
+		})
+		.collect();
+
+	let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+	let filename = format!("feed_{}_{}.{}", feed_id, date, format);
+
+	let (content_type, body) = if format == "xlsx" {
+		(
+			"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+			write_xlsx(&headers, &rows)?,
+		)
+	} else {
+		("text/csv", write_csv(&headers, &rows)?)
+	};
+
+	Ok(HttpResponse::Ok()
+		.content_type(content_type)
+		.append_header((
+			"Content-Disposition",
+			format!("attachment; filename=\"{}\"", filename),
+		))
+		.body(body))
+}
+
+// Stable column order: known Avito tags first, then every other tag alphabetically
+pub fn feed_export_columns<'a>(
+	ads_fields: impl Iterator<Item = &'a HashMap<String, String>>,
+) -> Vec<String> {
+	let all_tags: BTreeSet<String> = ads_fields.flat_map(|f| f.keys().cloned()).collect();
+
+	let mut columns: Vec<String> = LEADING_TAGS
+		.iter()
+		.filter(|tag| all_tags.contains(**tag))
+		.map(|tag| tag.to_string())
+		.collect();
+	columns.extend(
+		all_tags
+			.into_iter()
+			.filter(|tag| !LEADING_TAGS.contains(&tag.as_str())),
+	);
+	columns
+}
+
+// Apply an edited export back to the feed. Rows are matched by ad_id; every
+// column present in the file replaces that tag, an empty cell removes it.
+#[post("/avito/feeds/{feed_id}/reimport")]
+pub async fn reimport_avito_feed(
+	path: web::Path<FeedExportPath>,
+	payload: Multipart,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let feed_id = path.feed_id;
+	let form = read_multipart_form(payload).await?;
+
+	let file = match form.file {
+		Some(file) if !file.bytes.is_empty() => file,
+		_ => {
+			return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+				"status": "error",
+				"message": "No file uploaded"
+			})));
+		}
+	};
+
+	let rows = read_sheet_rows(&file.file_name, &file.bytes)?;
+
+	// Ads of the feed with their current Id, so an edited Id can't take another ad's
+	let feed_ads: HashMap<Uuid, Option<String>> = sqlx::query!(
+		"SELECT ad_id, parsed_id FROM avito_ads WHERE feed_id = $1",
+		feed_id
+	)
+	.fetch_all(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))?
+	.into_iter()
+	.map(|ad| (ad.ad_id, ad.parsed_id))
+	.collect();
+	let id_owners: HashMap<&str, Uuid> = feed_ads
+		.iter()
+		.filter_map(|(ad_id, parsed_id)| parsed_id.as_deref().map(|id| (id, *ad_id)))
+		.collect();
+
+	let mut errors = Vec::new();
+	let mut updated_ad_ids = Vec::new();
+	let mut removed_fields = Vec::new();
+	let mut new_fields = Vec::new();
+	let mut parsed_id_ad_ids = Vec::new();
+	let mut parsed_ids = Vec::new();
+	let mut seen_ad_ids = HashSet::new();
+	let mut seen_ids = HashSet::new();
+
+	for row in &rows {
+		let raw_ad_id = row.values.get(AD_ID_COLUMN).cloned().unwrap_or_default();
+		let ad_id = match Uuid::parse_str(&raw_ad_id) {
+			Ok(ad_id) if feed_ads.contains_key(&ad_id) => ad_id,
+			_ => {
+				errors.push(ImportRowError {
+					line: row.line,
+					ad_id: Some(raw_ad_id),
+					tag: Some(AD_ID_COLUMN.to_string()),
+					message: "Ad not found in this feed".to_string(),
+				});
+				continue;
+			}
+		};
+
+		// Otherwise the last of the repeated rows would silently win
+		if !seen_ad_ids.insert(ad_id) {
+			errors.push(ImportRowError {
+				line: row.line,
+				ad_id: Some(raw_ad_id),
+				tag: Some(AD_ID_COLUMN.to_string()),
+				message: "Duplicate ad_id, the ad is already updated by an earlier row".to_string(),
+			});
+			continue;
+		}
+		if let Some(id) = row.values.get("Id").filter(|id| !id.is_empty()) {
+			let taken = matches!(id_owners.get(id.as_str()), Some(owner) if *owner != ad_id);
+			if taken || !seen_ids.insert(id.clone()) {
+				errors.push(ImportRowError {
+					line: row.line,
+					ad_id: Some(id.clone()),
+					tag: Some("Id".to_string()),
+					message: format!("Duplicate Id {}", id),
+				});
+				continue;
+			}
+		}
+
+		for (tag, value) in &row.values {
+			if tag == AD_ID_COLUMN || tag.is_empty() {
+				continue;
+			}
+			if tag == "Id" {
+				if !value.is_empty() {
+					parsed_id_ad_ids.push(ad_id);
+					parsed_ids.push(value.clone());
+				}
+				continue;
+			}
+			removed_fields.push((ad_id, tag.clone()));
+			if !value.is_empty() {
+				new_fields.push((ad_id, tag.clone(), value.clone()));
+			}
+		}
+		updated_ad_ids.push(ad_id);
+	}
+
+	let mut tx = data.db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	delete_ad_fields(&mut tx, &removed_fields).await?;
+	insert_ad_fields(&mut tx, &new_fields).await?;
+
+	if !parsed_id_ad_ids.is_empty() {
+		sqlx::query!(
+			r#"
+            UPDATE avito_ads a
+            SET parsed_id = u.parsed_id
+            FROM UNNEST($1::uuid[], $2::varchar[]) AS u(ad_id, parsed_id)
+            WHERE a.ad_id = u.ad_id
+            "#,
+			&parsed_id_ad_ids,
+			&parsed_ids
+		)
+		.execute(&mut *tx)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to update Id: {}", e)))?;
+	}
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	spawn_image_checks(data.clone(), updated_ad_ids.clone());
+
+	Ok(HttpResponse::Ok().json(serde_json::json!({
+		"status": "success",
+		"message": "Re-import completed",
+		"feed_id": feed_id,
+		"rows_total": rows.len(),
+		"ads_updated": updated_ad_ids.len(),
+		"errors": errors
+	})))
+}
@@ -1,4 +1,5 @@
 use crate::{
+	api::avito_images::load_ad_image_checks,
 	jwt_auth::JwtMiddleware,
 	models::{AdResponse, ApiError},
 	AppState,
@@ -64,7 +65,9 @@ pub async fn get_avito_feed_ad(
             COALESCE(parsed_id, '') as parsed_id,
             COALESCE(is_active, true) as is_active,
             COALESCE(status, 'unknown') as status,
-            created_ts as "created_ts: chrono::DateTime<chrono::Utc>"
+            created_ts as "created_ts: chrono::DateTime<chrono::Utc>",
+            publish_at as "publish_at: chrono::DateTime<chrono::Utc>",
+            unpublish_at as "unpublish_at: chrono::DateTime<chrono::Utc>"
         FROM avito_ads
         WHERE ad_id = $1 AND feed_id = $2"#,
 		ad_id,
@@ -156,8 +159,16 @@ pub async fn get_avito_feed_ad(
 		fields,
 	};
 
+	// Latest image quality check results, empty until the first check has finished
+	let image_checks = load_ad_image_checks(&data.db, ad.ad_id).await?;
+
 	Ok(HttpResponse::Ok().json(serde_json::json!({
 		"status": "success",
-		"data": ad_response
+		"data": ad_response,
+		"image_checks": image_checks,
+		"schedule": {
+			"publish_at": ad.publish_at,
+			"unpublish_at": ad.unpublish_at
+		}
 	})))
 }
@@ -1,7 +1,8 @@
 use crate::{
+	api::shared::{Keyset, Page, SortKey},
 	jwt_auth::JwtMiddleware,
 	models::{
-		AdResponse, ApiError, FeedQueryParams, FeedResponse, FieldResponse, FieldValueResponse,
+		AdResponse, ApiError, FeedResponse, FieldResponse, FieldValueResponse, FilterOptions,
 	},
 	AppState,
 };
@@ -22,17 +23,59 @@ pub struct FeedIdPath {
 	pub feed_id: Uuid,
 }
 
+static FEED_ADS_KEYSET: Keyset = Keyset {
+	from: "avito_ads",
+	id_expr: "ad_id",
+	id_type: "uuid",
+	sort_keys: &[
+		SortKey {
+			name: "created_ts",
+			expr: "COALESCE(created_ts, 'epoch'::timestamptz)",
+			sql_type: "timestamptz",
+		},
+		SortKey {
+			name: "parsed_id",
+			expr: "COALESCE(parsed_id, '')",
+			sql_type: "text",
+		},
+		SortKey {
+			name: "status",
+			expr: "COALESCE(status, '')",
+			sql_type: "text",
+		},
+	],
+	default_descending: true,
+	default_limit: 10,
+	max_limit: 100,
+};
+
+#[derive(sqlx::FromRow)]
+struct FeedAdRow {
+	ad_id: Uuid,
+	avito_ad_id: Option<String>,
+	parsed_id: Option<String>,
+	is_active: Option<bool>,
+	status: Option<String>,
+	created_ts: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 #[get("/avito/feeds/{feed_id}")]
 pub async fn get_avito_feed_by_id(
 	path: web::Path<FeedIdPath>,
-	opts: web::Query<FeedQueryParams>,
+	opts: web::Query<FilterOptions>,
 	data: web::Data<AppState>,
 	_: JwtMiddleware,
 ) -> Result<HttpResponse, ApiError> {
 	let feed_id = path.feed_id;
-	let page = opts.page.unwrap_or(1);
-	let limit = opts.limit.unwrap_or(10);
-	let offset = (page - 1) * limit;
+	let request = match FEED_ADS_KEYSET.request(&opts) {
+		Ok(request) => request,
+		Err(message) => {
+			return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+				"status": "error",
+				"message": message
+			})));
+		}
+	};
 
 	// First, get the feed details
 	let feed_row = sqlx::query!(
@@ -56,53 +99,31 @@ pub async fn get_avito_feed_by_id(
 			return Ok(HttpResponse::Ok().json(serde_json::json!({
 				"status": "success",
 				"data": null,
-				"pagination": {
-					"page": page,
-					"limit": limit,
-					"total": 0,
-					"pages": 0
-				}
+				"pagination": Page::<FeedAdRow>::empty(&request).pagination()
 			})));
 		}
 	};
 
-	// Get total count of ads for this feed
-	let count_row = sqlx::query!(
-		r#"SELECT COUNT(*) as count FROM avito_ads WHERE feed_id = $1"#,
-		feed_id
-	)
-	.fetch_one(&data.db)
-	.await
-	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ad count: {}", e)))?;
-
-	let total_ads = count_row.count.unwrap_or(0) as u32;
-
-	// Get the paginated list of ads for this feed
-	let ad_rows = sqlx::query!(
-		r#"SELECT
-            ad_id,
-            COALESCE(avito_ad_id, '') as avito_ad_id,
-            COALESCE(parsed_id, '') as parsed_id,
-            COALESCE(is_active, true) as is_active,
-            COALESCE(status, 'unknown') as status,
-            created_ts as "created_ts: chrono::DateTime<chrono::Utc>"
-        FROM avito_ads
-        WHERE feed_id = $1
-        ORDER BY created_ts DESC
-        LIMIT $2 OFFSET $3"#,
-		feed_id,
-		limit as i64,
-		offset as i64
-	)
-	.fetch_all(&data.db)
-	.await
-	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))?;
+	// Get the requested page of ads for this feed
+	let page = FEED_ADS_KEYSET
+		.fetch::<FeedAdRow, _>(
+			&data.db,
+			&request,
+			"ad_id, avito_ad_id, parsed_id, is_active, status, created_ts",
+			|builder| {
+				builder.push(" AND feed_id = ");
+				builder.push_bind(feed_id);
+			},
+		)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))?;
+	let ad_rows = &page.items;
 
 	// Create ads map with empty fields initially and collect ad_ids
 	let mut ads_map: HashMap<Uuid, AdResponse> = HashMap::new();
 	let mut ad_ids: Vec<Uuid> = Vec::new();
 
-	for row in &ad_rows {
+	for row in ad_rows {
 		ads_map.insert(
 			row.ad_id,
 			AdResponse {
@@ -111,7 +132,7 @@ pub async fn get_avito_feed_by_id(
 				parsed_id: row.parsed_id.clone().unwrap_or_default(),
 				is_active: row.is_active.unwrap_or(true),
 				status: row.status.clone().unwrap_or_else(|| "unknown".to_string()),
-				created_ts: row.created_ts.unwrap(),
+				created_ts: row.created_ts.unwrap_or_else(chrono::Utc::now),
 				fields: Vec::new(),
 			},
 		);
@@ -206,7 +227,7 @@ pub async fn get_avito_feed_by_id(
 
 	// Convert HashMap to Vec maintaining the original order from ad_rows
 	let mut ads_vec: Vec<AdResponse> = Vec::new();
-	for row in &ad_rows {
+	for row in ad_rows {
 		if let Some(ad) = ads_map.get(&row.ad_id) {
 			ads_vec.push(ad.clone());
 		}
@@ -224,11 +245,6 @@ pub async fn get_avito_feed_by_id(
 	Ok(HttpResponse::Ok().json(serde_json::json!({
 		"status": "success",
 		"data": feed_response,
-		"pagination": {
-			"page": page,
-			"limit": limit,
-			"total": total_ads,
-			"pages": (total_ads as f64 / limit as f64).ceil() as u32
-		}
+		"pagination": page.pagination()
 	})))
 }
@@ -1,6 +1,7 @@
 use crate::{
+	api::shared::{Keyset, SortKey},
 	jwt_auth::JwtMiddleware,
-	models::{ApiError, FeedQueryParams, FeedResponse},
+	models::{ApiError, FeedResponse, FilterOptions},
 	AppState,
 };
 use actix_web::{
@@ -18,53 +19,78 @@ pub struct AccountIdRequest {
 	pub account_id: Uuid,
 }
 
+static FEEDS_KEYSET: Keyset = Keyset {
+	from: "avito_feeds",
+	id_expr: "feed_id",
+	id_type: "uuid",
+	sort_keys: &[
+		SortKey {
+			name: "created_ts",
+			expr: "COALESCE(created_ts, 'epoch'::timestamptz)",
+			sql_type: "timestamptz",
+		},
+		SortKey {
+			name: "category",
+			expr: "category",
+			sql_type: "text",
+		},
+	],
+	default_descending: true,
+	default_limit: 10,
+	max_limit: 100,
+};
+
+#[derive(sqlx::FromRow)]
+struct FeedRow {
+	feed_id: Uuid,
+	account_id: Uuid,
+	category: Option<String>,
+	created_ts: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 #[post("/avito/feeds")]
 pub async fn get_avito_feeds(
 	body: web::Json<AccountIdRequest>,
-	opts: web::Query<FeedQueryParams>,
+	opts: web::Query<FilterOptions>,
 	data: web::Data<AppState>,
 	_: JwtMiddleware,
 ) -> Result<HttpResponse, ApiError> {
 	let account_id = body.account_id;
-	let page = opts.page.unwrap_or(1);
-	let limit = opts.limit.unwrap_or(10);
-	let offset = (page - 1) * limit;
+	let request = match FEEDS_KEYSET.request(&opts) {
+		Ok(request) => request,
+		Err(message) => {
+			return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+				"status": "error",
+				"message": message
+			})));
+		}
+	};
 
 	// Fetch only the basic feed information
-	let feed_rows = sqlx::query!(
-		r#"
-        SELECT feed_id, account_id, category, created_ts
-        FROM avito_feeds
-        WHERE account_id = $1
-        ORDER BY created_ts DESC
-        LIMIT $2 OFFSET $3
-        "#,
-		account_id,
-		limit as i64,
-		offset as i64
-	)
-	.fetch_all(&data.db)
-	.await
-	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch feeds: {}", e)))?;
-
-	// Fetch total count of feeds for this account
-	let count_row = sqlx::query!(
-		r#"SELECT COUNT(*) as count FROM avito_feeds WHERE account_id = $1"#,
-		account_id
-	)
-	.fetch_one(&data.db)
-	.await
-	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch feed count: {}", e)))?;
-
-	let total_feeds = count_row.count.unwrap_or(0) as u32;
+	let page = FEEDS_KEYSET
+		.fetch::<FeedRow, _>(
+			&data.db,
+			&request,
+			"feed_id, account_id, category, created_ts",
+			|builder| {
+				builder.push(" AND account_id = ");
+				builder.push_bind(account_id);
+			},
+		)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch feeds: {}", e)))?;
 
 	// Convert to FeedResponse format
-	let feeds_vec: Vec<FeedResponse> = feed_rows
-		.into_iter()
+	let feeds_vec: Vec<FeedResponse> = page
+		.items
+		.iter()
 		.map(|row| FeedResponse {
 			feed_id: row.feed_id,
 			account_id: row.account_id,
-			category: row.category.unwrap_or_else(|| "unknown".to_string()),
+			category: row
+				.category
+				.clone()
+				.unwrap_or_else(|| "unknown".to_string()),
 			created_ts: row.created_ts.unwrap_or_else(|| chrono::Utc::now()),
 			ads: Vec::new(), // Empty ads list since we're only returning basic feed info
 		})
@@ -73,11 +99,6 @@ pub async fn get_avito_feeds(
 	Ok(HttpResponse::Ok().json(serde_json::json!({
 		"status": "success",
 		"data": feeds_vec,
-		"pagination": {
-			"page": page,
-			"limit": limit,
-			"total": total_feeds,
-			"pages": (total_feeds as f64 / limit as f64).ceil() as u32
-		}
+		"pagination": page.pagination()
 	})))
 }
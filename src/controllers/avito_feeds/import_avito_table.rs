@@ -0,0 +1,184 @@
+use crate::{
+	controllers::{avito_feeds::store_imported_feed, avito_import_profiles::fetch_import_profile},
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, ImportRowError, XmlAd},
+	utils::{
+		avito_requests::get_category_required_tags,
+		multipart::read_multipart_form,
+		spreadsheet::{read_sheet_rows, SheetRow},
+	},
+	AppState,
+};
+use actix_multipart::Multipart;
+use actix_web::{
+	post,
+	web::{self},
+	HttpResponse,
+};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+// Import ads from a CSV/XLSX file using a saved column-to-tag mapping profile.
+// Multipart fields: account_id, profile_id, file and an optional avito_token
+// used to fetch mandatory tags of the profile's category from Avito.
+#[post("/avito/import-table")]
+pub async fn import_avito_table(
+	payload: Multipart,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let form = read_multipart_form(payload).await?;
+
+	let account_id = form
+		.fields
+		.get("account_id")
+		.and_then(|value| Uuid::parse_str(value).ok());
+	let profile_id = form
+		.fields
+		.get("profile_id")
+		.and_then(|value| Uuid::parse_str(value).ok());
+
+	let (account_id, profile_id) = match (account_id, profile_id) {
+		(Some(account_id), Some(profile_id)) => (account_id, profile_id),
+		_ => {
+			return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+				"status": "error",
+				"message": "account_id and profile_id are required"
+			})));
+		}
+	};
+
+	let file = match form.file {
+		Some(file) if !file.bytes.is_empty() => file,
+		_ => {
+			return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+				"status": "error",
+				"message": "No file uploaded"
+			})));
+		}
+	};
+
+	let profile = match fetch_import_profile(&data, profile_id).await? {
+		Some(profile) if profile.account_id == account_id => profile,
+		_ => {
+			return Ok(HttpResponse::NotFound().json(serde_json::json!({
+				"status": "error",
+				"message": "Import profile not found or does not belong to the specified account"
+			})));
+		}
+	};
+
+	// Mandatory tags: the ones saved in the profile plus the category schema from Avito
+	let mut required_tags = profile.required_tags.clone();
+	if let (Some(avito_token), Some(avito_slug)) =
+		(form.fields.get("avito_token"), profile.avito_slug.as_ref())
+	{
+		let schema_tags = get_category_required_tags(avito_token, avito_slug)
+			.await
+			.map_err(|e| ApiError::Other(format!("Failed to fetch category schema: {}", e)))?;
+		for tag in schema_tags {
+			if !required_tags.contains(&tag) {
+				required_tags.push(tag);
+			}
+		}
+	}
+
+	let rows = read_sheet_rows(&file.file_name, &file.bytes)?;
+	println!("Read {} rows from {}", rows.len(), file.file_name);
+
+	let (ads, errors) = map_sheet_rows(
+		&rows,
+		&profile.mapping,
+		profile.category.as_deref(),
+		&required_tags,
+	);
+	println!(
+		"Mapped {} ads from {} rows, {} problems found",
+		ads.len(),
+		rows.len(),
+		errors.len()
+	);
+
+	let feed_id = if ads.is_empty() {
+		None
+	} else {
+		Some(store_imported_feed(&data, account_id, "IMPORT", &ads).await?)
+	};
+
+	Ok(HttpResponse::Ok().json(serde_json::json!({
+		"status": "success",
+		"message": "Import completed",
+		"feed_id": feed_id,
+		"rows_total": rows.len(),
+		"ads_processed": ads.len(),
+		"errors": errors
+	})))
+}
+
+// Turn spreadsheet rows into ads using the column -> tag mapping.
+// Rows missing a mandatory tag or repeating an Id are rejected and reported.
+pub fn map_sheet_rows(
+	rows: &[SheetRow],
+	mapping: &HashMap<String, String>,
+	category: Option<&str>,
+	required_tags: &[String],
+) -> (Vec<XmlAd>, Vec<ImportRowError>) {
+	let mut ads = Vec::new();
+	let mut errors = Vec::new();
+	let mut seen_ids = HashSet::new();
+
+	for row in rows {
+		let mut fields = HashMap::new();
+		for (column, tag) in mapping {
+			if let Some(value) = row.values.get(column) {
+				if !value.trim().is_empty() {
+					fields.insert(tag.clone(), value.trim().to_string());
+				}
+			}
+		}
+
+		if let Some(category) = category {
+			fields
+				.entry("Category".to_string())
+				.or_insert_with(|| category.to_string());
+		}
+
+		// Id is never missing, rows without one get it generated below
+		let missing: Vec<&String> = required_tags
+			.iter()
+			.filter(|tag| tag.as_str() != "Id" && !fields.contains_key(*tag))
+			.collect();
+		if !missing.is_empty() {
+			for tag in missing {
+				errors.push(ImportRowError {
+					line: row.line,
+					ad_id: fields.get("Id").cloned(),
+					tag: Some(tag.clone()),
+					message: format!("Mandatory tag {} is empty", tag),
+				});
+			}
+			continue;
+		}
+
+		// Rows without an Id column get a generated one so they can be re-imported later
+		let id = fields
+			.get("Id")
+			.cloned()
+			.unwrap_or_else(|| Uuid::new_v4().to_string());
+		fields.insert("Id".to_string(), id.clone());
+
+		if !seen_ids.insert(id.clone()) {
+			errors.push(ImportRowError {
+				line: row.line,
+				ad_id: Some(id.clone()),
+				tag: Some("Id".to_string()),
+				message: format!("Duplicate Id {}", id),
+			});
+			continue;
+		}
+
+		ads.push(XmlAd { id, fields });
+	}
+
+	(ads, errors)
+}
@@ -1,6 +1,8 @@
 use crate::{
+	controllers::avito_images::spawn_feed_image_checks,
 	jwt_auth::JwtMiddleware,
-	models::{ApiError, XmlAd},
+	models::{AdState, ApiError, ImportRowError, PositionedXmlAd, XmlAd},
+	utils::xml_position::LineCounter,
 	AppState,
 };
 use actix_web::{
@@ -40,6 +42,37 @@ pub async fn import_avito_xml(
 	};
 
 	// Fetch XML data
+	let xml_data = fetch_xml(xml_url).await?;
+
+	// Parse XML and extract ads
+	println!("Parsing XML data with length: {}", xml_data.len());
+	let ads = parse_xml_ads(&xml_data)?;
+	println!("Parsed {} ads from XML", ads.len());
+
+	// Print debug information about the first few ads
+	for (i, ad) in ads.iter().take(3).enumerate() {
+		println!("Ad {}: id={}, fields={}", i, ad.id, ad.fields.len());
+		if let Some(images) = ad.fields.get("Images") {
+			println!("  Images field: {}", images);
+		} else {
+			println!("  No Images field found");
+		}
+	}
+
+	println!("Parsed {} ads from XML", ads.len());
+
+	let feed_id = store_imported_feed(&data, account_id, "IMPORT", &ads).await?;
+
+	Ok(HttpResponse::Ok().json(serde_json::json!({
+		"status": "success",
+		"message": "Import completed successfully",
+		"feed_id": feed_id,
+		"ads_processed": ads.len()
+	})))
+}
+
+// Download a feed file by URL
+pub async fn fetch_xml(xml_url: &str) -> Result<String, ApiError> {
 	let client = Client::builder()
 		.timeout(Duration::from_secs(30))
 		.build()
@@ -58,28 +91,19 @@ pub async fn import_avito_xml(
 		)));
 	}
 
-	let xml_data = response
+	response
 		.text()
 		.await
-		.map_err(|e| ApiError::InternalServerError(format!("Failed to read response: {}", e)))?;
-
-	// Parse XML and extract ads
-	println!("Parsing XML data with length: {}", xml_data.len());
-	let ads = parse_xml_ads(&xml_data)?;
-	println!("Parsed {} ads from XML", ads.len());
-
-	// Print debug information about the first few ads
-	for (i, ad) in ads.iter().take(3).enumerate() {
-		println!("Ad {}: id={}, fields={}", i, ad.id, ad.fields.len());
-		if let Some(images) = ad.fields.get("Images") {
-			println!("  Images field: {}", images);
-		} else {
-			println!("  No Images field found");
-		}
-	}
-
-	println!("Parsed {} ads from XML", ads.len());
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to read response: {}", e)))
+}
 
+// Create a feed with the given category for the account and store the parsed ads in it
+pub async fn store_imported_feed(
+	data: &web::Data<AppState>,
+	account_id: Uuid,
+	category: &str,
+	ads: &[XmlAd],
+) -> Result<Uuid, ApiError> {
 	// Start transaction
 	let mut tx = data.db.begin().await.map_err(|e| {
 		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
@@ -94,7 +118,7 @@ pub async fn import_avito_xml(
         "#,
 		feed_id,
 		account_id,
-		"IMPORT"
+		category
 	)
 	.execute(&mut *tx)
 	.await
@@ -102,23 +126,20 @@ pub async fn import_avito_xml(
 
 	// Batch process ads
 	println!("Starting batch processing of {} ads", ads.len());
-	batch_process_ads(&mut tx, &feed_id, &ads).await?;
+	batch_process_ads(&mut tx, &feed_id, ads).await?;
 	println!("Finished batch processing");
 
 	tx.commit().await.map_err(|e| {
 		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
 	})?;
 
-	Ok(HttpResponse::Ok().json(serde_json::json!({
-		"status": "success",
-		"message": "Import completed successfully",
-		"feed_id": feed_id,
-		"ads_processed": ads.len()
-	})))
+	spawn_feed_image_checks(data.clone(), feed_id);
+
+	Ok(feed_id)
 }
 
 // Batch process ads for better performance
-async fn batch_process_ads(
+pub async fn batch_process_ads(
 	tx: &mut Transaction<'_, Postgres>,
 	feed_id: &Uuid,
 	ads: &[XmlAd],
@@ -136,7 +157,7 @@ async fn batch_process_ads(
 		parsed_ids.push(ad.id.clone()); // Clone the String
 		feed_ids.push(feed_id.clone()); // Clone the Uuid
 		is_active_flags.push(true);
-		statuses.push("active".to_string());
+		statuses.push(AdState::Ready.as_str().to_string());
 	}
 
 	// Batch insert ads
@@ -253,34 +274,88 @@ async fn batch_process_ads(
 }
 
 pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
-	let mut reader = Reader::from_str(xml_data);
+	let (ads, parse_error) = parse_xml_ads_positioned(xml_data);
+	if let Some(e) = parse_error {
+		return Err(ApiError::Other(format!("Line {}: {}", e.line, e.message)));
+	}
+
+	// Ads without an Id can't be matched to Avito listings, so they are not imported
+	let ads: Vec<XmlAd> = ads
+		.into_iter()
+		.map(|positioned| positioned.ad)
+		.filter(|ad| !ad.id.is_empty())
+		.collect();
+
+	println!("Finished parsing {} ads", ads.len());
+	Ok(ads)
+}
+
+fn utf8_error(
+	line: usize,
+	ad: &Option<PositionedXmlAd>,
+	e: impl std::fmt::Display,
+) -> ImportRowError {
+	ImportRowError {
+		line,
+		ad_id: ad
+			.as_ref()
+			.map(|a| a.ad.id.clone())
+			.filter(|id| !id.is_empty()),
+		tag: None,
+		message: format!("UTF-8 error: {}", e),
+	}
+}
+
+// Parse every <Ad> (including ones without an Id) and remember where each ad and tag
+// starts in the file. A malformed document stops parsing: the ads read before the
+// error are returned together with it.
+pub fn parse_xml_ads_positioned(xml_data: &str) -> (Vec<PositionedXmlAd>, Option<ImportRowError>) {
 	let mut ads = Vec::new();
+	let parse_error = read_positioned_ads(xml_data, &mut ads).err();
+	(ads, parse_error)
+}
+
+fn read_positioned_ads(
+	xml_data: &str,
+	ads: &mut Vec<PositionedXmlAd>,
+) -> Result<(), ImportRowError> {
+	let mut reader = Reader::from_str(xml_data);
+	let mut lines = LineCounter::new(xml_data);
 	let mut buf = Vec::new();
-	let mut current_ad: Option<XmlAd> = None;
+	let mut current_ad: Option<PositionedXmlAd> = None;
 	let mut current_path = Vec::new();
+	let mut current_lines = Vec::new();
 	let mut current_values = String::new();
 	let mut in_ad = false;
 	let mut delivery_buffer = Vec::new();
 	let mut images_buffer: Vec<String> = Vec::new();
+	let mut image_lines: Vec<usize> = Vec::new();
 
 	loop {
+		let line = lines.line_at(reader.buffer_position() as usize);
 		match reader.read_event_into(&mut buf) {
 			Ok(Event::Start(e)) => {
 				let name = std::str::from_utf8(e.name().as_ref())
-					.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?
+					.map_err(|e| utf8_error(line, &current_ad, e))?
 					.to_string();
 
-				// println!("Start element: {}, current path: {:?}", name, current_path);
 				current_path.push(name.clone());
+				current_lines.push(line);
 
 				if name == "Ad" {
 					in_ad = true;
-					current_ad = Some(XmlAd {
-						id: String::new(),
-						fields: HashMap::new(),
+					current_ad = Some(PositionedXmlAd {
+						ad: XmlAd {
+							id: String::new(),
+							fields: HashMap::new(),
+						},
+						line,
+						field_lines: HashMap::new(),
+						image_lines: Vec::new(),
 					});
 					delivery_buffer.clear();
 					images_buffer.clear();
+					image_lines.clear();
 				}
 
 				current_values.clear();
@@ -288,7 +363,7 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 			Ok(Event::Text(e)) => {
 				// Extract text content directly from the bytes
 				let text = std::str::from_utf8(e.into_inner().as_ref())
-					.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?
+					.map_err(|e| utf8_error(line, &current_ad, e))?
 					.to_string();
 
 				if in_ad && !&text.trim().is_empty() {
@@ -298,7 +373,7 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 			Ok(Event::CData(e)) => {
 				// Handle CDATA content (for Description)
 				let text = std::str::from_utf8(e.as_ref())
-					.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?
+					.map_err(|e| utf8_error(line, &current_ad, e))?
 					.to_string();
 
 				if in_ad {
@@ -307,7 +382,7 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 			}
 			Ok(Event::Empty(e)) => {
 				let name = std::str::from_utf8(e.name().as_ref())
-					.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?
+					.map_err(|e| utf8_error(line, &current_ad, e))?
 					.to_string();
 
 				// Handle Image tags with attributes
@@ -318,6 +393,7 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 							if attr.key.as_ref() == b"url" {
 								if let Ok(url) = std::str::from_utf8(&attr.value) {
 									images_buffer.push(url.to_string());
+									image_lines.push(line);
 								}
 							}
 						}
@@ -326,7 +402,7 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 				// Handle other empty elements (fallback)
 				else {
 					let text = std::str::from_utf8(e.as_ref())
-						.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?
+						.map_err(|e| utf8_error(line, &current_ad, e))?
 						.to_string();
 
 					if in_ad {
@@ -336,14 +412,19 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 			}
 			Ok(Event::End(e)) => {
 				let name = std::str::from_utf8(e.name().as_ref())
-					.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?
+					.map_err(|e| utf8_error(line, &current_ad, e))?
 					.to_string();
+				let start_line = current_lines.last().copied().unwrap_or(line);
 
-				if let Some(ad) = &mut current_ad {
+				if let Some(positioned) = &mut current_ad {
+					let ad = &mut positioned.ad;
 					// Special handling for Delivery - store as comma-separated options
 					if name == "Delivery" && !delivery_buffer.is_empty() {
 						ad.fields
 							.insert("Delivery".to_string(), delivery_buffer.join(","));
+						positioned
+							.field_lines
+							.insert("Delivery".to_string(), start_line);
 						delivery_buffer.clear();
 					}
 					// Special handling for Option elements inside Delivery
@@ -356,12 +437,17 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 						if !images_buffer.is_empty() {
 							ad.fields
 								.insert("Images".to_string(), images_buffer.join(","));
+							positioned
+								.field_lines
+								.insert("Images".to_string(), start_line);
+							positioned.image_lines = std::mem::take(&mut image_lines);
 							images_buffer.clear();
 						}
 					} else if name == "Image" && current_path.contains(&"Images".to_string()) {
 						// For non-empty Image tags, add their text content to images_buffer
 						if !current_values.trim().is_empty() {
 							images_buffer.push(current_values.trim().to_string());
+							image_lines.push(start_line);
 						}
 					}
 					// Store other field values if not empty
@@ -370,14 +456,22 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 						// Skip storing individual Image and Option elements as they're handled specially
 						if field_name != "Image" && field_name != "Option" {
 							ad.fields
-								.insert(field_name, current_values.trim().to_string());
+								.insert(field_name.clone(), current_values.trim().to_string());
+							positioned.field_lines.insert(field_name, start_line);
 						}
 					}
+					// Empty tags are kept in positions only, so validation can point at them
+					else if name != "Ad" && name != "Image" && name != "Option" {
+						positioned
+							.field_lines
+							.entry(name.clone())
+							.or_insert(start_line);
+					}
 
 					// Special handling for Id field
 					if name == "Id" {
-						if let Some(id_value) = ad.fields.get("Id") {
-							ad.id = id_value.clone();
+						if let Some(id_value) = positioned.ad.fields.get("Id") {
+							positioned.ad.id = id_value.clone();
 						}
 					}
 				}
@@ -386,18 +480,26 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 				if name == "Ad" {
 					in_ad = false;
 					if let Some(ad) = current_ad.take() {
-						if !ad.id.is_empty() {
-							ads.push(ad);
-						}
+						ads.push(ad);
 					}
 				}
 
-				// println!("End element: {}, current path: {:?}", name, current_path);
 				current_path.pop();
+				current_lines.pop();
 				current_values.clear();
 			}
 			Ok(Event::Eof) => break,
-			Err(e) => return Err(ApiError::Other(format!("XML parse error: {}", e))),
+			Err(e) => {
+				return Err(ImportRowError {
+					line: lines.line_at(reader.error_position() as usize),
+					ad_id: current_ad
+						.as_ref()
+						.map(|a| a.ad.id.clone())
+						.filter(|id| !id.is_empty()),
+					tag: current_path.last().cloned(),
+					message: format!("XML parse error: {}", e),
+				})
+			}
 			_ => (),
 		}
 
@@ -405,6 +507,5 @@ pub fn parse_xml_ads(xml_data: &str) -> Result<Vec<XmlAd>, ApiError> {
 		buf.clear();
 	}
 
-	println!("Finished parsing {} ads", ads.len());
-	Ok(ads)
+	Ok(())
 }
@@ -0,0 +1,170 @@
+use crate::{
+	controllers::avito_feeds::{parse_xml_ads, store_imported_feed},
+	jwt_auth::JwtMiddleware,
+	models::ApiError,
+	utils::multipart::read_multipart_form,
+	AppState,
+};
+use actix_multipart::Multipart;
+use actix_web::{
+	post,
+	web::{self},
+	HttpResponse,
+};
+use flate2::read::GzDecoder;
+use std::io::{Cursor, Read};
+use uuid::Uuid;
+
+// Uncompressed size cap for gzip/zip uploads, so a small archive can't expand without bound
+pub const MAX_DECOMPRESSED_BYTES: u64 = 500 * 1024 * 1024;
+
+#[post("/avito/import-xml-file")]
+pub async fn import_avito_xml_file(
+	payload: Multipart,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let form = read_multipart_form(payload).await?;
+
+	let account_id = form
+		.fields
+		.get("account_id")
+		.and_then(|value| Uuid::parse_str(value).ok());
+	let (file_name, file_bytes) = match form.file {
+		Some(file) => (file.file_name, file.bytes),
+		None => (String::new(), Vec::new()),
+	};
+
+	let account_id = match account_id {
+		Some(account_id) => account_id,
+		None => {
+			return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+				"status": "error",
+				"message": "account_id is missing or invalid"
+			})));
+		}
+	};
+
+	if file_bytes.is_empty() {
+		return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+			"status": "error",
+			"message": "No file uploaded"
+		})));
+	}
+
+	println!(
+		"Received feed file {} with size: {}",
+		file_name,
+		file_bytes.len()
+	);
+	let xml_data = decode_uploaded_xml(&file_bytes)?;
+
+	// Parse XML and extract ads
+	println!("Parsing XML data with length: {}", xml_data.len());
+	let ads = parse_xml_ads(&xml_data)?;
+	println!("Parsed {} ads from XML", ads.len());
+
+	let feed_id = store_imported_feed(&data, account_id, "IMPORT", &ads).await?;
+
+	Ok(HttpResponse::Ok().json(serde_json::json!({
+		"status": "success",
+		"message": "Import completed successfully",
+		"feed_id": feed_id,
+		"file_name": file_name,
+		"ads_processed": ads.len()
+	})))
+}
+
+// Decompress the uploaded file if needed (gzip or zip) and return the XML text
+pub fn decode_uploaded_xml(bytes: &[u8]) -> Result<String, ApiError> {
+	let xml_bytes = if bytes.starts_with(&[0x1f, 0x8b]) {
+		// gzip
+		read_decompressed(GzDecoder::new(bytes), MAX_DECOMPRESSED_BYTES)
+			.map_err(|e| ApiError::Other(format!("Failed to decompress gzip: {}", e)))?
+	} else if bytes.starts_with(b"PK\x03\x04") {
+		// zip: take the first .xml entry, or the first file if there is none
+		let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+			.map_err(|e| ApiError::Other(format!("Failed to open zip archive: {}", e)))?;
+
+		let mut entry_index = None;
+		for i in 0..archive.len() {
+			let entry = archive
+				.by_index(i)
+				.map_err(|e| ApiError::Other(format!("Failed to read zip entry: {}", e)))?;
+			if entry.is_dir() {
+				continue;
+			}
+			if entry.name().to_lowercase().ends_with(".xml") {
+				entry_index = Some(i);
+				break;
+			}
+			if entry_index.is_none() {
+				entry_index = Some(i);
+			}
+		}
+
+		let index =
+			entry_index.ok_or_else(|| ApiError::Other("Zip archive is empty".to_string()))?;
+		let entry = archive
+			.by_index(index)
+			.map_err(|e| ApiError::Other(format!("Failed to read zip entry: {}", e)))?;
+		read_decompressed(entry, MAX_DECOMPRESSED_BYTES)
+			.map_err(|e| ApiError::Other(format!("Failed to decompress zip entry: {}", e)))?
+	} else {
+		bytes.to_vec()
+	};
+
+	String::from_utf8(xml_bytes).map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))
+}
+
+// Read a decompressing reader to the end, failing once it yields more than `limit` bytes
+fn read_decompressed(reader: impl Read, limit: u64) -> Result<Vec<u8>, String> {
+	let mut decoded = Vec::new();
+	reader
+		.take(limit + 1)
+		.read_to_end(&mut decoded)
+		.map_err(|e| e.to_string())?;
+	if decoded.len() as u64 > limit {
+		return Err(format!(
+			"uncompressed file is larger than {} MB",
+			limit / (1024 * 1024)
+		));
+	}
+	Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use flate2::{write::GzEncoder, Compression};
+	use std::io::Write;
+
+	fn gzip(data: &[u8]) -> Vec<u8> {
+		let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+		encoder.write_all(data).unwrap();
+		encoder.finish().unwrap()
+	}
+
+	#[test]
+	fn plain_xml_is_passed_through() {
+		let xml = "<Ads><Ad><Id>1</Id></Ad></Ads>";
+		assert_eq!(decode_uploaded_xml(xml.as_bytes()).unwrap(), xml);
+	}
+
+	#[test]
+	fn gzip_upload_is_decompressed() {
+		let xml = "<Ads><Ad><Id>1</Id></Ad></Ads>";
+		assert_eq!(decode_uploaded_xml(&gzip(xml.as_bytes())).unwrap(), xml);
+	}
+
+	#[test]
+	fn output_over_the_limit_is_rejected() {
+		let data = vec![b'a'; 4096];
+		let compressed = gzip(&data);
+		assert_eq!(
+			read_decompressed(GzDecoder::new(compressed.as_slice()), 4096).unwrap(),
+			data
+		);
+		assert!(read_decompressed(GzDecoder::new(compressed.as_slice()), 4095).is_err());
+	}
+}
@@ -0,0 +1,63 @@
+use crate::{
+	controllers::avito_feeds::{fetch_xml, store_imported_feed},
+	jwt_auth::JwtMiddleware,
+	models::ApiError,
+	utils::yml_feed::{convert_yml_offers, parse_yml_catalog, YmlMapping},
+	AppState,
+};
+use actix_web::{
+	post,
+	web::{self},
+	HttpResponse,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+// Structure for POST request body containing account_id, yml_url and the offer -> tag mapping
+#[derive(Deserialize)]
+pub struct ImportYmlRequest {
+	pub account_id: Uuid,
+	pub yml_url: String,
+	#[serde(default)]
+	pub mapping: YmlMapping,
+}
+
+#[post("/avito/import-yml")]
+pub async fn import_yml(
+	body: web::Json<ImportYmlRequest>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let yml_data = fetch_xml(&body.yml_url).await?;
+
+	// Parse YML catalog and convert offers to Avito ads
+	println!("Parsing YML data with length: {}", yml_data.len());
+	let (categories, offers) = parse_yml_catalog(&yml_data)?;
+	println!(
+		"Parsed {} offers and {} categories from YML",
+		offers.len(),
+		categories.len()
+	);
+
+	let (ads, errors) = convert_yml_offers(&categories, &offers, &body.mapping);
+	println!(
+		"Converted {} offers to ads, {} offers skipped",
+		ads.len(),
+		errors.len()
+	);
+
+	let feed_id = if ads.is_empty() {
+		None
+	} else {
+		Some(store_imported_feed(&data, body.account_id, "YML_IMPORT", &ads).await?)
+	};
+
+	Ok(HttpResponse::Ok().json(serde_json::json!({
+		"status": "success",
+		"message": "YML import completed",
+		"feed_id": feed_id,
+		"offers_total": offers.len(),
+		"ads_processed": ads.len(),
+		"errors": errors
+	})))
+}
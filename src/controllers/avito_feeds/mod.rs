@@ -1,9 +1,21 @@
+pub mod bulk_edit_feed_ads;
+pub mod export_avito_feed;
 pub mod get_avito_feed_ad;
 pub mod get_avito_feed_by_id;
 pub mod get_avito_feeds;
+pub mod import_avito_table;
 pub mod import_avito_xml;
+pub mod import_avito_xml_file;
+pub mod import_yml;
+pub mod validate_avito_xml;
 
+pub use self::bulk_edit_feed_ads::*;
+pub use self::export_avito_feed::*;
 pub use self::get_avito_feed_ad::*;
 pub use self::get_avito_feed_by_id::*;
 pub use self::get_avito_feeds::*;
+pub use self::import_avito_table::*;
 pub use self::import_avito_xml::*;
+pub use self::import_avito_xml_file::*;
+pub use self::import_yml::*;
+pub use self::validate_avito_xml::*;
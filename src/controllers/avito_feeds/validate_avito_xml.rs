@@ -0,0 +1,142 @@
+use crate::{
+	controllers::avito_feeds::{decode_uploaded_xml, fetch_xml, parse_xml_ads_positioned},
+	jwt_auth::JwtMiddleware,
+	models::ApiError,
+	utils::{
+		avito_requests::{get_category_names, get_category_required_tags},
+		feed_validation::{validate_feed_ads, FeedValidationOptions},
+		multipart::read_multipart_form,
+	},
+};
+use actix_multipart::Multipart;
+use actix_web::{
+	post,
+	web::{self},
+	HttpResponse,
+};
+use serde::Deserialize;
+
+// Structure for POST request body of a dry-run feed check
+#[derive(Deserialize)]
+pub struct ValidateAvitoXmlRequest {
+	pub xml_url: String,
+	pub avito_token: Option<String>,
+	pub avito_slug: Option<String>,
+	#[serde(default)]
+	pub required_tags: Vec<String>,
+}
+
+// Check a feed by URL and report every problem found; nothing is written to the DB
+#[post("/avito/validate-xml")]
+pub async fn validate_avito_xml(
+	body: web::Json<ValidateAvitoXmlRequest>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let xml_data = fetch_xml(&body.xml_url).await?;
+
+	let options = validation_options(
+		body.avito_token.as_deref(),
+		body.avito_slug.as_deref(),
+		body.required_tags.clone(),
+	)
+	.await?;
+
+	Ok(HttpResponse::Ok().json(validation_report(&xml_data, &options)))
+}
+
+// Same check for an uploaded file. Multipart fields: file and optional avito_token,
+// avito_slug and required_tags (comma-separated)
+#[post("/avito/validate-xml-file")]
+pub async fn validate_avito_xml_file(
+	payload: Multipart,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let form = read_multipart_form(payload).await?;
+
+	let file = match form.file {
+		Some(file) if !file.bytes.is_empty() => file,
+		_ => {
+			return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+				"status": "error",
+				"message": "No file uploaded"
+			})));
+		}
+	};
+	let xml_data = decode_uploaded_xml(&file.bytes)?;
+
+	let required_tags = form
+		.fields
+		.get("required_tags")
+		.map(|tags| {
+			tags.split(',')
+				.map(|tag| tag.trim().to_string())
+				.filter(|tag| !tag.is_empty())
+				.collect()
+		})
+		.unwrap_or_default();
+	let options = validation_options(
+		form.fields.get("avito_token").map(|v| v.as_str()),
+		form.fields.get("avito_slug").map(|v| v.as_str()),
+		required_tags,
+	)
+	.await?;
+
+	Ok(HttpResponse::Ok().json(validation_report(&xml_data, &options)))
+}
+
+// With an Avito token the category schema and category list are checked as well
+async fn validation_options(
+	avito_token: Option<&str>,
+	avito_slug: Option<&str>,
+	mut required_tags: Vec<String>,
+) -> Result<FeedValidationOptions, ApiError> {
+	let mut known_categories = None;
+
+	if let Some(avito_token) = avito_token.filter(|token| !token.is_empty()) {
+		if let Some(avito_slug) = avito_slug.filter(|slug| !slug.is_empty()) {
+			let schema_tags = get_category_required_tags(avito_token, avito_slug)
+				.await
+				.map_err(|e| ApiError::Other(format!("Failed to fetch category schema: {}", e)))?;
+			for tag in schema_tags {
+				if !required_tags.contains(&tag) {
+					required_tags.push(tag);
+				}
+			}
+		}
+
+		known_categories = Some(
+			get_category_names(avito_token)
+				.await
+				.map_err(|e| ApiError::Other(format!("Failed to fetch categories: {}", e)))?,
+		);
+	}
+
+	Ok(FeedValidationOptions {
+		required_tags,
+		known_categories,
+	})
+}
+
+fn validation_report(xml_data: &str, options: &FeedValidationOptions) -> serde_json::Value {
+	println!("Validating XML data with length: {}", xml_data.len());
+	let (ads, parse_error) = parse_xml_ads_positioned(xml_data);
+
+	let mut errors = validate_feed_ads(&ads, options);
+	if let Some(parse_error) = parse_error {
+		errors.push(parse_error);
+	}
+	errors.sort_by_key(|e| e.line);
+	println!(
+		"Validated {} ads from XML, {} problems found",
+		ads.len(),
+		errors.len()
+	);
+
+	serde_json::json!({
+		"status": "success",
+		"valid": errors.is_empty(),
+		"ads_total": ads.len(),
+		"categories_checked": options.known_categories.is_some(),
+		"errors": errors
+	})
+}
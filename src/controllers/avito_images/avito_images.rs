@@ -0,0 +1,273 @@
+use crate::{
+	api::{
+		avito_ads::{delete_ad_fields, insert_ad_fields, load_ad_fields},
+		avito_images::{
+			find_mirrored_sources, insert_image, load_ad_images, replace_ad_images,
+			upsert_image_source,
+		},
+	},
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, AvitoImage, ImageMirrorError, MirrorImagesSchema},
+	utils::images::{
+		download_image, image_content_type, image_path, inspect_image, save_image, split_image_urls,
+	},
+	AppState,
+};
+use actix_web::{
+	get, post,
+	web::{self},
+	HttpResponse,
+};
+use futures::{stream, StreamExt};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::time::Duration;
+use uuid::Uuid;
+
+// Parallel downloads per mirror request
+const MIRROR_CONCURRENCY: usize = 8;
+
+#[derive(Deserialize)]
+pub struct MirrorImagesPath {
+	pub feed_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct AdImagesPath {
+	pub ad_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct ImageFilePath {
+	pub file_name: String,
+}
+
+// Public link to a mirrored file, served by get_avito_image
+fn mirrored_image_url(public_base_url: &str, file_name: &str) -> String {
+	format!(
+		"{}/api/avito/images/{}",
+		public_base_url.trim_end_matches('/'),
+		file_name
+	)
+}
+
+// Download every image referenced by the feed's ads into local storage.
+// Files are deduplicated by content hash; URLs mirrored earlier are not fetched again.
+#[post("/avito/feeds/{feed_id}/mirror-images")]
+pub async fn mirror_feed_images(
+	path: web::Path<MirrorImagesPath>,
+	body: web::Json<MirrorImagesSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let feed_id = path.feed_id;
+	let rewrite_urls = body.rewrite_urls.unwrap_or(false);
+
+	let ad_ids: Vec<Uuid> =
+		sqlx::query_scalar!("SELECT ad_id FROM avito_ads WHERE feed_id = $1", feed_id)
+			.fetch_all(&data.db)
+			.await
+			.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))?;
+
+	let ads_fields = load_ad_fields(&data.db, &ad_ids).await?;
+	let ad_urls: Vec<(Uuid, Vec<String>)> = ad_ids
+		.iter()
+		.filter_map(|ad_id| {
+			let images = ads_fields.get(ad_id)?.get("Images")?;
+			Some((*ad_id, split_image_urls(images)))
+		})
+		.collect();
+
+	let unique_urls: Vec<String> = ad_urls
+		.iter()
+		.flat_map(|(_, urls)| urls.iter().cloned())
+		.collect::<HashSet<_>>()
+		.into_iter()
+		.collect();
+
+	let mut mirrored = find_mirrored_sources(&data.db, &unique_urls).await?;
+	let reused = mirrored.len();
+	let pending: Vec<String> = unique_urls
+		.iter()
+		.filter(|url| !mirrored.contains_key(*url))
+		.cloned()
+		.collect();
+	println!(
+		"Mirroring images of feed {}: {} urls, {} already mirrored",
+		feed_id,
+		unique_urls.len(),
+		reused
+	);
+
+	let client = Client::builder()
+		.timeout(Duration::from_secs(60))
+		.build()
+		.map_err(|e| ApiError::Other(format!("Failed to create HTTP client: {}", e)))?;
+
+	let results: Vec<(String, Result<AvitoImage, ApiError>)> = stream::iter(pending)
+		.map(|url| {
+			let client = &client;
+			let data = &data;
+			async move {
+				let result = mirror_image(client, data, &url).await;
+				(url, result)
+			}
+		})
+		.buffer_unordered(MIRROR_CONCURRENCY)
+		.collect()
+		.await;
+
+	let mut errors = Vec::new();
+	let mut downloaded = 0;
+	for (url, result) in results {
+		match result {
+			Ok(image) => {
+				upsert_image_source(&data.db, &url, Some(image.image_id), None).await?;
+				mirrored.insert(url, image);
+				downloaded += 1;
+			}
+			Err(e) => {
+				let message = e.to_string();
+				upsert_image_source(&data.db, &url, None, Some(&message)).await?;
+				errors.push(ImageMirrorError { url, message });
+			}
+		}
+	}
+
+	// Links to our own copies resolve to the same images on the next run
+	if rewrite_urls {
+		for image in mirrored.values().cloned().collect::<Vec<_>>() {
+			let local_url = mirrored_image_url(&data.env.public_base_url, &image.file_name);
+			if !mirrored.contains_key(&local_url) {
+				upsert_image_source(&data.db, &local_url, Some(image.image_id), None).await?;
+			}
+		}
+	}
+
+	let mut ad_images = Vec::new();
+	let mut removed_fields = Vec::new();
+	let mut new_fields = Vec::new();
+	for (ad_id, urls) in &ad_urls {
+		let mut rewritten = Vec::new();
+		for (position, url) in urls.iter().enumerate() {
+			let image = mirrored.get(url);
+			ad_images.push((
+				*ad_id,
+				position as i32,
+				url.clone(),
+				image.map(|i| i.image_id),
+			));
+			rewritten.push(match image {
+				Some(image) => mirrored_image_url(&data.env.public_base_url, &image.file_name),
+				// Failed downloads keep the original link
+				None => url.clone(),
+			});
+		}
+
+		if rewrite_urls && &rewritten != urls {
+			removed_fields.push((*ad_id, "Images".to_string()));
+			new_fields.push((*ad_id, "Images".to_string(), rewritten.join(",")));
+		}
+	}
+
+	let ads_with_images: Vec<Uuid> = ad_urls.iter().map(|(ad_id, _)| *ad_id).collect();
+
+	let mut tx = data.db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	replace_ad_images(&mut tx, &ads_with_images, &ad_images).await?;
+	delete_ad_fields(&mut tx, &removed_fields).await?;
+	insert_ad_fields(&mut tx, &new_fields).await?;
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	Ok(HttpResponse::Ok().json(serde_json::json!({
+		"status": "success",
+		"feed_id": feed_id,
+		"images_total": unique_urls.len(),
+		"images_downloaded": downloaded,
+		"images_reused": reused,
+		"ads_rewritten": new_fields.len(),
+		"errors": errors
+	})))
+}
+
+async fn mirror_image(
+	client: &Client,
+	data: &web::Data<AppState>,
+	url: &str,
+) -> Result<AvitoImage, ApiError> {
+	let bytes = download_image(client, url).await?;
+	let info = inspect_image(&bytes)?;
+	let storage_dir = data.env.image_storage_dir.clone();
+	let file_name = info.file_name();
+	// File writes block, so they run on the blocking pool like the reads in get_avito_image
+	web::block(move || save_image(&storage_dir, &file_name, &bytes))
+		.await
+		.map_err(|e| ApiError::Other(format!("Failed to save image: {}", e)))??;
+	insert_image(&data.db, &info).await
+}
+
+#[get("/avito/ads/{ad_id}/images")]
+pub async fn get_avito_ad_images(
+	path: web::Path<AdImagesPath>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let images = load_ad_images(&data.db, path.ad_id).await?;
+
+	let images: Vec<serde_json::Value> = images
+		.into_iter()
+		.map(|image| {
+			let url = image
+				.file_name
+				.as_ref()
+				.map(|file_name| mirrored_image_url(&data.env.public_base_url, file_name));
+			let mut value = serde_json::to_value(&image).unwrap_or_default();
+			value["url"] = serde_json::json!(url);
+			value
+		})
+		.collect();
+
+	Ok(HttpResponse::Ok().json(serde_json::json!({
+		"status": "success",
+		"data": images
+	})))
+}
+
+// Public: Avito fetches mirrored images from here, so no auth
+#[get("/avito/images/{file_name}")]
+pub async fn get_avito_image(
+	path: web::Path<ImageFilePath>,
+	data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+	let not_found = || {
+		HttpResponse::NotFound().json(serde_json::json!({
+			"status": "error",
+			"message": "Image not found"
+		}))
+	};
+
+	let file_path = match image_path(&data.env.image_storage_dir, &path.file_name) {
+		Some(file_path) => file_path,
+		None => return Ok(not_found()),
+	};
+
+	let bytes = match web::block(move || std::fs::read(file_path))
+		.await
+		.map_err(|e| ApiError::Other(format!("Failed to read image: {}", e)))?
+	{
+		Ok(bytes) => bytes,
+		Err(_) => return Ok(not_found()),
+	};
+
+	// File names are content hashes, so the content never changes
+	Ok(HttpResponse::Ok()
+		.content_type(image_content_type(&path.file_name))
+		.append_header(("Cache-Control", "public, max-age=31536000, immutable"))
+		.body(bytes))
+}
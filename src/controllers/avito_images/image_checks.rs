@@ -0,0 +1,220 @@
+use crate::{
+	api::{
+		avito_ads::load_ad_fields,
+		avito_images::{load_feed_image_hashes, replace_ad_image_checks},
+	},
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, NewAdImageCheck},
+	utils::{
+		image_quality::{
+			check_image_bytes, hash_distance, ImageQualityIssue, ImageQualityReport,
+			DUPLICATE_HASH_DISTANCE,
+		},
+		images::{download_image, split_image_urls, ImageDownloadError},
+	},
+	AppState,
+};
+use actix_web::{
+	post,
+	web::{self},
+	HttpResponse,
+};
+use futures::{stream, StreamExt};
+use reqwest::Client;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use uuid::Uuid;
+
+// Parallel downloads per check run
+const CHECK_CONCURRENCY: usize = 8;
+
+#[derive(Deserialize)]
+pub struct CheckImagesPath {
+	pub feed_id: Uuid,
+}
+
+#[post("/avito/feeds/{feed_id}/check-images")]
+pub async fn check_feed_images(
+	path: web::Path<CheckImagesPath>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let ad_ids = feed_ad_ids(&data.db, path.feed_id).await?;
+	let checks = run_image_checks(&data.db, &ad_ids).await?;
+
+	let count = |status: &str| checks.iter().filter(|c| c.status == status).count();
+
+	Ok(HttpResponse::Ok().json(serde_json::json!({
+		"status": "success",
+		"feed_id": path.feed_id,
+		"images_checked": checks.len(),
+		"images_ok": count("ok"),
+		"images_with_warnings": count("warning"),
+		"images_with_errors": count("error")
+	})))
+}
+
+async fn feed_ad_ids(db: &Pool<Postgres>, feed_id: Uuid) -> Result<Vec<Uuid>, ApiError> {
+	sqlx::query_scalar!("SELECT ad_id FROM avito_ads WHERE feed_id = $1", feed_id)
+		.fetch_all(db)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))
+}
+
+// Check ads' images in the background after they were created, updated or imported
+pub fn spawn_image_checks(data: web::Data<AppState>, ad_ids: Vec<Uuid>) {
+	actix_web::rt::spawn(async move {
+		if let Err(e) = run_image_checks(&data.db, &ad_ids).await {
+			eprintln!("Image checks failed: {}", e);
+		}
+	});
+}
+
+pub fn spawn_feed_image_checks(data: web::Data<AppState>, feed_id: Uuid) {
+	actix_web::rt::spawn(async move {
+		let result = match feed_ad_ids(&data.db, feed_id).await {
+			Ok(ad_ids) => run_image_checks(&data.db, &ad_ids).await.map(|_| ()),
+			Err(e) => Err(e),
+		};
+		if let Err(e) = result {
+			eprintln!("Image checks of feed {} failed: {}", feed_id, e);
+		}
+	});
+}
+
+// Download and check every image of the ads, flag near-identical photos used by
+// other ads of the same feed and store the results
+pub async fn run_image_checks(
+	db: &Pool<Postgres>,
+	ad_ids: &[Uuid],
+) -> Result<Vec<NewAdImageCheck>, ApiError> {
+	if ad_ids.is_empty() {
+		return Ok(Vec::new());
+	}
+
+	let ads_fields = load_ad_fields(db, ad_ids).await?;
+	let ad_urls: Vec<(Uuid, Vec<String>)> = ad_ids
+		.iter()
+		.map(|ad_id| {
+			let urls = ads_fields
+				.get(ad_id)
+				.and_then(|fields| fields.get("Images"))
+				.map(|images| split_image_urls(images))
+				.unwrap_or_default();
+			(*ad_id, urls)
+		})
+		.collect();
+
+	let unique_urls: HashSet<String> = ad_urls
+		.iter()
+		.flat_map(|(_, urls)| urls.iter().cloned())
+		.collect();
+
+	let client = Client::builder()
+		.timeout(Duration::from_secs(30))
+		.build()
+		.map_err(|e| ApiError::Other(format!("Failed to create HTTP client: {}", e)))?;
+
+	let reports: HashMap<String, ImageQualityReport> = stream::iter(unique_urls)
+		.map(|url| {
+			let client = &client;
+			async move {
+				let report = match download_image(client, &url).await {
+					Ok(bytes) => {
+						// Decoding is CPU-bound, keep it off the async workers
+						web::block(move || check_image_bytes(&bytes))
+							.await
+							.unwrap_or_else(|e| {
+								ImageQualityReport::unreachable(format!("Check failed: {}", e))
+							})
+					}
+					Err(ImageDownloadError::TooLarge(size)) => ImageQualityReport::too_large(size),
+					Err(ImageDownloadError::Failed(e)) => {
+						ImageQualityReport::unreachable(e.to_string())
+					}
+				};
+				(url, report)
+			}
+		})
+		.buffer_unordered(CHECK_CONCURRENCY)
+		.collect()
+		.await;
+
+	// Feed of every checked ad, used to look for duplicates only inside that feed
+	let ad_feeds: HashMap<Uuid, Uuid> = sqlx::query_as::<_, (Uuid, Uuid)>(
+		"SELECT ad_id, feed_id FROM avito_ads WHERE ad_id = ANY($1)",
+	)
+	.bind(ad_ids)
+	.fetch_all(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch ads: {}", e)))?
+	.into_iter()
+	.collect();
+
+	let feed_ids: Vec<Uuid> = ad_feeds
+		.values()
+		.cloned()
+		.collect::<HashSet<_>>()
+		.into_iter()
+		.collect();
+	let checked: HashSet<Uuid> = ad_ids.iter().cloned().collect();
+
+	// (feed_id, ad_id, position, hash) of images we compare against
+	let mut known_hashes: Vec<(Uuid, Uuid, i32, u64)> = load_feed_image_hashes(db, &feed_ids)
+		.await?
+		.into_iter()
+		.filter(|(_, ad_id, _, _)| !checked.contains(ad_id))
+		.map(|(feed_id, ad_id, position, phash)| (feed_id, ad_id, position, phash as u64))
+		.collect();
+
+	let mut checks = Vec::new();
+	for (ad_id, urls) in &ad_urls {
+		let feed_id = ad_feeds.get(ad_id).cloned();
+
+		for (position, url) in urls.iter().enumerate() {
+			let mut report = reports.get(url).cloned().unwrap_or_default();
+
+			if let (Some(feed_id), Some(phash)) = (feed_id, report.phash) {
+				let duplicate =
+					known_hashes
+						.iter()
+						.find(|(other_feed, other_ad, _, other_hash)| {
+							*other_feed == feed_id
+								&& other_ad != ad_id && hash_distance(phash, *other_hash)
+								<= DUPLICATE_HASH_DISTANCE
+						});
+				if let Some((_, other_ad, other_position, _)) = duplicate {
+					report.issues.push(ImageQualityIssue::warning(
+						"duplicate",
+						format!(
+							"Same photo as image {} of ad {}",
+							other_position + 1,
+							other_ad
+						),
+					));
+				}
+				known_hashes.push((feed_id, *ad_id, position as i32, phash));
+			}
+
+			checks.push(NewAdImageCheck {
+				ad_id: *ad_id,
+				position: position as i32,
+				url: url.clone(),
+				status: report.status().to_string(),
+				issues: serde_json::to_value(&report.issues).unwrap_or_default(),
+				format: report.format.clone(),
+				width: report.width.map(|w| w as i32),
+				height: report.height.map(|h| h as i32),
+				size_bytes: report.size_bytes.map(|s| s as i64),
+				phash: report.phash.map(|h| h as i64),
+			});
+		}
+	}
+
+	replace_ad_image_checks(db, ad_ids, &checks).await?;
+	println!("Checked {} images of {} ads", checks.len(), ad_ids.len());
+
+	Ok(checks)
+}
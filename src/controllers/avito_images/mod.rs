@@ -0,0 +1,5 @@
+pub mod avito_images;
+pub mod image_checks;
+
+pub use self::avito_images::*;
+pub use self::image_checks::*;
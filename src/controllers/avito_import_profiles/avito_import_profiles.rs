@@ -0,0 +1,210 @@
+use crate::{
+	jwt_auth::JwtMiddleware,
+	models::{
+		ApiError, AvitoImportProfile, CreateImportProfileSchema, ImportProfilesQuery,
+		UpdateImportProfileSchema,
+	},
+	AppState,
+};
+use actix_web::{
+	delete, get, post, put,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+// GET all import profiles of an account
+#[get("/avito/import-profiles")]
+pub async fn get_import_profiles_handler(
+	opts: web::Query<ImportProfilesQuery>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let profiles = sqlx::query_as::<_, AvitoImportProfile>(
+		r#"
+        SELECT profile_id, account_id, name, category, avito_slug, mapping, required_tags,
+               created_ts, updated_ts
+        FROM avito_import_profiles
+        WHERE account_id = $1
+        ORDER BY created_ts DESC
+        "#,
+	)
+	.bind(opts.account_id)
+	.fetch_all(&data.db)
+	.await
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to fetch import profiles: {}", e))
+	})?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"import_profiles": profiles
+		})
+	})))
+}
+
+// GET import profile by id
+#[get("/avito/import-profiles/{profile_id}")]
+pub async fn get_import_profile_handler(
+	path: Path<Uuid>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let profile_id = path.into_inner();
+
+	match fetch_import_profile(&data, profile_id).await? {
+		Some(profile) => Ok(HttpResponse::Ok().json(json!({
+			"status": "success",
+			"data": json!({
+				"import_profile": profile
+			})
+		}))),
+		None => Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Import profile not found"
+		}))),
+	}
+}
+
+// Create import profile
+#[post("/avito/import-profiles")]
+pub async fn create_import_profile_handler(
+	body: web::Json<CreateImportProfileSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	if body.mapping.is_empty() {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "Mapping cannot be empty"
+		})));
+	}
+
+	let profile = sqlx::query_as::<_, AvitoImportProfile>(
+		r#"
+        INSERT INTO avito_import_profiles (account_id, name, category, avito_slug, mapping, required_tags)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING profile_id, account_id, name, category, avito_slug, mapping, required_tags,
+                  created_ts, updated_ts
+        "#,
+	)
+	.bind(body.account_id)
+	.bind(&body.name)
+	.bind(&body.category)
+	.bind(&body.avito_slug)
+	.bind(Json(&body.mapping))
+	.bind(body.required_tags.clone().unwrap_or_default())
+	.fetch_one(&data.db)
+	.await
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to create import profile: {}", e))
+	})?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"import_profile": profile
+		})
+	})))
+}
+
+// Update import profile
+#[put("/avito/import-profiles/{profile_id}")]
+pub async fn update_import_profile_handler(
+	path: Path<Uuid>,
+	body: web::Json<UpdateImportProfileSchema>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let profile_id = path.into_inner();
+
+	let profile = sqlx::query_as::<_, AvitoImportProfile>(
+		r#"
+        UPDATE avito_import_profiles
+        SET name = COALESCE($2, name),
+            category = COALESCE($3, category),
+            avito_slug = COALESCE($4, avito_slug),
+            mapping = COALESCE($5, mapping),
+            required_tags = COALESCE($6, required_tags),
+            updated_ts = NOW()
+        WHERE profile_id = $1
+        RETURNING profile_id, account_id, name, category, avito_slug, mapping, required_tags,
+                  created_ts, updated_ts
+        "#,
+	)
+	.bind(profile_id)
+	.bind(&body.name)
+	.bind(&body.category)
+	.bind(&body.avito_slug)
+	.bind(body.mapping.as_ref().map(Json))
+	.bind(&body.required_tags)
+	.fetch_optional(&data.db)
+	.await
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to update import profile: {}", e))
+	})?;
+
+	match profile {
+		Some(profile) => Ok(HttpResponse::Ok().json(json!({
+			"status": "success",
+			"data": json!({
+				"import_profile": profile
+			})
+		}))),
+		None => Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Import profile not found"
+		}))),
+	}
+}
+
+// Delete import profile
+#[delete("/avito/import-profiles/{profile_id}")]
+pub async fn delete_import_profile_handler(
+	path: Path<Uuid>,
+	data: web::Data<AppState>,
+	_: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let profile_id = path.into_inner();
+
+	let result = sqlx::query("DELETE FROM avito_import_profiles WHERE profile_id = $1")
+		.bind(profile_id)
+		.execute(&data.db)
+		.await
+		.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to delete import profile: {}", e))
+		})?;
+
+	if result.rows_affected() == 0 {
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Import profile not found"
+		})));
+	}
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"message": "Import profile deleted successfully"
+	})))
+}
+
+pub async fn fetch_import_profile(
+	data: &web::Data<AppState>,
+	profile_id: Uuid,
+) -> Result<Option<AvitoImportProfile>, ApiError> {
+	sqlx::query_as::<_, AvitoImportProfile>(
+		r#"
+        SELECT profile_id, account_id, name, category, avito_slug, mapping, required_tags,
+               created_ts, updated_ts
+        FROM avito_import_profiles
+        WHERE profile_id = $1
+        "#,
+	)
+	.bind(profile_id)
+	.fetch_optional(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch import profile: {}", e)))
+}
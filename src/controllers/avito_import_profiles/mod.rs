@@ -0,0 +1,3 @@
+pub mod avito_import_profiles;
+
+pub use self::avito_import_profiles::*;
@@ -1,9 +1,18 @@
 use crate::controllers::auth::Role;
+use crate::controllers::avito_requests::export_request_ads;
 use crate::utils::avito_requests::filter_add_avito_request_record;
 use crate::{
+	api::{
+		avito_requests::{
+			AD_RECORD_COLUMNS, AVITO_REQUESTS_KEYSET, OWN_AVITO_REQUESTS_KEYSET,
+			USER_AVITO_REQUESTS_KEYSET,
+		},
+		shared::{Keyset, SortKey},
+	},
 	jwt_auth::JwtMiddleware,
 	models::{
-		AdRecord, AvitoRequest, Count, FilterOptions, FilteredAvitoRequest, SaveAvitoRequest,
+		AdRecord, ApiError, AvitoRequest, CrawlExportQuery, CrawlProgress, CrawlStatus,
+		FilterOptions, FilteredAvitoRequest, SaveAvitoRequest,
 	},
 	AppState,
 };
@@ -16,12 +25,10 @@ use actix_web_grants::proc_macro::has_any_role;
 use serde_json::json;
 use uuid::Uuid;
 
-use crate::utils::transliterate::Translit;
-use csv::Writer;
 use serde::{Deserialize, Serialize};
-use std::io::Cursor;
+use sqlx::{Pool, Postgres};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AvitoRequestMessage {
 	pub request_id: Uuid,
 	pub user_id: Uuid,
@@ -33,6 +40,16 @@ pub struct AvitoRequestMessage {
 	pub created_ts: chrono::DateTime<chrono::Utc>,
 }
 
+// Control message for a crawl task that was already published
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AvitoRequestControlMessage {
+	pub request_id: Uuid,
+	pub user_id: Uuid,
+	/// Only "cancel" for now
+	pub action: String,
+	pub created_ts: chrono::DateTime<chrono::Utc>,
+}
+
 // Get my account avito requests
 #[get("/avito_requests/{id}")]
 #[has_any_role("Role::Admin", type = "Role")]
@@ -43,28 +60,28 @@ async fn get_avito_requests_handler(
 	// _: jwt_auth::JwtMiddleware,
 ) -> impl Responder {
 	let user_id = &path.into_inner();
-	let limit = opts.limit.unwrap_or(10);
-	let offset = (opts.page.unwrap_or(1) - 1) * limit;
-	let table = String::from("avito_requests");
+	let request = match USER_AVITO_REQUESTS_KEYSET.request(&opts) {
+		Ok(request) => request,
+		Err(message) => {
+			return HttpResponse::BadRequest().json(json!({"status": "error","message": message}));
+		}
+	};
 
-	let query_result =
-		AvitoRequest::get_avito_requests_by_user(&data.db, user_id, limit as i64, offset as i64)
-			.await;
+	let query_result = AvitoRequest::get_avito_requests_by_user(&data.db, user_id, &request).await;
 	let reviews_message = "Что-то пошло не так во время чтения category";
 	if query_result.is_err() {
 		return HttpResponse::InternalServerError()
 			.json(json!({"status": "error","message": &reviews_message}));
 	}
-	let reviews = query_result.expect(&reviews_message);
-
-	let avito_requests_count = Count::count(&data.db, table).await.unwrap_or(0);
+	let page = query_result.expect(&reviews_message);
 
 	let json_response = json!({
 		"status":  "success",
 		"data": json!({
-			"avito_requests": &reviews.into_iter().map(|review| filter_add_avito_request_record(&review)).collect::<Vec<FilteredAvitoRequest>>(),
-			"avito_requests_count": &avito_requests_count,
-		})
+			"avito_requests": &page.items.iter().map(filter_add_avito_request_record).collect::<Vec<FilteredAvitoRequest>>(),
+			"avito_requests_count": &page.total,
+		}),
+		"pagination": page.pagination()
 	});
 
 	HttpResponse::Ok().json(json_response)
@@ -114,7 +131,7 @@ async fn create_avito_request_handler(
 			};
 
 			// Publish to RabbitMQ
-			match publish_avito_request(&data.rabbitmq_channel, &message).await {
+			match queue_crawl(&data.db, &data.rabbitmq_channel, &message).await {
 				Ok(_) => {
 					let avito_request_response = serde_json::json!({
 						"status": "success",
@@ -140,8 +157,120 @@ async fn create_avito_request_handler(
 	}
 }
 
+// Loads a request for an analytics handler; the error is the response for a missing or foreign one
+pub async fn find_own_avito_request(
+	db: &Pool<Postgres>,
+	request_id: Uuid,
+	user_id: Uuid,
+) -> Result<Result<AvitoRequest, HttpResponse>, ApiError> {
+	let avito_request = match AvitoRequest::get_avito_request_by_id(db, request_id)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch request: {}", e)))?
+	{
+		Some(avito_request) => avito_request,
+		None => {
+			return Ok(Err(HttpResponse::NotFound().json(json!({
+				"status": "error",
+				"message": "Avito request not found"
+			}))));
+		}
+	};
+	if avito_request.user_id != user_id {
+		return Ok(Err(HttpResponse::Forbidden().json(json!({
+			"status": "error",
+			"message": "Access denied. You can only access your own avito requests."
+		}))));
+	}
+	Ok(Ok(avito_request))
+}
+
+// GET crawl job status of an avito request
+#[get("/avito_requests/{request_id}/status")]
+async fn get_avito_request_status_handler(
+	path: Path<Uuid>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> impl Responder {
+	let request_id = path.into_inner();
+
+	let avito_request = match AvitoRequest::get_avito_request_by_id(&data.db, request_id).await {
+		Ok(Some(avito_request)) => avito_request,
+		Ok(None) => {
+			return HttpResponse::NotFound()
+				.json(json!({"status": "error", "message": "Avito request not found"}));
+		}
+		Err(e) => {
+			return HttpResponse::InternalServerError()
+				.json(json!({"status": "error", "message": format!("{:?}", e)}));
+		}
+	};
+
+	if avito_request.user_id != user.user_id {
+		return HttpResponse::Forbidden().json(json!({"status": "error", "message": "Access denied. You can only access your own avito requests."}));
+	}
+
+	// Percent done when the crawler reports a total
+	let percent = match (avito_request.progress_current, avito_request.progress_total) {
+		(Some(current), Some(total)) if total > 0 => {
+			Some(((current as f64 / total as f64) * 100.0).min(100.0).round())
+		}
+		_ => None,
+	};
+	let duration_secs = avito_request.started_ts.map(|started| {
+		(avito_request.finished_ts.unwrap_or_else(chrono::Utc::now) - started).num_seconds()
+	});
+
+	HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"request_id": avito_request.request_id,
+			"crawl_status": avito_request.status,
+			"createdTs": avito_request.created_ts,
+			"startedTs": avito_request.started_ts,
+			"finishedTs": avito_request.finished_ts,
+			"duration_secs": duration_secs,
+			"progress": json!({
+				"current": avito_request.progress_current,
+				"total": avito_request.progress_total,
+				"percent": percent
+			}),
+			"ads_count": avito_request.ads_count,
+			"last_error": avito_request.last_error
+		})
+	}))
+}
+
 // Message publishing function
-async fn publish_avito_request(
+// Publishes a crawl task; when that fails the request is marked failed since the crawler
+// will never pick it up
+pub async fn queue_crawl(
+	db: &Pool<Postgres>,
+	channel: &lapin::Channel,
+	message: &AvitoRequestMessage,
+) -> Result<(), String> {
+	let error = match publish_avito_request(channel, message).await {
+		Ok(_) => return Ok(()),
+		Err(e) => e.to_string(),
+	};
+
+	let progress = CrawlProgress {
+		request_id: message.request_id,
+		status: CrawlStatus::Failed,
+		progress_current: None,
+		progress_total: None,
+		ads_count: None,
+		error: Some(format!("Failed to queue crawl task: {}", error)),
+	};
+	if let Err(e) = AvitoRequest::apply_crawl_progress(db, &progress).await {
+		eprintln!(
+			"Failed to mark request {} as failed: {}",
+			message.request_id, e
+		);
+	}
+	Err(error)
+}
+
+pub async fn publish_avito_request(
 	channel: &lapin::Channel,
 	message: &AvitoRequestMessage,
 ) -> Result<(), Box<dyn std::error::Error>> {
@@ -179,6 +308,84 @@ async fn publish_avito_request(
 	Ok(())
 }
 
+// Control messages go to the same exchange as the tasks, routed as task.control.{user_id}
+pub async fn publish_avito_request_control(
+	channel: &lapin::Channel,
+	message: &AvitoRequestControlMessage,
+) -> Result<(), Box<dyn std::error::Error>> {
+	let message_json = serde_json::to_string(message)?;
+
+	channel
+		.exchange_declare(
+			"avito_exchange",
+			lapin::ExchangeKind::Topic,
+			lapin::options::ExchangeDeclareOptions {
+				durable: true,
+				..lapin::options::ExchangeDeclareOptions::default()
+			},
+			lapin::types::FieldTable::default(),
+		)
+		.await?;
+
+	channel
+		.basic_publish(
+			"avito_exchange",
+			&format!("task.control.{}", message.user_id),
+			lapin::options::BasicPublishOptions::default(),
+			message_json.as_bytes(),
+			lapin::BasicProperties::default(),
+		)
+		.await?;
+
+	log::info!(
+		"Published {} control message for avito request {}",
+		message.action,
+		message.request_id
+	);
+	Ok(())
+}
+
+static ANALYTICS_ADS_KEYSET: Keyset = Keyset {
+	from: "avito_analytics_ads",
+	id_expr: "ad_id",
+	id_type: "uuid",
+	sort_keys: &[
+		SortKey {
+			name: "position",
+			expr: "position",
+			sql_type: "int4",
+		},
+		SortKey {
+			name: "title",
+			expr: "title",
+			sql_type: "text",
+		},
+		SortKey {
+			name: "price",
+			expr: "COALESCE(price_value, -1)",
+			sql_type: "numeric",
+		},
+		SortKey {
+			name: "views",
+			expr: "COALESCE(views_value, -1)",
+			sql_type: "int4",
+		},
+		SortKey {
+			name: "ad_date",
+			expr: "COALESCE(ad_ts, 'epoch'::timestamptz)",
+			sql_type: "timestamptz",
+		},
+		SortKey {
+			name: "created_ts",
+			expr: "COALESCE(created_ts, 'epoch'::timestamptz)",
+			sql_type: "timestamptz",
+		},
+	],
+	default_descending: false,
+	default_limit: 10,
+	max_limit: 100,
+};
+
 // GET avito request with ads
 #[get("/avito_requests/{avito_request_id}/ads")]
 #[has_any_role("Role::Admin", type = "Role")]
@@ -189,46 +396,29 @@ async fn get_ads_by_avito_request_id_handler(
 	_: JwtMiddleware,
 ) -> impl Responder {
 	let avito_request_id = path.into_inner();
-	let limit = opts.limit.unwrap_or(10);
-	let offset = (opts.page.unwrap_or(1) - 1) * limit;
-
-	let query = "
-	           SELECT ad_id, my_ad, run_date, city_query, search_query, position, views, views_today,
-	                  promotion, delivery, ad_date, avito_ad_id, title, price, link, categories,
-	                  seller_id, seller_name, seller_type, register_date, answer_time,
-	                  rating, reviews_count, ads_count, closed_ads_count, photo_count,
-	                  address, description, avito_request_id, created_ts
-	           FROM avito_analytics_ads
-	           WHERE avito_request_id = $1
-	           ORDER BY position
-	           LIMIT $2 OFFSET $3
-	       ";
-
-	let query_result = sqlx::query_as::<_, AdRecord>(query)
-		.bind(avito_request_id)
-		.bind(limit as i64)
-		.bind(offset as i64)
-		.fetch_all(&data.db)
+	let request = match ANALYTICS_ADS_KEYSET.request(&opts) {
+		Ok(request) => request,
+		Err(message) => {
+			return HttpResponse::BadRequest().json(json!({"status": "error", "message": message}));
+		}
+	};
+
+	let query_result = ANALYTICS_ADS_KEYSET
+		.fetch::<AdRecord, _>(&data.db, &request, AD_RECORD_COLUMNS, |builder| {
+			builder.push(" AND avito_request_id = ");
+			builder.push_bind(avito_request_id);
+		})
 		.await;
 
 	match query_result {
-		Ok(ads) => {
-			// Get total count of ads for this request
-			let ads_count = sqlx::query_scalar!(
-				"SELECT COUNT(*) FROM avito_analytics_ads WHERE avito_request_id = $1",
-				avito_request_id
-			)
-			.fetch_one(&data.db)
-			.await
-			.unwrap_or(Some(0i64))
-			.unwrap_or(0i64);
-
+		Ok(page) => {
 			let json_response = serde_json::json!({
 				"status": "success",
 				"data": json!({
-					"ads": &ads,
-					"ads_count": &ads_count
-				})
+					"ads": &page.items,
+					"ads_count": &page.total
+				}),
+				"pagination": page.pagination()
 			});
 			HttpResponse::Ok().json(json_response)
 		}
@@ -247,127 +437,22 @@ async fn get_ads_by_avito_request_id_csv_handler(
 ) -> impl Responder {
 	let avito_request_id = path.into_inner();
 
-	let query = "
-	           SELECT ad_id, my_ad, run_date, city_query, search_query, position, views, views_today,
-	                  promotion, delivery, ad_date, avito_ad_id, title, price, link, categories,
-	                  seller_id, seller_name, seller_type, register_date, answer_time,
-	                  rating, reviews_count, ads_count, closed_ads_count, photo_count,
-	                  address, description, avito_request_id, created_ts
-	           FROM avito_analytics_ads
-	           WHERE avito_request_id = $1
-	           ORDER BY position
-	       ";
-
-	let query_result = sqlx::query_as::<_, AdRecord>(query)
-		.bind(avito_request_id)
-		.fetch_all(&data.db)
-		.await;
-
-	match query_result {
-		Ok(ads) => {
-			// Create CSV in memory
-			let mut writer = Writer::from_writer(Cursor::new(Vec::new()));
-
-			// Write headers
-			writer
-				.write_record(&[
-					"Мое",
-					"Дата прогона",
-					"Город (запрос)",
-					"Поиск (запрос)",
-					"Поз.",
-					"Просмотров",
-					"Просмотров сегодня",
-					"Продвижение",
-					"Доставка",
-					"Дата объявления",
-					"id",
-					"Название",
-					"Цена",
-					"Ссылка",
-					"Категории",
-					"id Продавца",
-					"Продавец",
-					"Тип продавца",
-					"Дата регистрации",
-					"Время ответа",
-					"Рейтинг",
-					"Кол. отзывов",
-					"Кол. объявлений",
-					"Кол. закрытых",
-					"Фото",
-					"Адрес",
-					"Описание",
-				])
-				.unwrap();
-
-			// Write records
-			for ad in &ads {
-				writer
-					.write_record(&[
-						ad.my_ad.as_str(),
-						ad.run_date.to_rfc3339().as_str(),
-						ad.city_query.as_str(),
-						ad.search_query.as_str(),
-						ad.position.to_string().as_str(),
-						ad.views.as_str(),
-						ad.views_today.as_str(),
-						ad.promotion.as_str(),
-						ad.delivery.as_str(),
-						ad.ad_date.as_str(),
-						ad.avito_ad_id.as_str(),
-						ad.title.as_str(),
-						ad.price.as_str(),
-						ad.link.as_str(),
-						ad.categories.as_str(),
-						ad.seller_id.as_str(),
-						ad.seller_name.as_str(),
-						ad.seller_type.as_str(),
-						ad.register_date.as_str(),
-						ad.answer_time.as_str(),
-						ad.rating.as_str(),
-						ad.reviews_count.as_str(),
-						ad.ads_count.as_str(),
-						ad.closed_ads_count.as_str(),
-						ad.photo_count.as_str(),
-						ad.address.as_str(),
-						ad.description.as_str(),
-					])
-					.unwrap();
+	let avito_request =
+		match AvitoRequest::get_avito_request_by_id(&data.db, avito_request_id).await {
+			Ok(Some(avito_request)) => avito_request,
+			Ok(None) => {
+				return HttpResponse::NotFound()
+					.json(json!({"status": "error", "message": "Avito request not found"}));
 			}
+			Err(e) => {
+				return HttpResponse::InternalServerError()
+					.json(json!({"status": "error", "message": format!("{:?}", e)}));
+			}
+		};
 
-			// Get the CSV bytes
-			let csv_bytes = writer.into_inner().unwrap().into_inner();
-
-			// Generate filename using search_query and date
-			let filename = if !ads.is_empty() {
-				let first_ad = &ads[0];
-				let search_query = first_ad.search_query.clone();
-				// Replace invalid filename characters with underscores
-				let sanitized_query = search_query
-					.chars()
-					.map(|c| match c {
-						'/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '\0' => '_',
-						_ => c,
-					})
-					.collect::<String>();
-				let transliterated_query = Translit::convert(Some(sanitized_query));
-				let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
-				format!("{}_{}.csv", transliterated_query, date)
-			} else {
-				let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
-				format!("ads_{}.csv", date)
-			};
-
-			// Create response with CSV content type
-			HttpResponse::Ok()
-				.content_type("text/csv")
-				.append_header((
-					"Content-Disposition",
-					format!("attachment; filename=\"{}\"", filename),
-				))
-				.body(csv_bytes)
-		}
+	// The classic 27 columns with Russian headers
+	match export_request_ads(&data.db, &avito_request, &CrawlExportQuery::default()).await {
+		Ok(response) => response,
 		Err(e) => HttpResponse::InternalServerError()
 			.json(serde_json::json!({"status": "error","message": format!("{:?}", e)})),
 	}
@@ -381,18 +466,16 @@ async fn get_all_avito_requests_handler(
 	data: web::Data<AppState>,
 	_: JwtMiddleware,
 ) -> impl Responder {
-	let limit = opts.limit.unwrap_or(10);
-	let offset = (opts.page.unwrap_or(1) - 1) * limit;
-	let table = String::from("avito_requests");
+	let request = match AVITO_REQUESTS_KEYSET.request(&opts) {
+		Ok(request) => request,
+		Err(message) => {
+			return HttpResponse::BadRequest().json(json!({"status": "error", "message": message}));
+		}
+	};
 
-	let query_result = sqlx::query_as!(
-		AvitoRequest,
-		"SELECT * FROM avito_requests ORDER BY created_ts DESC LIMIT $1 OFFSET $2",
-		limit as i64,
-		offset as i64
-	)
-	.fetch_all(&data.db)
-	.await;
+	let query_result = AVITO_REQUESTS_KEYSET
+		.fetch::<AvitoRequest, _>(&data.db, &request, "*", |_| {})
+		.await;
 
 	let error_message = "Error fetching avito requests";
 	if query_result.is_err() {
@@ -400,15 +483,15 @@ async fn get_all_avito_requests_handler(
 			.json(json!({"status": "error", "message": error_message}));
 	}
 
-	let avito_requests = query_result.expect(error_message);
-	let avito_requests_count = Count::count(&data.db, table).await.unwrap_or(0);
+	let page = query_result.expect(error_message);
 
 	let json_response = json!({
 		"status": "success",
 		"data": json!({
-			"avito_requests": &avito_requests.into_iter().map(|request| filter_add_avito_request_record(&request)).collect::<Vec<FilteredAvitoRequest>>(),
-			"avito_requests_count": &avito_requests_count,
-		})
+			"avito_requests": &page.items.iter().map(filter_add_avito_request_record).collect::<Vec<FilteredAvitoRequest>>(),
+			"avito_requests_count": &page.total,
+		}),
+		"pagination": page.pagination()
 	});
 
 	HttpResponse::Ok().json(json_response)
@@ -424,8 +507,6 @@ async fn get_avito_requests_by_user_handler(
 ) -> impl Responder {
 	let requested_user_id = path.into_inner();
 	let current_user_id = user.user_id; // Get the authenticated user's ID
-	let limit = opts.limit.unwrap_or(20);
-	let offset = (opts.page.unwrap_or(1) - 1) * limit;
 
 	// Check if the requested user_id matches the authenticated user's ID
 	if requested_user_id != current_user_id {
@@ -433,15 +514,15 @@ async fn get_avito_requests_by_user_handler(
 			.json(json!({"status": "error", "message": "Access denied. You can only access your own avito requests."}));
 	}
 
-	let query_result = sqlx::query_as!(
-	AvitoRequest,
-		"SELECT * FROM avito_requests WHERE user_id = $1 ORDER BY created_ts DESC LIMIT $2 OFFSET $3",
-		requested_user_id,
-		limit as i64,
-		offset as i64
-	)
-	.fetch_all(&data.db)
-	.await;
+	let request = match OWN_AVITO_REQUESTS_KEYSET.request(&opts) {
+		Ok(request) => request,
+		Err(message) => {
+			return HttpResponse::BadRequest().json(json!({"status": "error", "message": message}));
+		}
+	};
+
+	let query_result =
+		AvitoRequest::get_avito_requests_by_user(&data.db, &requested_user_id, &request).await;
 
 	let error_message = "Error fetching avito requests for user";
 	if query_result.is_err() {
@@ -449,22 +530,15 @@ async fn get_avito_requests_by_user_handler(
 			.json(json!({"status": "error", "message": error_message}));
 	}
 
-	let avito_requests = query_result.expect(error_message);
-	let avito_requests_count = sqlx::query_scalar!(
-		"SELECT COUNT(*) FROM avito_requests WHERE user_id = $1",
-		requested_user_id
-	)
-	.fetch_one(&data.db)
-	.await
-	.unwrap_or(Some(0i64))
-	.unwrap_or(0i64);
+	let page = query_result.expect(error_message);
 
 	let json_response = json!({
 		"status": "success",
-	"data": json!({
-			"avito_requests": &avito_requests.into_iter().map(|request| filter_add_avito_request_record(&request)).collect::<Vec<FilteredAvitoRequest>>(),
-			"avito_requests_count": &avito_requests_count,
-		})
+		"data": json!({
+			"avito_requests": &page.items.iter().map(filter_add_avito_request_record).collect::<Vec<FilteredAvitoRequest>>(),
+			"avito_requests_count": &page.total,
+		}),
+		"pagination": page.pagination()
 	});
 
 	HttpResponse::Ok().json(json_response)
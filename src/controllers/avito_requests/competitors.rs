@@ -0,0 +1,50 @@
+use crate::{
+	api::avito_requests::load_competitors,
+	controllers::avito_requests::find_own_avito_request,
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, CompetitorsQuery},
+	AppState,
+};
+use actix_web::{
+	get,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+const DEFAULT_RUNS: i64 = 10;
+const MAX_RUNS: i64 = 60;
+
+// GET top competing sellers of a crawl run with their rating trend across the search
+#[get("/avito_requests/{avito_request_id}/competitors")]
+pub async fn get_avito_request_competitors_handler(
+	path: Path<Uuid>,
+	opts: web::Query<CompetitorsQuery>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let avito_request =
+		match find_own_avito_request(&data.db, path.into_inner(), user.user_id).await? {
+			Ok(avito_request) => avito_request,
+			Err(response) => return Ok(response),
+		};
+
+	// Read only: the progress consumer syncs sellers while a run goes and once it finishes
+	let limit = opts.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+	let runs = opts.runs.unwrap_or(DEFAULT_RUNS).clamp(1, MAX_RUNS);
+	let competitors = load_competitors(&data.db, avito_request.request_id, limit, runs).await?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"request_id": avito_request.request_id,
+			"request": avito_request.request,
+			"city": avito_request.city,
+			"createdTs": avito_request.created_ts,
+			"competitors": competitors
+		})
+	})))
+}
@@ -0,0 +1,150 @@
+use crate::{
+	controllers::avito_requests::{
+		find_own_avito_request, publish_avito_request_control, queue_crawl,
+		AvitoRequestControlMessage, AvitoRequestMessage,
+	},
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, AvitoRequest, CrawlProgress, CrawlStatus},
+	utils::avito_requests::filter_add_avito_request_record,
+	AppState,
+};
+use actix_web::{
+	post,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+// POST stop a queued or running crawl
+#[post("/avito_requests/{request_id}/cancel")]
+pub async fn cancel_avito_request_handler(
+	path: Path<Uuid>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let avito_request =
+		match find_own_avito_request(&data.db, path.into_inner(), user.user_id).await? {
+			Ok(avito_request) => avito_request,
+			Err(response) => return Ok(response),
+		};
+
+	// Marked first, so progress the crawler sends before it stops is ignored
+	let progress = CrawlProgress {
+		request_id: avito_request.request_id,
+		status: CrawlStatus::Cancelled,
+		progress_current: None,
+		progress_total: None,
+		ads_count: None,
+		error: None,
+	};
+	let cancelled = AvitoRequest::apply_crawl_progress(&data.db, &progress)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to cancel request: {}", e)))?;
+	if cancelled.is_none() {
+		// The crawl may have finished since it was read above
+		let status = AvitoRequest::get_avito_request_by_id(&data.db, avito_request.request_id)
+			.await
+			.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch request: {}", e)))?
+			.map(|current| current.status)
+			.unwrap_or(avito_request.status);
+		return Ok(HttpResponse::Conflict().json(json!({
+			"status": "error",
+			"message": format!("Crawl is already {}", status)
+		})));
+	}
+
+	let message = AvitoRequestControlMessage {
+		request_id: avito_request.request_id,
+		user_id: avito_request.user_id,
+		action: "cancel".to_string(),
+		created_ts: chrono::Utc::now(),
+	};
+	let crawler_notified =
+		match publish_avito_request_control(&data.rabbitmq_channel, &message).await {
+			Ok(_) => true,
+			Err(e) => {
+				eprintln!(
+					"Failed to publish cancel of avito request {}: {}",
+					avito_request.request_id, e
+				);
+				false
+			}
+		};
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"request_id": avito_request.request_id,
+			"crawl_status": CrawlStatus::Cancelled,
+			"crawler_notified": crawler_notified
+		})
+	})))
+}
+
+// POST queue a new crawl with the parameters of an earlier one
+#[post("/avito_requests/{request_id}/rerun")]
+pub async fn rerun_avito_request_handler(
+	path: Path<Uuid>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let avito_request =
+		match find_own_avito_request(&data.db, path.into_inner(), user.user_id).await? {
+			Ok(avito_request) => avito_request,
+			Err(response) => return Ok(response),
+		};
+
+	let active =
+		CrawlStatus::parse(&avito_request.status).is_some_and(|status| !status.is_finished());
+	if active {
+		return Ok(HttpResponse::Conflict().json(json!({
+			"status": "error",
+			"message": "Crawl is still in progress, cancel it before running it again"
+		})));
+	}
+
+	// A rerun of a scheduled crawl stays among the runs of its schedule
+	let message = sqlx::query_as::<_, AvitoRequestMessage>(
+		r#"
+        INSERT INTO avito_requests (user_id, request, city, coords, radius, district, schedule_id)
+        SELECT user_id, request, city, coords, radius, district, schedule_id
+        FROM avito_requests
+        WHERE request_id = $1
+        RETURNING request_id, user_id, COALESCE(request, '') AS request, COALESCE(city, '') AS city,
+            COALESCE(coords, '') AS coords, COALESCE(radius, '') AS radius,
+            COALESCE(district, '') AS district, COALESCE(created_ts, NOW()) AS created_ts
+        "#,
+	)
+	.bind(avito_request.request_id)
+	.fetch_one(&data.db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to create request: {}", e)))?;
+
+	let queued = match queue_crawl(&data.db, &data.rabbitmq_channel, &message).await {
+		Ok(_) => true,
+		Err(e) => {
+			eprintln!("Failed to publish rerun {}: {}", message.request_id, e);
+			false
+		}
+	};
+
+	let rerun = AvitoRequest::get_avito_request_by_id(&data.db, message.request_id)
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch request: {}", e)))?
+		.ok_or_else(|| ApiError::InternalServerError("Rerun request disappeared".to_string()))?;
+
+	let response = json!({
+		"status": "success",
+		"data": json!({
+			"rerun_of": avito_request.request_id,
+			"avito_request": filter_add_avito_request_record(&rerun)
+		})
+	});
+	if queued {
+		Ok(HttpResponse::Ok().json(response))
+	} else {
+		// Same as creating a request: the row exists but the crawler was not notified
+		Ok(HttpResponse::Accepted().json(response))
+	}
+}
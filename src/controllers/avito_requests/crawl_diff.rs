@@ -0,0 +1,80 @@
+use crate::{
+	api::avito_requests::{is_same_search, load_own_ad_ids, load_request_ads},
+	controllers::avito_requests::find_own_avito_request,
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, CrawlDiffQuery},
+	utils::crawl_diff::diff_crawl_runs,
+	AppState,
+};
+use actix_web::{
+	get,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+const DEFAULT_POSITION_JUMP: i32 = 5;
+
+// GET what changed in the results between two runs of the same search
+#[get("/avito_requests/{from_request_id}/diff/{to_request_id}")]
+pub async fn get_avito_requests_diff_handler(
+	path: Path<(Uuid, Uuid)>,
+	opts: web::Query<CrawlDiffQuery>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let (from_id, to_id) = path.into_inner();
+	if from_id == to_id {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "Two different runs are required"
+		})));
+	}
+
+	let mut runs = Vec::new();
+	for request_id in [from_id, to_id] {
+		match find_own_avito_request(&data.db, request_id, user.user_id).await? {
+			Ok(avito_request) => runs.push(avito_request),
+			Err(response) => return Ok(response),
+		}
+	}
+	if !is_same_search(&data.db, from_id, to_id).await? {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "Runs belong to different searches"
+		})));
+	}
+
+	let position_jump = opts.position_jump.unwrap_or(DEFAULT_POSITION_JUMP).max(1);
+	let from_ads = load_request_ads(&data.db, from_id).await?;
+	let to_ads = load_request_ads(&data.db, to_id).await?;
+	let own_ads = load_own_ad_ids(&data.db, &[from_id, to_id]).await?;
+	let diff = diff_crawl_runs(&from_ads, &to_ads, &own_ads, position_jump);
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"from": json!({
+				"request_id": from_id,
+				"createdTs": runs[0].created_ts,
+				"ads_count": from_ads.len()
+			}),
+			"to": json!({
+				"request_id": to_id,
+				"createdTs": runs[1].created_ts,
+				"ads_count": to_ads.len()
+			}),
+			"position_jump": position_jump,
+			"summary": json!({
+				"new_ads": diff.new_ads.len(),
+				"removed_ads": diff.removed_ads.len(),
+				"price_changes": diff.price_changes.len(),
+				"promotion_changes": diff.promotion_changes.len(),
+				"position_changes": diff.position_changes.len(),
+				"unchanged": diff.unchanged
+			}),
+			"diff": diff
+		})
+	})))
+}
@@ -0,0 +1,242 @@
+use crate::{
+	controllers::avito_requests::find_own_avito_request,
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, AvitoRequest, CrawlExportQuery, ExportFormat, HeaderLang},
+	utils::crawl_export::{
+		export_filename, export_json_select, export_text_select, select_export_columns,
+		ExportColumn,
+	},
+	AppState,
+};
+use actix_web::{
+	get,
+	web::{self, Bytes, Path},
+	HttpResponse,
+};
+use futures::TryStreamExt;
+use rust_xlsxwriter::{Format, Workbook};
+use serde_json::json;
+use sqlx::{Pool, Postgres, Row};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+// Streamed bodies are sent in chunks of about this size
+const CHUNK_SIZE: usize = 64 * 1024;
+
+type Chunk = Result<Bytes, std::io::Error>;
+
+// Export of one run's crawled ads. CSV and NDJSON are streamed from the database as they are
+// read; XLSX is assembled in memory since the workbook is only written out at the end.
+pub async fn export_request_ads(
+	db: &Pool<Postgres>,
+	avito_request: &AvitoRequest,
+	opts: &CrawlExportQuery,
+) -> Result<HttpResponse, ApiError> {
+	let bad_request = |message: String| -> Result<HttpResponse, ApiError> {
+		Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": message
+		})))
+	};
+
+	let Some(format) = ExportFormat::parse(opts.format.as_deref().unwrap_or("csv")) else {
+		return bad_request("Unsupported format, expected csv, xlsx or ndjson".to_string());
+	};
+	let Some(lang) = HeaderLang::parse(opts.lang.as_deref().unwrap_or("ru")) else {
+		return bad_request("Unsupported header language, expected ru or en".to_string());
+	};
+	let columns = match select_export_columns(opts.columns.as_deref()) {
+		Ok(columns) => columns,
+		Err(message) => return bad_request(message),
+	};
+
+	let select = match format {
+		ExportFormat::Ndjson => export_json_select(&columns),
+		ExportFormat::Csv | ExportFormat::Xlsx => export_text_select(&columns),
+	};
+	let query = format!(
+		"SELECT {} FROM avito_analytics_ads WHERE avito_request_id = $1 ORDER BY position, ad_id",
+		select
+	);
+	let headers: Vec<&'static str> = columns.iter().map(|column| column.header(lang)).collect();
+
+	// Named after the first exported ad like the original CSV export
+	let search_query: Option<String> = sqlx::query_scalar(
+		"SELECT search_query FROM avito_analytics_ads WHERE avito_request_id = $1 ORDER BY position, ad_id LIMIT 1",
+	)
+	.bind(avito_request.request_id)
+	.fetch_optional(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch crawled ads: {}", e)))?;
+	let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+
+	let mut response = HttpResponse::Ok();
+	response.content_type(format.content_type()).append_header((
+		"Content-Disposition",
+		format!(
+			"attachment; filename=\"{}\"",
+			export_filename(search_query.as_deref(), &date, format)
+		),
+	));
+
+	if format == ExportFormat::Xlsx {
+		let body =
+			write_xlsx_export(db, &query, avito_request.request_id, &columns, &headers).await?;
+		return Ok(response.body(body));
+	}
+
+	// The task keeps reading while the client keeps up; a closed connection drops the receiver
+	let (sender, receiver) = mpsc::channel::<Chunk>(4);
+	let db = db.clone();
+	let request_id = avito_request.request_id;
+	actix_web::rt::spawn(async move {
+		if let Err(e) = stream_export(&db, &query, request_id, format, &headers, &sender).await {
+			eprintln!("Failed to export ads of {}: {}", request_id, e);
+			let _ = sender.send(Err(std::io::Error::other(e))).await;
+		}
+	});
+
+	let body = futures::stream::unfold(receiver, |mut receiver| async move {
+		receiver.recv().await.map(|chunk| (chunk, receiver))
+	});
+	Ok(response.streaming(body))
+}
+
+async fn stream_export(
+	db: &Pool<Postgres>,
+	query: &str,
+	request_id: Uuid,
+	format: ExportFormat,
+	headers: &[&str],
+	sender: &mpsc::Sender<Chunk>,
+) -> Result<(), String> {
+	let mut csv_writer = csv::Writer::from_writer(Vec::new());
+	let mut buffer: Vec<u8> = Vec::new();
+	if format == ExportFormat::Csv {
+		csv_writer
+			.write_record(headers)
+			.map_err(|e| format!("Failed to write CSV: {}", e))?;
+	}
+
+	let mut rows = sqlx::query(query).bind(request_id).fetch(db);
+	while let Some(row) = rows
+		.try_next()
+		.await
+		.map_err(|e| format!("Failed to fetch crawled ads: {}", e))?
+	{
+		if format == ExportFormat::Ndjson {
+			let line: String = row
+				.try_get(0)
+				.map_err(|e| format!("Failed to read row: {}", e))?;
+			buffer.extend_from_slice(line.as_bytes());
+			buffer.push(b'\n');
+		} else {
+			let values = (0..headers.len())
+				.map(|i| {
+					row.try_get::<Option<String>, _>(i)
+						.map(Option::unwrap_or_default)
+				})
+				.collect::<Result<Vec<String>, _>>()
+				.map_err(|e| format!("Failed to read row: {}", e))?;
+			csv_writer
+				.write_record(&values)
+				.map_err(|e| format!("Failed to write CSV: {}", e))?;
+			// The writer flushes into its Vec on its own as its buffer fills up
+			if csv_writer.get_ref().len() >= CHUNK_SIZE {
+				buffer.append(&mut take_csv(&mut csv_writer)?);
+			}
+		}
+
+		if buffer.len() >= CHUNK_SIZE && !send_chunk(sender, &mut buffer).await {
+			// The client went away
+			return Ok(());
+		}
+	}
+
+	if format == ExportFormat::Csv {
+		buffer.append(&mut take_csv(&mut csv_writer)?);
+	}
+	send_chunk(sender, &mut buffer).await;
+	Ok(())
+}
+
+// Everything written so far, leaving a fresh writer in place
+fn take_csv(csv_writer: &mut csv::Writer<Vec<u8>>) -> Result<Vec<u8>, String> {
+	std::mem::replace(csv_writer, csv::Writer::from_writer(Vec::new()))
+		.into_inner()
+		.map_err(|e| format!("Failed to write CSV: {}", e))
+}
+
+// Sends the buffered bytes; false once the response has been dropped
+async fn send_chunk(sender: &mpsc::Sender<Chunk>, buffer: &mut Vec<u8>) -> bool {
+	if buffer.is_empty() {
+		return true;
+	}
+	sender
+		.send(Ok(Bytes::from(std::mem::take(buffer))))
+		.await
+		.is_ok()
+}
+
+async fn write_xlsx_export(
+	db: &Pool<Postgres>,
+	query: &str,
+	request_id: Uuid,
+	columns: &[&ExportColumn],
+	headers: &[&str],
+) -> Result<Vec<u8>, ApiError> {
+	let xlsx_error =
+		|e: rust_xlsxwriter::XlsxError| ApiError::Other(format!("Failed to write XLSX: {}", e));
+	let mut workbook = Workbook::new();
+	let header_format = Format::new().set_bold();
+	let worksheet = workbook.add_worksheet();
+
+	for (col, header) in headers.iter().enumerate() {
+		worksheet
+			.write_string_with_format(0, col as u16, *header, &header_format)
+			.map_err(xlsx_error)?;
+	}
+
+	let mut rows = sqlx::query(query).bind(request_id).fetch(db);
+	let mut line: u32 = 1;
+	while let Some(row) = rows
+		.try_next()
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch crawled ads: {}", e)))?
+	{
+		for (col, column) in columns.iter().enumerate() {
+			let value: Option<String> = row
+				.try_get(col)
+				.map_err(|e| ApiError::InternalServerError(format!("Failed to read row: {}", e)))?;
+			let Some(value) = value.filter(|value| !value.is_empty()) else {
+				continue;
+			};
+			match value.parse::<f64>() {
+				Ok(number) if column.numeric => worksheet.write_number(line, col as u16, number),
+				_ => worksheet.write_string(line, col as u16, &value),
+			}
+			.map_err(xlsx_error)?;
+		}
+		line += 1;
+	}
+	worksheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+
+	workbook.save_to_buffer().map_err(xlsx_error)
+}
+
+// GET crawled ads of a run as CSV, XLSX or NDJSON with the chosen columns
+#[get("/avito_requests/{avito_request_id}/ads/export")]
+pub async fn export_avito_request_ads_handler(
+	path: Path<Uuid>,
+	opts: web::Query<CrawlExportQuery>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let avito_request =
+		match find_own_avito_request(&data.db, path.into_inner(), user.user_id).await? {
+			Ok(avito_request) => avito_request,
+			Err(response) => return Ok(response),
+		};
+
+	export_request_ads(&data.db, &avito_request, &opts).await
+}
@@ -0,0 +1,393 @@
+use crate::{
+	api::avito_requests::AVITO_REQUESTS_KEYSET,
+	controllers::avito_requests::{queue_crawl, AvitoRequestMessage},
+	jwt_auth::JwtMiddleware,
+	models::{
+		ApiError, AvitoCrawlSchedule, AvitoRequest, CreateCrawlScheduleSchema, FilterOptions,
+		FilteredAvitoRequest, UpdateCrawlScheduleSchema,
+	},
+	utils::avito_requests::filter_add_avito_request_record,
+	AppState,
+};
+use actix_web::{
+	delete, get, post, put,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+use uuid::Uuid;
+
+// Due schedules dispatched per scheduler transaction
+const CRAWL_SCHEDULER_BATCH_SIZE: i64 = 100;
+
+const SCHEDULE_COLUMNS: &str =
+	"schedule_id, user_id, name, request, city, coords, radius, district,
+	interval_hours, is_active, next_run_ts, last_run_ts, last_request_id, created_ts, updated_ts";
+
+fn validate_interval(interval_hours: Option<i32>) -> Result<(), String> {
+	match interval_hours {
+		Some(hours) if !(1..=720).contains(&hours) => {
+			Err("interval_hours must be between 1 and 720".to_string())
+		}
+		_ => Ok(()),
+	}
+}
+
+// GET saved searches of the current user
+#[get("/avito_crawl_schedules")]
+pub async fn get_crawl_schedules_handler(
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let schedules = sqlx::query_as::<_, AvitoCrawlSchedule>(&format!(
+		"SELECT {} FROM avito_crawl_schedules WHERE user_id = $1 ORDER BY created_ts DESC",
+		SCHEDULE_COLUMNS
+	))
+	.bind(user.user_id)
+	.fetch_all(&data.db)
+	.await
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to fetch crawl schedules: {}", e))
+	})?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"crawl_schedules": schedules
+		})
+	})))
+}
+
+// GET saved search by id
+#[get("/avito_crawl_schedules/{schedule_id}")]
+pub async fn get_crawl_schedule_handler(
+	path: Path<Uuid>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	match fetch_crawl_schedule(&data.db, path.into_inner(), user.user_id).await? {
+		Some(schedule) => Ok(HttpResponse::Ok().json(json!({
+			"status": "success",
+			"data": json!({
+				"crawl_schedule": schedule
+			})
+		}))),
+		None => Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Crawl schedule not found"
+		}))),
+	}
+}
+
+// Create saved search
+#[post("/avito_crawl_schedules")]
+pub async fn create_crawl_schedule_handler(
+	body: web::Json<CreateCrawlScheduleSchema>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	if body.request.trim().is_empty() {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "Search request cannot be empty"
+		})));
+	}
+	if let Err(message) = validate_interval(body.interval_hours) {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": message
+		})));
+	}
+
+	let schedule = sqlx::query_as::<_, AvitoCrawlSchedule>(&format!(
+		r#"
+        INSERT INTO avito_crawl_schedules
+            (user_id, name, request, city, coords, radius, district, interval_hours, next_run_ts)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, COALESCE($8, 24), COALESCE($9, NOW()))
+        RETURNING {}
+        "#,
+		SCHEDULE_COLUMNS
+	))
+	.bind(user.user_id)
+	.bind(&body.name)
+	.bind(body.request.trim())
+	.bind(&body.city)
+	.bind(&body.coords)
+	.bind(&body.radius)
+	.bind(&body.district)
+	.bind(body.interval_hours)
+	.bind(body.start_at)
+	.fetch_one(&data.db)
+	.await
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to create crawl schedule: {}", e))
+	})?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"crawl_schedule": schedule
+		})
+	})))
+}
+
+// Update saved search; pause and resume with is_active
+#[put("/avito_crawl_schedules/{schedule_id}")]
+pub async fn update_crawl_schedule_handler(
+	path: Path<Uuid>,
+	body: web::Json<UpdateCrawlScheduleSchema>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	if body
+		.request
+		.as_ref()
+		.map(|request| request.trim().is_empty())
+		.unwrap_or(false)
+	{
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": "Search request cannot be empty"
+		})));
+	}
+	if let Err(message) = validate_interval(body.interval_hours) {
+		return Ok(HttpResponse::BadRequest().json(json!({
+			"status": "error",
+			"message": message
+		})));
+	}
+
+	let schedule = sqlx::query_as::<_, AvitoCrawlSchedule>(&format!(
+		r#"
+        UPDATE avito_crawl_schedules
+        SET name = COALESCE($3, name),
+            request = COALESCE($4, request),
+            city = COALESCE($5, city),
+            coords = COALESCE($6, coords),
+            radius = COALESCE($7, radius),
+            district = COALESCE($8, district),
+            interval_hours = COALESCE($9, interval_hours),
+            is_active = COALESCE($10, is_active),
+            next_run_ts = COALESCE($11, next_run_ts),
+            updated_ts = NOW()
+        WHERE schedule_id = $1 AND user_id = $2
+        RETURNING {}
+        "#,
+		SCHEDULE_COLUMNS
+	))
+	.bind(path.into_inner())
+	.bind(user.user_id)
+	.bind(&body.name)
+	.bind(body.request.as_ref().map(|request| request.trim()))
+	.bind(&body.city)
+	.bind(&body.coords)
+	.bind(&body.radius)
+	.bind(&body.district)
+	.bind(body.interval_hours)
+	.bind(body.is_active)
+	.bind(body.next_run_at)
+	.fetch_optional(&data.db)
+	.await
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to update crawl schedule: {}", e))
+	})?;
+
+	match schedule {
+		Some(schedule) => Ok(HttpResponse::Ok().json(json!({
+			"status": "success",
+			"data": json!({
+				"crawl_schedule": schedule
+			})
+		}))),
+		None => Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Crawl schedule not found"
+		}))),
+	}
+}
+
+// Delete saved search; its past runs and their ads are kept
+#[delete("/avito_crawl_schedules/{schedule_id}")]
+pub async fn delete_crawl_schedule_handler(
+	path: Path<Uuid>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let result =
+		sqlx::query("DELETE FROM avito_crawl_schedules WHERE schedule_id = $1 AND user_id = $2")
+			.bind(path.into_inner())
+			.bind(user.user_id)
+			.execute(&data.db)
+			.await
+			.map_err(|e| {
+				ApiError::InternalServerError(format!("Failed to delete crawl schedule: {}", e))
+			})?;
+
+	if result.rows_affected() == 0 {
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Crawl schedule not found"
+		})));
+	}
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"message": "Crawl schedule deleted successfully"
+	})))
+}
+
+// GET runs of a saved search, newest first by default
+#[get("/avito_crawl_schedules/{schedule_id}/runs")]
+pub async fn get_crawl_schedule_runs_handler(
+	path: Path<Uuid>,
+	opts: web::Query<FilterOptions>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let schedule_id = path.into_inner();
+	if fetch_crawl_schedule(&data.db, schedule_id, user.user_id)
+		.await?
+		.is_none()
+	{
+		return Ok(HttpResponse::NotFound().json(json!({
+			"status": "error",
+			"message": "Crawl schedule not found"
+		})));
+	}
+
+	let request = match AVITO_REQUESTS_KEYSET.request(&opts) {
+		Ok(request) => request,
+		Err(message) => {
+			return Ok(HttpResponse::BadRequest().json(json!({
+				"status": "error",
+				"message": message
+			})));
+		}
+	};
+
+	let page = AVITO_REQUESTS_KEYSET
+		.fetch::<AvitoRequest, _>(&data.db, &request, "*", |builder| {
+			builder.push(" AND schedule_id = ");
+			builder.push_bind(schedule_id);
+		})
+		.await
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch crawl runs: {}", e)))?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"avito_requests": page.items.iter().map(filter_add_avito_request_record).collect::<Vec<FilteredAvitoRequest>>(),
+			"avito_requests_count": page.total,
+		}),
+		"pagination": page.pagination()
+	})))
+}
+
+async fn fetch_crawl_schedule(
+	db: &Pool<Postgres>,
+	schedule_id: Uuid,
+	user_id: Uuid,
+) -> Result<Option<AvitoCrawlSchedule>, ApiError> {
+	sqlx::query_as::<_, AvitoCrawlSchedule>(&format!(
+		"SELECT {} FROM avito_crawl_schedules WHERE schedule_id = $1 AND user_id = $2",
+		SCHEDULE_COLUMNS
+	))
+	.bind(schedule_id)
+	.bind(user_id)
+	.fetch_optional(db)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to fetch crawl schedule: {}", e)))
+}
+
+// Background task: start the crawls of due saved searches every `interval`
+pub async fn run_crawl_scheduler(db: Pool<Postgres>, channel: lapin::Channel, interval: Duration) {
+	let mut ticker = tokio::time::interval(interval);
+	loop {
+		ticker.tick().await;
+		loop {
+			match dispatch_due_crawls(&db, &channel).await {
+				// A full batch means more schedules may be due
+				Ok(dispatched) if dispatched == CRAWL_SCHEDULER_BATCH_SIZE as usize => continue,
+				Ok(_) => break,
+				Err(e) => {
+					eprintln!("Crawl scheduler failed: {}", e);
+					break;
+				}
+			}
+		}
+	}
+}
+
+// Creates one run per due schedule and publishes its crawl task. A schedule waits while its
+// previous run is still queued or running, unless that run is older than the interval.
+async fn dispatch_due_crawls(
+	db: &Pool<Postgres>,
+	channel: &lapin::Channel,
+) -> Result<usize, ApiError> {
+	let mut tx = db.begin().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to start transaction: {}", e))
+	})?;
+
+	// Missed runs are skipped: the next run moves to the first slot after now
+	let runs = sqlx::query_as::<_, AvitoRequestMessage>(
+		r#"
+        WITH due AS (
+            SELECT s.schedule_id
+            FROM avito_crawl_schedules s
+            LEFT JOIN avito_requests r ON r.request_id = s.last_request_id
+            WHERE s.is_active
+                AND s.next_run_ts <= NOW()
+                AND (
+                    r.request_id IS NULL
+                    OR r.status NOT IN ('queued', 'running')
+                    OR r.created_ts < NOW() - make_interval(hours => s.interval_hours)
+                )
+            ORDER BY s.next_run_ts
+            LIMIT $1
+            FOR UPDATE OF s SKIP LOCKED
+        ),
+        runs AS (
+            INSERT INTO avito_requests (user_id, request, city, coords, radius, district, schedule_id)
+            SELECT s.user_id, s.request, s.city, s.coords, s.radius, s.district, s.schedule_id
+            FROM avito_crawl_schedules s
+            JOIN due ON due.schedule_id = s.schedule_id
+            RETURNING request_id, user_id, request, city, coords, radius, district, created_ts, schedule_id
+        )
+        UPDATE avito_crawl_schedules s
+        SET last_run_ts = NOW(),
+            last_request_id = runs.request_id,
+            next_run_ts = s.next_run_ts + make_interval(hours => s.interval_hours * (
+                floor(extract(epoch FROM NOW() - s.next_run_ts) / (s.interval_hours * 3600))::int + 1
+            ))
+        FROM runs
+        WHERE s.schedule_id = runs.schedule_id
+        RETURNING runs.request_id, runs.user_id, runs.request, runs.city, runs.coords,
+            runs.radius, runs.district, runs.created_ts
+        "#,
+	)
+	.bind(CRAWL_SCHEDULER_BATCH_SIZE)
+	.fetch_all(&mut *tx)
+	.await
+	.map_err(|e| ApiError::InternalServerError(format!("Failed to claim crawl schedules: {}", e)))?;
+
+	tx.commit().await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to commit transaction: {}", e))
+	})?;
+
+	for run in &runs {
+		if let Err(e) = queue_crawl(db, channel, run).await {
+			eprintln!(
+				"Failed to publish scheduled crawl {}: {}",
+				run.request_id, e
+			);
+		}
+	}
+
+	if !runs.is_empty() {
+		println!("Crawl scheduler started {} scheduled crawls", runs.len());
+	}
+
+	Ok(runs.len())
+}
@@ -1,3 +1,19 @@
 pub mod avito_requests;
+pub mod competitors;
+pub mod crawl_control;
+pub mod crawl_diff;
+pub mod crawl_export;
+pub mod crawl_schedules;
+pub mod positions;
+pub mod price_stats;
+pub mod promotions;
 
 pub use self::avito_requests::*;
+pub use self::competitors::*;
+pub use self::crawl_control::*;
+pub use self::crawl_diff::*;
+pub use self::crawl_export::*;
+pub use self::crawl_schedules::*;
+pub use self::positions::*;
+pub use self::price_stats::*;
+pub use self::promotions::*;
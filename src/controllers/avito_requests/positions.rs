@@ -0,0 +1,106 @@
+use crate::{
+	api::avito_requests::{load_own_ad_positions, load_position_runs},
+	controllers::avito_requests::find_own_avito_request,
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, AvitoRequest, PositionRun, PositionsQuery},
+	utils::{
+		crawl_export::file_name_query,
+		position_report::{build_position_report, position_report_csv},
+	},
+	AppState,
+};
+use actix_web::{
+	get,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+const DEFAULT_RUNS: i64 = 10;
+const MAX_RUNS: i64 = 60;
+const DEFAULT_TOP: i32 = 10;
+
+// Resolves the run, checks it belongs to the user and loads the runs of its search
+async fn load_report_runs(
+	data: &web::Data<AppState>,
+	request_id: Uuid,
+	user_id: Uuid,
+	opts: &PositionsQuery,
+) -> Result<Result<(AvitoRequest, Vec<PositionRun>, i32), HttpResponse>, ApiError> {
+	let avito_request = match find_own_avito_request(&data.db, request_id, user_id).await? {
+		Ok(avito_request) => avito_request,
+		Err(response) => return Ok(Err(response)),
+	};
+
+	let runs_limit = opts.runs.unwrap_or(DEFAULT_RUNS).clamp(1, MAX_RUNS);
+	let top = opts.top.unwrap_or(DEFAULT_TOP).max(1);
+	let runs = load_position_runs(&data.db, request_id, runs_limit, top).await?;
+	Ok(Ok((avito_request, runs, top)))
+}
+
+// GET positions of our ads across the runs of the same search
+#[get("/avito_requests/{avito_request_id}/positions")]
+pub async fn get_avito_request_positions_handler(
+	path: Path<Uuid>,
+	opts: web::Query<PositionsQuery>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let (avito_request, runs, top) =
+		match load_report_runs(&data, path.into_inner(), user.user_id, &opts).await? {
+			Ok(loaded) => loaded,
+			Err(response) => return Ok(response),
+		};
+
+	let run_ids: Vec<Uuid> = runs.iter().map(|run| run.request_id).collect();
+	let positions = load_own_ad_positions(&data.db, &run_ids).await?;
+	let ads = build_position_report(&runs, positions);
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"request": avito_request.request,
+			"city": avito_request.city,
+			"top": top,
+			"runs": runs,
+			"ads": ads
+		})
+	})))
+}
+
+// GET positions report as a csv file
+#[get("/avito_requests/{avito_request_id}/positions/csv")]
+pub async fn get_avito_request_positions_csv_handler(
+	path: Path<Uuid>,
+	opts: web::Query<PositionsQuery>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let (avito_request, runs, top) =
+		match load_report_runs(&data, path.into_inner(), user.user_id, &opts).await? {
+			Ok(loaded) => loaded,
+			Err(response) => return Ok(response),
+		};
+
+	let run_ids: Vec<Uuid> = runs.iter().map(|run| run.request_id).collect();
+	let positions = load_own_ad_positions(&data.db, &run_ids).await?;
+	let ads = build_position_report(&runs, positions);
+	let csv_bytes = position_report_csv(&runs, &ads, top)
+		.map_err(|e| ApiError::InternalServerError(format!("Failed to write csv: {}", e)))?;
+
+	// Same naming as the ads export, with a positions prefix
+	let filename = format!(
+		"positions_{}_{}.csv",
+		file_name_query(&avito_request.request.unwrap_or_default()),
+		chrono::Utc::now().format("%Y-%m-%d")
+	);
+
+	Ok(HttpResponse::Ok()
+		.content_type("text/csv")
+		.append_header((
+			"Content-Disposition",
+			format!("attachment; filename=\"{}\"", filename),
+		))
+		.body(csv_bytes))
+}
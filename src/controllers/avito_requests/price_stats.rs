@@ -0,0 +1,78 @@
+use crate::{
+	api::avito_requests::{load_market_price_stats, load_price_history},
+	controllers::avito_requests::find_own_avito_request,
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, PriceHistoryQuery, PriceStatsQuery},
+	AppState,
+};
+use actix_web::{
+	get,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+const DEFAULT_BUCKETS: i32 = 10;
+const MAX_BUCKETS: i32 = 50;
+const DEFAULT_RUNS: i64 = 10;
+const MAX_RUNS: i64 = 60;
+
+// GET market price statistics of one crawl run
+#[get("/avito_requests/{avito_request_id}/price_stats")]
+pub async fn get_avito_request_price_stats_handler(
+	path: Path<Uuid>,
+	opts: web::Query<PriceStatsQuery>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let avito_request =
+		match find_own_avito_request(&data.db, path.into_inner(), user.user_id).await? {
+			Ok(avito_request) => avito_request,
+			Err(response) => return Ok(response),
+		};
+
+	let buckets = opts
+		.buckets
+		.unwrap_or(DEFAULT_BUCKETS)
+		.clamp(1, MAX_BUCKETS);
+	let stats = load_market_price_stats(&data.db, avito_request.request_id, buckets).await?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"request_id": avito_request.request_id,
+			"request": avito_request.request,
+			"city": avito_request.city,
+			"createdTs": avito_request.created_ts,
+			"stats": stats
+		})
+	})))
+}
+
+// GET market price statistics across the runs of the same search
+#[get("/avito_requests/{avito_request_id}/price_stats/history")]
+pub async fn get_avito_request_price_history_handler(
+	path: Path<Uuid>,
+	opts: web::Query<PriceHistoryQuery>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let avito_request =
+		match find_own_avito_request(&data.db, path.into_inner(), user.user_id).await? {
+			Ok(avito_request) => avito_request,
+			Err(response) => return Ok(response),
+		};
+
+	let runs_limit = opts.runs.unwrap_or(DEFAULT_RUNS).clamp(1, MAX_RUNS);
+	let runs = load_price_history(&data.db, avito_request.request_id, runs_limit).await?;
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"request": avito_request.request,
+			"city": avito_request.city,
+			"runs": runs
+		})
+	})))
+}
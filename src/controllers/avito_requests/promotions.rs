@@ -0,0 +1,66 @@
+use crate::{
+	api::avito_requests::{load_promotion_runs, load_promotion_types},
+	controllers::avito_requests::find_own_avito_request,
+	jwt_auth::JwtMiddleware,
+	models::{ApiError, PromotionsQuery},
+	AppState,
+};
+use actix_web::{
+	get,
+	web::{self, Path},
+	HttpResponse,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+const DEFAULT_RUNS: i64 = 10;
+const MAX_RUNS: i64 = 60;
+const DEFAULT_TOP: i32 = 10;
+const MAX_TOP: i32 = 100;
+
+// GET how promoted ads hold the top positions of a search, per promotion type and across runs
+#[get("/avito_requests/{avito_request_id}/promotions")]
+pub async fn get_avito_request_promotions_handler(
+	path: Path<Uuid>,
+	opts: web::Query<PromotionsQuery>,
+	data: web::Data<AppState>,
+	user: JwtMiddleware,
+) -> Result<HttpResponse, ApiError> {
+	let avito_request =
+		match find_own_avito_request(&data.db, path.into_inner(), user.user_id).await? {
+			Ok(avito_request) => avito_request,
+			Err(response) => return Ok(response),
+		};
+
+	let top = opts.top.unwrap_or(DEFAULT_TOP).clamp(1, MAX_TOP);
+	let runs_limit = opts.runs.unwrap_or(DEFAULT_RUNS).clamp(1, MAX_RUNS);
+	let types = load_promotion_types(&data.db, avito_request.request_id, top).await?;
+	let runs = load_promotion_runs(&data.db, avito_request.request_id, runs_limit, top).await?;
+
+	// Change of the promoted share of the top between the oldest and the newest run shown
+	let top_share_change = match (runs.first(), runs.last()) {
+		(Some(first), Some(last)) if runs.len() > 1 => last
+			.promoted_top_share
+			.zip(first.promoted_top_share)
+			.map(|(last, first)| last - first),
+		_ => None,
+	};
+	let current = runs
+		.iter()
+		.find(|run| run.request_id == avito_request.request_id);
+
+	Ok(HttpResponse::Ok().json(json!({
+		"status": "success",
+		"data": json!({
+			"request_id": avito_request.request_id,
+			"request": avito_request.request,
+			"city": avito_request.city,
+			"createdTs": avito_request.created_ts,
+			"top": top,
+			"current": current,
+			"types": types,
+			"runs": runs,
+			"top_share_change": top_share_change
+		})
+	})))
+}
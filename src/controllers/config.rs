@@ -5,10 +5,13 @@ use crate::controllers::ai_description_processing::*;
 use crate::controllers::ai_title_processing::*;
 use crate::controllers::auth::*;
 use crate::controllers::avito_accounts::*;
+use crate::controllers::avito_ad_templates::*;
 use crate::controllers::avito_ads::*;
 use crate::controllers::avito_client::*;
 use crate::controllers::avito_editor::*;
 use crate::controllers::avito_feeds::*;
+use crate::controllers::avito_images::*;
+use crate::controllers::avito_import_profiles::*;
 use crate::controllers::avito_requests::*;
 use crate::controllers::user::*;
 use crate::controllers::websocket::*;
@@ -35,24 +38,73 @@ pub fn config(conf: &mut web::ServiceConfig) {
 		.service(get_avito_balance)
 		.service(update_avito_price)
 		.service(import_avito_xml)
+		.service(import_avito_xml_file)
+		.service(import_avito_table)
+		.service(import_yml)
+		.service(validate_avito_xml)
+		.service(validate_avito_xml_file)
 		.service(avito_create_ad)
 		.service(avito_update_ad)
+		.service(clone_avito_ad)
 		.service(avito_batch_update_ads)
 		.service(avito_delete_ad)
+		.service(search_avito_ads_handler)
+		.service(change_avito_ads_status)
+		.service(get_avito_ad_status_history)
+		.service(schedule_avito_ads)
+		.service(find_avito_duplicates)
+		.service(resolve_avito_duplicates)
 		.service(get_avito_categories_tree)
 		.service(get_avito_category_fields)
 		.service(get_avito_feeds)
 		.service(get_avito_feed_by_id)
 		.service(get_avito_feed_ad)
+		.service(export_avito_feed)
+		.service(reimport_avito_feed)
+		.service(preview_bulk_edit_handler)
+		.service(bulk_edit_handler)
+		.service(mirror_feed_images)
+		.service(get_avito_ad_images)
+		.service(get_avito_image)
+		.service(check_feed_images)
 		.service(fetch_and_update_avito_ads)
 		.service(create_avito_request_handler)
+		.service(get_avito_request_status_handler)
+		.service(get_crawl_schedules_handler)
+		.service(get_crawl_schedule_handler)
+		.service(create_crawl_schedule_handler)
+		.service(update_crawl_schedule_handler)
+		.service(delete_crawl_schedule_handler)
+		.service(get_crawl_schedule_runs_handler)
 		.service(get_ads_by_avito_request_id_handler)
 		.service(get_ads_by_avito_request_id_csv_handler)
+		.service(get_avito_request_positions_handler)
+		.service(get_avito_request_positions_csv_handler)
+		.service(get_avito_requests_diff_handler)
+		.service(get_avito_request_price_stats_handler)
+		.service(get_avito_request_price_history_handler)
+		.service(get_avito_request_competitors_handler)
+		.service(export_avito_request_ads_handler)
+		.service(cancel_avito_request_handler)
+		.service(rerun_avito_request_handler)
+		.service(get_avito_request_promotions_handler)
 		.service(get_avito_accounts_handler)
 		.service(get_avito_account_by_id_handler)
 		.service(create_avito_account_handler)
 		.service(update_avito_account_handler)
 		.service(delete_avito_account_handler)
+		.service(get_import_profiles_handler)
+		.service(get_import_profile_handler)
+		.service(create_import_profile_handler)
+		.service(update_import_profile_handler)
+		.service(delete_import_profile_handler)
+		.service(get_ad_templates_handler)
+		.service(get_ad_template_handler)
+		.service(create_ad_template_handler)
+		.service(update_ad_template_handler)
+		.service(delete_ad_template_handler)
+		.service(create_ad_from_template_handler)
+		.service(generate_ad_variants_handler)
 		.service(create_ai_title_processing_handler)
 		.service(create_ai_description_processing_handler)
 		.route(
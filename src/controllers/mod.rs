@@ -2,10 +2,13 @@ pub mod ai_description_processing;
 pub mod ai_title_processing;
 pub mod auth;
 pub mod avito_accounts;
+pub mod avito_ad_templates;
 pub mod avito_ads;
 pub mod avito_client;
 pub mod avito_editor;
 pub mod avito_feeds;
+pub mod avito_images;
+pub mod avito_import_profiles;
 pub mod avito_requests;
 pub mod config;
 pub mod rabbitmq_consumer;
@@ -1,4 +1,7 @@
+use crate::api::avito_requests::sync_request_sellers;
 use crate::controllers::websocket::WebSocketConnections;
+use crate::models::{AvitoRequest, CrawlStatus};
+use crate::utils::avito_requests::parse_crawl_progress;
 use futures::StreamExt;
 use lapin::{
 	options::{BasicConsumeOptions, QueueDeclareOptions},
@@ -6,7 +9,15 @@ use lapin::{
 	Channel,
 };
 use serde_json::Value;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+// Competitors of a running crawl are refreshed at most this often; finished runs are
+// synced once by apply_crawl_progress
+const RUNNING_SELLERS_SYNC_INTERVAL: Duration = Duration::from_secs(30);
 
 pub struct RabbitMQConsumer;
 
@@ -14,6 +25,7 @@ impl RabbitMQConsumer {
 	pub async fn start_consumer(
 		rabbitmq_channel: Channel,
 		websocket_connections: Arc<WebSocketConnections>,
+		db: Pool<Postgres>,
 	) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 		// Declare the exchange
 		rabbitmq_channel
@@ -77,6 +89,7 @@ impl RabbitMQConsumer {
 		// Process messages
 		let websocket_connections_clone = websocket_connections.clone();
 		let mut consumer_stream = consumer;
+		let mut sellers_synced: HashMap<Uuid, Instant> = HashMap::new();
 
 		while let Some(delivery_result) = consumer_stream.next().await {
 			match delivery_result {
@@ -88,6 +101,26 @@ impl RabbitMQConsumer {
 					// Parse the message as JSON to extract user_id and request_id if present
 					match serde_json::from_str::<Value>(&message_data) {
 						Ok(json_value) => {
+							// Keep the crawl job status in avito_requests up to date
+							if let Some(progress) = parse_crawl_progress(&json_value) {
+								match AvitoRequest::apply_crawl_progress(&db, &progress).await {
+									Ok(Some(CrawlStatus::Running)) => sync_running_sellers(
+										&db,
+										&mut sellers_synced,
+										progress.request_id,
+									),
+									Ok(_) => {
+										sellers_synced.remove(&progress.request_id);
+									}
+									Err(e) => {
+										eprintln!(
+											"Failed to update crawl status of request {}: {}",
+											progress.request_id, e
+										);
+									}
+								}
+							}
+
 							// Check if the message contains request_id for targeted delivery
 							if let Some(request_id) = extract_request_id_from_message(&json_value) {
 								// Send the message to specific request's WebSocket connections
@@ -146,6 +179,28 @@ impl RabbitMQConsumer {
 	}
 }
 
+// Refresh the competitors of a running crawl in the background, at most once per interval
+fn sync_running_sellers(
+	db: &Pool<Postgres>,
+	sellers_synced: &mut HashMap<Uuid, Instant>,
+	request_id: Uuid,
+) {
+	let due = sellers_synced
+		.get(&request_id)
+		.is_none_or(|synced| synced.elapsed() >= RUNNING_SELLERS_SYNC_INTERVAL);
+	if !due {
+		return;
+	}
+	sellers_synced.insert(request_id, Instant::now());
+
+	let db = db.clone();
+	tokio::spawn(async move {
+		if let Err(e) = sync_request_sellers(&db, request_id).await {
+			eprintln!("Failed to sync sellers of {}: {:?}", request_id, e);
+		}
+	});
+}
+
 // Helper function to extract user_id from message
 fn extract_user_id_from_message(json_value: &Value) -> Option<String> {
 	// First try to get user_id directly from the root object
@@ -1,6 +1,7 @@
 use crate::{
+	api::shared::{Keyset, SortKey},
 	jwt_auth,
-	models::{Count, FilterOptions, FilteredUser, UpdateUserSchema, User},
+	models::{FilterOptions, FilteredUser, UpdateUserSchema, User},
 	AppState,
 };
 use actix_web::{
@@ -15,6 +16,37 @@ use uuid::Uuid;
 use crate::controllers::auth::Role;
 use crate::utils::filter_user_record;
 
+static USERS_KEYSET: Keyset = Keyset {
+	from: "users",
+	id_expr: "id",
+	id_type: "uuid",
+	sort_keys: &[
+		SortKey {
+			name: "id",
+			expr: "id",
+			sql_type: "uuid",
+		},
+		SortKey {
+			name: "created_at",
+			expr: "COALESCE(created_at, 'epoch'::timestamptz)",
+			sql_type: "timestamptz",
+		},
+		SortKey {
+			name: "name",
+			expr: "name",
+			sql_type: "text",
+		},
+		SortKey {
+			name: "email",
+			expr: "email",
+			sql_type: "text",
+		},
+	],
+	default_descending: false,
+	default_limit: 10,
+	max_limit: 100,
+};
+
 #[get("/users")]
 #[has_any_role("Role::Admin", type = "Role")]
 async fn get_users_handler(
@@ -22,20 +54,16 @@ async fn get_users_handler(
 	data: web::Data<AppState>,
 	_: jwt_auth::JwtMiddleware,
 ) -> impl Responder {
-	let limit = opts.limit.unwrap_or(10);
-	let offset = (opts.page.unwrap_or(1) - 1) * limit;
-	let table = String::from("users");
-
-	let query_result = sqlx::query_as!(
-		User,
-		"SELECT * FROM users ORDER by id LIMIT $1 OFFSET $2",
-		limit as i32,
-		offset as i32
-	)
-	.fetch_all(&data.db)
-	.await;
+	let request = match USERS_KEYSET.request(&opts) {
+		Ok(request) => request,
+		Err(message) => {
+			return HttpResponse::BadRequest().json(json!({"status": "error","message": message}));
+		}
+	};
 
-	let users_count = Count::count(&data.db, table).await.unwrap_or(0);
+	let query_result = USERS_KEYSET
+		.fetch::<User, _>(&data.db, &request, "*", |_| {})
+		.await;
 
 	if query_result.is_err() {
 		let message = "Что-то пошло не так во время чтения пользователей";
@@ -43,14 +71,15 @@ async fn get_users_handler(
 			.json(json!({"status": "error","message": message}));
 	}
 
-	let users = query_result.unwrap();
+	let page = query_result.unwrap();
 
 	let json_response = json!({
 		"status":  "success",
 		"data": json!({
-			"users": &users.into_iter().map(|user| filter_user_record(&user)).collect::<Vec<FilteredUser>>(),
-			"users_count": &users_count
-		})
+			"users": &page.items.iter().map(filter_user_record).collect::<Vec<FilteredUser>>(),
+			"users_count": &page.total
+		}),
+		"pagination": page.pagination()
 	});
 
 	HttpResponse::Ok().json(json_response)
@@ -87,10 +87,12 @@ async fn main() -> std::io::Result<()> {
 	// Start RabbitMQ consumers
 	let rabbitmq_channel_clone = channel.clone();
 	let ws_connections_clone = Arc::new(websocket_connections.clone());
+	let consumer_pool = pool.clone();
 	tokio::spawn(async move {
 		if let Err(e) = crate::controllers::rabbitmq_consumer::RabbitMQConsumer::start_consumer(
 			rabbitmq_channel_clone,
 			ws_connections_clone,
+			consumer_pool,
 		)
 		.await
 		{
@@ -112,6 +114,33 @@ async fn main() -> std::io::Result<()> {
 		}
 	});
 
+	// Start the scheduled publish/unpublish task
+	let scheduler_pool = pool.clone();
+	let scheduler_ws_connections = websocket_connections.clone();
+	let scheduler_interval = std::time::Duration::from_secs(config.ad_scheduler_interval_secs);
+	tokio::spawn(async move {
+		crate::controllers::avito_ads::run_ad_scheduler(
+			scheduler_pool,
+			scheduler_ws_connections,
+			scheduler_interval,
+		)
+		.await;
+	});
+
+	// Start the saved search crawl scheduler
+	let crawl_scheduler_pool = pool.clone();
+	let crawl_scheduler_channel = channel.clone();
+	let crawl_scheduler_interval =
+		std::time::Duration::from_secs(config.crawl_scheduler_interval_secs);
+	tokio::spawn(async move {
+		crate::controllers::avito_requests::run_crawl_scheduler(
+			crawl_scheduler_pool,
+			crawl_scheduler_channel,
+			crawl_scheduler_interval,
+		)
+		.await;
+	});
+
 	println!("✅ Server started successfully on http://localhost:8081/api");
 
 	HttpServer::new(move || {
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Lifecycle of an ad: draft -> ready -> published -> rejected/blocked/expired -> archived
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdState {
+	Draft,
+	Ready,
+	Published,
+	Rejected,
+	Blocked,
+	Expired,
+	Archived,
+}
+
+impl AdState {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			AdState::Draft => "draft",
+			AdState::Ready => "ready",
+			AdState::Published => "published",
+			AdState::Rejected => "rejected",
+			AdState::Blocked => "blocked",
+			AdState::Expired => "expired",
+			AdState::Archived => "archived",
+		}
+	}
+
+	pub fn parse(value: &str) -> Option<AdState> {
+		match value {
+			"draft" => Some(AdState::Draft),
+			"ready" => Some(AdState::Ready),
+			"published" => Some(AdState::Published),
+			"rejected" => Some(AdState::Rejected),
+			"blocked" => Some(AdState::Blocked),
+			"expired" => Some(AdState::Expired),
+			"archived" => Some(AdState::Archived),
+			_ => None,
+		}
+	}
+}
+
+// Who asked for a state change; each source has its own allowed transitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionSource {
+	User,
+	Autoload,
+	System,
+}
+
+impl TransitionSource {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			TransitionSource::User => "user",
+			TransitionSource::Autoload => "autoload",
+			TransitionSource::System => "system",
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct AdStateChangeRequest {
+	pub ad_id: Uuid,
+	pub to: AdState,
+	pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdStateChange {
+	pub ad_id: Uuid,
+	pub from: AdState,
+	pub to: AdState,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct AdStateTransitionResult {
+	pub changed: Vec<AdStateChange>,
+	/// Transitions not allowed from the ad's current state
+	pub rejected: Vec<AdStateChange>,
+	/// Ads that don't exist
+	pub missing: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AvitoAdStateHistory {
+	pub history_id: Uuid,
+	pub ad_id: Uuid,
+	pub from_status: Option<String>,
+	pub to_status: String,
+	pub source: String,
+	pub reason: Option<String>,
+	pub report_id: Option<i64>,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeAdsStatusSchema {
+	pub account_id: Uuid,
+	pub ad_ids: Vec<Uuid>,
+	pub status: AdState,
+	pub reason: Option<String>,
+}
+
+// Dates are replaced as given; null clears a date
+#[derive(Debug, Deserialize)]
+pub struct ScheduleAdsSchema {
+	pub account_id: Uuid,
+	pub ad_ids: Vec<Uuid>,
+	pub publish_at: Option<DateTime<Utc>>,
+	pub unpublish_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AvitoAdTemplate {
+	pub template_id: Uuid,
+	pub account_id: Uuid,
+	pub name: String,
+	/// Written into the Category tag of new ads unless the fields have one
+	pub category: Option<String>,
+	/// Avito tag -> value
+	pub fields: Json<HashMap<String, String>>,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+	#[serde(rename = "updatedTs")]
+	pub updated_ts: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdTemplatesQuery {
+	pub account_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAdTemplateSchema {
+	pub account_id: Uuid,
+	pub name: String,
+	pub category: Option<String>,
+	pub fields: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateAdTemplateSchema {
+	pub name: Option<String>,
+	pub category: Option<String>,
+	pub fields: Option<HashMap<String, serde_json::Value>>,
+}
+
+// New ad from a template or a copy of an existing ad.
+// Without feed_id the ad goes to the account's MANUAL_CREATE feed;
+// an empty or null override removes the tag.
+#[derive(Debug, Deserialize)]
+pub struct CreateAdFromSourceSchema {
+	pub account_id: Uuid,
+	pub feed_id: Option<Uuid>,
+	#[serde(default)]
+	pub overrides: HashMap<String, serde_json::Value>,
+	/// Create even when a near-duplicate ad exists
+	#[serde(default)]
+	pub force: bool,
+}
+
+// Variable rows for mass generation: every row is a map of placeholder name -> value
+#[derive(Debug, Deserialize)]
+pub struct GenerateAdVariantsSchema {
+	pub account_id: Uuid,
+	pub feed_id: Option<Uuid>,
+	pub rows: Vec<HashMap<String, serde_json::Value>>,
+	/// 0..1, defaults to DEFAULT_SIMILARITY_THRESHOLD
+	pub similarity_threshold: Option<f64>,
+	/// Same seed gives the same spintax choices
+	pub seed: Option<u64>,
+	/// Return the variants without creating ads
+	pub dry_run: Option<bool>,
+}
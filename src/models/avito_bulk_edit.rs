@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Which ads of the feed a bulk edit applies to; empty filter matches every ad
+#[derive(Debug, Default, Deserialize)]
+pub struct BulkEditFilter {
+	pub category: Option<String>,
+	/// Tag -> exact value
+	#[serde(default)]
+	pub tags: HashMap<String, String>,
+	pub price_min: Option<f64>,
+	pub price_max: Option<f64>,
+	pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BulkEditOperation {
+	Set {
+		tag: String,
+		value: String,
+	},
+	Delete {
+		tag: String,
+	},
+	/// Literal find/replace, in Title and Description unless tags are given
+	Replace {
+		find: String,
+		replace: String,
+		tags: Option<Vec<String>>,
+		#[serde(default)]
+		ignore_case: bool,
+	},
+	AppendDescription {
+		text: String,
+		separator: Option<String>,
+	},
+	/// Price is rounded to whole rubles, or to round_to (10, 100, ...) when given
+	MultiplyPrice {
+		factor: f64,
+		round_to: Option<i64>,
+	},
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkEditSchema {
+	pub account_id: Uuid,
+	#[serde(default)]
+	pub filter: BulkEditFilter,
+	pub operations: Vec<BulkEditOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldChange {
+	pub tag: String,
+	pub old: Option<String>,
+	pub new: Option<String>,
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct CrawlDiffQuery {
+	/// Smallest position change reported as a jump, 5 by default
+	pub position_jump: Option<i32>,
+}
+
+// An ad as found in one of the compared runs
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlDiffAd {
+	pub avito_ad_id: String,
+	pub title: String,
+	pub link: String,
+	pub seller_id: String,
+	pub seller_name: String,
+	pub position: i32,
+	pub price: String,
+	pub promotion: String,
+	/// One of our own ads
+	pub own: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlPriceChange {
+	pub ad: CrawlDiffAd,
+	pub old_price: String,
+	pub new_price: String,
+	pub change: Option<f64>,
+	pub change_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlPromotionChange {
+	pub ad: CrawlDiffAd,
+	pub old_promotion: String,
+	pub new_promotion: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlPositionChange {
+	pub ad: CrawlDiffAd,
+	pub old_position: i32,
+	pub new_position: i32,
+	/// Places gained; negative when the ad moved down
+	pub change: i32,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct CrawlDiff {
+	pub new_ads: Vec<CrawlDiffAd>,
+	pub removed_ads: Vec<CrawlDiffAd>,
+	pub price_changes: Vec<CrawlPriceChange>,
+	pub promotion_changes: Vec<CrawlPromotionChange>,
+	pub position_changes: Vec<CrawlPositionChange>,
+	pub unchanged: usize,
+}
@@ -0,0 +1,63 @@
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CrawlExportQuery {
+	/// csv (default), xlsx or ndjson
+	pub format: Option<String>,
+	/// Comma-separated column keys in output order, the classic 27 columns by default
+	pub columns: Option<String>,
+	/// Header language: ru (default) or en; ndjson always uses the column keys
+	pub lang: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+	Csv,
+	Xlsx,
+	Ndjson,
+}
+
+impl ExportFormat {
+	pub fn parse(value: &str) -> Option<Self> {
+		match value.trim().to_lowercase().as_str() {
+			"csv" => Some(ExportFormat::Csv),
+			"xlsx" => Some(ExportFormat::Xlsx),
+			"ndjson" | "jsonl" => Some(ExportFormat::Ndjson),
+			_ => None,
+		}
+	}
+
+	pub fn extension(&self) -> &'static str {
+		match self {
+			ExportFormat::Csv => "csv",
+			ExportFormat::Xlsx => "xlsx",
+			ExportFormat::Ndjson => "ndjson",
+		}
+	}
+
+	pub fn content_type(&self) -> &'static str {
+		match self {
+			ExportFormat::Csv => "text/csv",
+			ExportFormat::Xlsx => {
+				"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+			}
+			ExportFormat::Ndjson => "application/x-ndjson",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderLang {
+	Ru,
+	En,
+}
+
+impl HeaderLang {
+	pub fn parse(value: &str) -> Option<Self> {
+		match value.trim().to_lowercase().as_str() {
+			"ru" => Some(HeaderLang::Ru),
+			"en" => Some(HeaderLang::En),
+			_ => None,
+		}
+	}
+}
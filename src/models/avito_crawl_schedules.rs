@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Saved search crawled every `interval_hours`
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AvitoCrawlSchedule {
+	pub schedule_id: Uuid,
+	pub user_id: Uuid,
+	pub name: Option<String>,
+	pub request: String,
+	pub city: String,
+	pub coords: String,
+	pub radius: String,
+	pub district: String,
+	pub interval_hours: i32,
+	pub is_active: bool,
+	#[serde(rename = "nextRunTs")]
+	pub next_run_ts: DateTime<Utc>,
+	#[serde(rename = "lastRunTs")]
+	pub last_run_ts: Option<DateTime<Utc>>,
+	/// avito_requests row of the latest run
+	pub last_request_id: Option<Uuid>,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+	#[serde(rename = "updatedTs")]
+	pub updated_ts: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCrawlScheduleSchema {
+	pub name: Option<String>,
+	pub request: String,
+	#[serde(default)]
+	pub city: String,
+	#[serde(default)]
+	pub coords: String,
+	#[serde(default)]
+	pub radius: String,
+	#[serde(default)]
+	pub district: String,
+	/// 24 when not given
+	pub interval_hours: Option<i32>,
+	/// First run; now when not given
+	pub start_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCrawlScheduleSchema {
+	pub name: Option<String>,
+	pub request: Option<String>,
+	pub city: Option<String>,
+	pub coords: Option<String>,
+	pub radius: Option<String>,
+	pub district: Option<String>,
+	pub interval_hours: Option<i32>,
+	pub is_active: Option<bool>,
+	pub next_run_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utils::ad_duplicates::DuplicatePair;
+
+#[derive(Debug, Deserialize)]
+pub struct FindDuplicatesSchema {
+	pub account_id: Uuid,
+	/// All feeds of the account when empty
+	#[serde(default)]
+	pub feed_ids: Vec<Uuid>,
+	/// Minimum score from 0 to 1
+	pub threshold: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateAction {
+	/// Copy missing fields and images into the kept ad, then archive the rest
+	Merge,
+	/// Archive the other ads as they are
+	#[serde(alias = "delete")]
+	Archive,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveDuplicatesSchema {
+	pub account_id: Uuid,
+	pub keep_ad_id: Uuid,
+	pub ad_ids: Vec<Uuid>,
+	pub action: DuplicateAction,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DuplicateClusterAd {
+	pub ad_id: Uuid,
+	pub feed_id: Uuid,
+	pub parsed_id: Option<String>,
+	pub status: String,
+	pub title: Option<String>,
+	pub price: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateCluster {
+	pub ads: Vec<DuplicateClusterAd>,
+	/// Highest pair score in the cluster
+	pub score: f64,
+	pub pairs: Vec<DuplicatePair>,
+}
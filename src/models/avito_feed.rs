@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use sqlx::FromRow;
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -10,11 +10,14 @@ pub struct XmlAd {
 	pub fields: HashMap<String, String>,
 }
 
-// Query parameters for pagination
-#[derive(Debug, Deserialize)]
-pub struct FeedQueryParams {
-	pub page: Option<u32>,
-	pub limit: Option<u32>,
+// Ad from an XML feed with the lines where the <Ad> element and each of its tags start
+#[derive(Debug)]
+pub struct PositionedXmlAd {
+	pub ad: XmlAd,
+	pub line: usize,
+	pub field_lines: HashMap<String, usize>,
+	// One entry per URL in the Images field
+	pub image_lines: Vec<usize>,
 }
 
 // Response structures
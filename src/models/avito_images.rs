@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AvitoImage {
+	pub image_id: Uuid,
+	/// SHA-256 of the file content
+	pub content_hash: String,
+	pub format: String,
+	pub width: i32,
+	pub height: i32,
+	pub size_bytes: i64,
+	pub file_name: String,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AvitoAdImage {
+	pub position: i32,
+	pub source_url: String,
+	pub image_id: Option<Uuid>,
+	pub format: Option<String>,
+	pub width: Option<i32>,
+	pub height: Option<i32>,
+	pub size_bytes: Option<i64>,
+	pub file_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MirrorImagesSchema {
+	/// Replace URLs in the Images field with links to the mirrored copies
+	pub rewrite_urls: Option<bool>,
+}
+
+// Image URL that could not be mirrored
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageMirrorError {
+	pub url: String,
+	pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AvitoAdImageCheck {
+	pub position: i32,
+	pub url: String,
+	/// ok, warning or error
+	pub status: String,
+	pub issues: serde_json::Value,
+	pub format: Option<String>,
+	pub width: Option<i32>,
+	pub height: Option<i32>,
+	pub size_bytes: Option<i64>,
+	#[serde(rename = "checkedTs")]
+	pub checked_ts: Option<DateTime<Utc>>,
+}
+
+// Row written to avito_ad_image_checks
+#[derive(Debug, Clone)]
+pub struct NewAdImageCheck {
+	pub ad_id: Uuid,
+	pub position: i32,
+	pub url: String,
+	pub status: String,
+	pub issues: serde_json::Value,
+	pub format: Option<String>,
+	pub width: Option<i32>,
+	pub height: Option<i32>,
+	pub size_bytes: Option<i64>,
+	pub phash: Option<i64>,
+}
@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AvitoImportProfile {
+	pub profile_id: Uuid,
+	pub account_id: Uuid,
+	pub name: String,
+	/// Avito category written into the Category tag when the sheet has no such column
+	pub category: Option<String>,
+	/// Autoload category slug used to fetch mandatory tags from Avito
+	pub avito_slug: Option<String>,
+	/// Spreadsheet column header -> Avito tag
+	pub mapping: Json<HashMap<String, String>>,
+	pub required_tags: Vec<String>,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+	#[serde(rename = "updatedTs")]
+	pub updated_ts: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportProfilesQuery {
+	pub account_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateImportProfileSchema {
+	pub account_id: Uuid,
+	pub name: String,
+	pub category: Option<String>,
+	pub avito_slug: Option<String>,
+	pub mapping: HashMap<String, String>,
+	pub required_tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateImportProfileSchema {
+	pub name: Option<String>,
+	pub category: Option<String>,
+	pub avito_slug: Option<String>,
+	pub mapping: Option<HashMap<String, String>>,
+	pub required_tags: Option<Vec<String>>,
+}
+
+// A problem found in one row of an imported spreadsheet
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRowError {
+	pub line: usize,
+	pub ad_id: Option<String>,
+	pub tag: Option<String>,
+	pub message: String,
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct PositionsQuery {
+	/// Latest runs of the search to include, 10 by default
+	pub runs: Option<i64>,
+	/// Size of the top block whose share is reported, 10 by default
+	pub top: Option<i32>,
+}
+
+// One crawl of the tracked search with our standing in its results
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PositionRun {
+	pub request_id: Uuid,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+	pub status: String,
+	pub ads_total: i64,
+	/// Filled slots among the first `top` positions
+	pub top_slots: i64,
+	/// Of those, slots taken by our ads
+	pub own_top_slots: i64,
+	pub own_ads: i64,
+	/// own_top_slots / top_slots, None when the run found nothing
+	pub top_share: Option<f64>,
+}
+
+// Best position of one of our ads in one run
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OwnAdPosition {
+	pub request_id: Uuid,
+	pub avito_ad_id: String,
+	pub title: String,
+	pub link: String,
+	pub position: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionMovement {
+	Up,
+	Down,
+	Same,
+	/// Not found in the previous run
+	New,
+	/// Not found in the latest run
+	Dropped,
+	/// Not found in either of the last two runs
+	Absent,
+}
+
+// Position of one of our ads across the report runs, oldest run first
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnAdPositions {
+	pub avito_ad_id: String,
+	pub title: String,
+	pub link: String,
+	pub positions: Vec<Option<i32>>,
+	pub best_position: Option<i32>,
+	/// Places gained since the previous run; negative when the ad moved down
+	pub change: Option<i32>,
+	pub movement: PositionMovement,
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct PriceStatsQuery {
+	/// Number of distribution buckets, 10 by default
+	pub buckets: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PriceHistoryQuery {
+	/// Latest runs of the search to include, 10 by default
+	pub runs: Option<i64>,
+}
+
+// Price aggregates over the ads of one run; prices are None when no ad has one
+#[derive(Debug, Clone, Default, Serialize, sqlx::FromRow)]
+pub struct PriceStats {
+	pub ads_count: i64,
+	/// Ads with a positive price, the ones the aggregates are over
+	pub priced_count: i64,
+	pub min_price: Option<f64>,
+	pub p10: Option<f64>,
+	pub p25: Option<f64>,
+	pub median: Option<f64>,
+	pub p75: Option<f64>,
+	pub p90: Option<f64>,
+	pub mean_price: Option<f64>,
+	pub max_price: Option<f64>,
+}
+
+// Aggregates for one group of a breakdown, e.g. seller_type = "Компания"
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PriceStatsGroup {
+	/// seller_type, promotion or delivery; "all" for the whole run
+	#[serde(skip)]
+	pub dimension: String,
+	pub group: String,
+	#[sqlx(flatten)]
+	#[serde(flatten)]
+	pub stats: PriceStats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceBucket {
+	pub from_price: f64,
+	pub to_price: f64,
+	pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MarketPriceStats {
+	pub overall: PriceStats,
+	/// Equal-width buckets between the 5th and 95th percentile; the outer buckets
+	/// also hold the cheaper and pricier outliers
+	pub buckets: Vec<PriceBucket>,
+	pub by_seller_type: Vec<PriceStatsGroup>,
+	/// "promoted" and "regular"
+	pub by_promotion: Vec<PriceStatsGroup>,
+	/// "with_delivery" and "without_delivery"
+	pub by_delivery: Vec<PriceStatsGroup>,
+}
+
+// Overall price aggregates of one run of the search
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PriceStatsRun {
+	pub request_id: Uuid,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+	pub status: String,
+	#[sqlx(flatten)]
+	#[serde(flatten)]
+	pub stats: PriceStats,
+}
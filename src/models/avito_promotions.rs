@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct PromotionsQuery {
+	/// Latest runs of the search to include, 10 by default
+	pub runs: Option<i64>,
+	/// Size of the top block whose share is reported, 10 by default
+	pub top: Option<i32>,
+}
+
+// Promotion usage in one crawl of the search
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PromotionRun {
+	pub request_id: Uuid,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+	pub status: String,
+	pub ads_total: i64,
+	pub promoted_ads: i64,
+	pub promoted_share: Option<f64>,
+	/// Filled slots among the first `top` positions
+	pub top_slots: i64,
+	/// Of those, slots taken by promoted ads
+	pub promoted_top_slots: i64,
+	pub promoted_top_share: Option<f64>,
+	pub avg_position_promoted: Option<f64>,
+	pub avg_position_regular: Option<f64>,
+	/// Correlation of position and being promoted; negative when promoted ads rank higher
+	pub position_correlation: Option<f64>,
+	pub own_promoted_ads: i64,
+}
+
+// Where the ads with one kind of promotion land in a run; "none" for ads without one
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PromotionTypeStats {
+	pub promotion: String,
+	pub ads: i64,
+	pub best_position: i32,
+	pub avg_position: Option<f64>,
+	pub median_position: Option<f64>,
+	pub top_slots: i64,
+	/// Share of the filled top slots held by this kind of promotion
+	pub top_share: Option<f64>,
+	pub own_ads: i64,
+}
@@ -20,6 +20,70 @@ pub struct AvitoRequest {
 	pub created_ts: Option<DateTime<Utc>>,
 	#[serde(rename = "updatedTs")]
 	pub updated_ts: Option<DateTime<Utc>>,
+	/// Crawl job state: queued, running, done, failed or cancelled
+	pub status: String,
+	#[serde(rename = "startedTs")]
+	pub started_ts: Option<DateTime<Utc>>,
+	#[serde(rename = "finishedTs")]
+	pub finished_ts: Option<DateTime<Utc>>,
+	pub progress_current: Option<i32>,
+	pub progress_total: Option<i32>,
+	pub ads_count: Option<i32>,
+	pub last_error: Option<String>,
+	/// Saved search this run was started by
+	pub schedule_id: Option<uuid::Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrawlStatus {
+	Queued,
+	Running,
+	Done,
+	Failed,
+	Cancelled,
+}
+
+impl CrawlStatus {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			CrawlStatus::Queued => "queued",
+			CrawlStatus::Running => "running",
+			CrawlStatus::Done => "done",
+			CrawlStatus::Failed => "failed",
+			CrawlStatus::Cancelled => "cancelled",
+		}
+	}
+
+	pub fn parse(value: &str) -> Option<Self> {
+		match value {
+			"queued" => Some(CrawlStatus::Queued),
+			"running" => Some(CrawlStatus::Running),
+			"done" => Some(CrawlStatus::Done),
+			"failed" => Some(CrawlStatus::Failed),
+			"cancelled" => Some(CrawlStatus::Cancelled),
+			_ => None,
+		}
+	}
+
+	/// Finished jobs ignore further crawler messages
+	pub fn is_finished(&self) -> bool {
+		matches!(
+			self,
+			CrawlStatus::Done | CrawlStatus::Failed | CrawlStatus::Cancelled
+		)
+	}
+}
+
+// State and counters carried by one crawler progress message
+#[derive(Debug, Clone)]
+pub struct CrawlProgress {
+	pub request_id: uuid::Uuid,
+	pub status: CrawlStatus,
+	pub progress_current: Option<i32>,
+	pub progress_total: Option<i32>,
+	pub ads_count: Option<i32>,
+	pub error: Option<String>,
 }
 
 #[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
@@ -42,6 +106,10 @@ pub struct FilteredAvitoRequest {
 	pub district: Option<String>,
 	#[serde(rename = "createdTs")]
 	pub created_ts: Option<DateTime<Utc>>,
+	pub status: String,
+	#[serde(rename = "finishedTs")]
+	pub finished_ts: Option<DateTime<Utc>>,
+	pub ads_count: Option<i32>,
 }
 
 #[derive(sqlx::FromRow, Debug, Deserialize, Serialize, Clone)]
@@ -76,4 +144,14 @@ pub struct AdRecord {
 	pub description: String,
 	pub avito_request_id: uuid::Uuid,
 	pub created_ts: Option<DateTime<Utc>>,
+	// Typed values parsed from the strings above, None when the text has no number or date
+	pub views_value: Option<i32>,
+	pub views_today_value: Option<i32>,
+	pub price_value: Option<f64>,
+	pub rating_value: Option<f64>,
+	pub reviews_count_value: Option<i32>,
+	pub ads_count_value: Option<i32>,
+	pub photo_count_value: Option<i32>,
+	/// Publication time parsed from ad_date, in UTC
+	pub ad_ts: Option<DateTime<Utc>>,
 }
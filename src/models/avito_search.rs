@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdSearchSort {
+	/// Full-text rank; newest first when there is no query
+	#[default]
+	Relevance,
+	CreatedDesc,
+	PriceAsc,
+	PriceDesc,
+	Title,
+}
+
+// Filters shared by the result list, the total and the facet counts
+#[derive(Debug, Default, Deserialize)]
+pub struct AdSearchFilters {
+	pub account_id: Option<Uuid>,
+	pub feed_ids: Option<Vec<Uuid>>,
+	pub statuses: Option<Vec<String>>,
+	/// Web search syntax: words, "phrases", -excluded, or
+	pub q: Option<String>,
+	/// Tag -> accepted values
+	#[serde(default)]
+	pub tags: HashMap<String, Vec<String>>,
+	pub price_min: Option<f64>,
+	pub price_max: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdSearchSchema {
+	#[serde(flatten)]
+	pub filters: AdSearchFilters,
+	/// Tag names, or "status", "feed_id", "account_id"
+	#[serde(default)]
+	pub facets: Vec<String>,
+	#[serde(default)]
+	pub sort: AdSearchSort,
+	pub limit: Option<i64>,
+	pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AdSearchHit {
+	pub ad_id: Uuid,
+	pub feed_id: Uuid,
+	pub account_id: Uuid,
+	pub parsed_id: Option<String>,
+	pub avito_ad_id: Option<String>,
+	pub status: Option<String>,
+	pub category: Option<String>,
+	pub title: Option<String>,
+	pub price: Option<f64>,
+	pub snippet: Option<String>,
+	pub rank: Option<f32>,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AdSearchFacetCount {
+	pub value: String,
+	pub count: i64,
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct CompetitorsQuery {
+	/// Number of sellers to return, 20 by default
+	pub limit: Option<i64>,
+	/// Latest runs of the search the rating trend covers, 10 by default
+	pub runs: Option<i64>,
+}
+
+// A competing seller as seen in one crawl run
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Competitor {
+	pub seller_id: String,
+	pub seller_name: String,
+	pub seller_type: String,
+	pub register_date: String,
+	/// Ads of the seller in the run's results
+	pub ads_in_run: i32,
+	pub promoted_ads: i32,
+	pub promotion_share: Option<f64>,
+	pub avg_price: Option<f64>,
+	pub best_position: i32,
+	pub rating: Option<f64>,
+	pub reviews_count: Option<i32>,
+	/// Active and closed ads of the whole seller profile
+	pub ads_count: Option<i32>,
+	pub closed_ads_count: Option<i32>,
+	#[serde(rename = "firstSeenTs")]
+	pub first_seen_ts: DateTime<Utc>,
+}
+
+// The seller's profile and presence in one run of the search
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SellerSnapshot {
+	#[serde(skip)]
+	pub seller_id: String,
+	pub request_id: Uuid,
+	#[serde(rename = "createdTs")]
+	pub created_ts: Option<DateTime<Utc>>,
+	pub rating: Option<f64>,
+	pub reviews_count: Option<i32>,
+	pub ads_in_run: i32,
+	pub avg_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompetitorProfile {
+	#[serde(flatten)]
+	pub seller: Competitor,
+	/// Rating change between the oldest and the latest run with a rating
+	pub rating_change: Option<f64>,
+	/// Oldest run first
+	pub trend: Vec<SellerSnapshot>,
+}
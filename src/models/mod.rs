@@ -1,17 +1,45 @@
 pub mod avito_accounts;
+pub mod avito_ad_lifecycle;
+pub mod avito_ad_templates;
+pub mod avito_bulk_edit;
 pub mod avito_client;
+pub mod avito_crawl_diff;
+pub mod avito_crawl_export;
+pub mod avito_crawl_schedules;
+pub mod avito_duplicates;
 pub mod avito_feed;
+pub mod avito_images;
+pub mod avito_import_profiles;
+pub mod avito_positions;
+pub mod avito_price_stats;
+pub mod avito_promotions;
 pub mod avito_reports;
 pub mod avito_requests;
+pub mod avito_search;
+pub mod avito_sellers;
 pub mod response;
 pub mod shared;
 pub mod users;
 
 pub use self::avito_accounts::*;
+pub use self::avito_ad_lifecycle::*;
+pub use self::avito_ad_templates::*;
+pub use self::avito_bulk_edit::*;
 pub use self::avito_client::*;
+pub use self::avito_crawl_diff::*;
+pub use self::avito_crawl_export::*;
+pub use self::avito_crawl_schedules::*;
+pub use self::avito_duplicates::*;
 pub use self::avito_feed::*;
+pub use self::avito_images::*;
+pub use self::avito_import_profiles::*;
+pub use self::avito_positions::*;
+pub use self::avito_price_stats::*;
+pub use self::avito_promotions::*;
 pub use self::avito_reports::*;
 pub use self::avito_requests::*;
+pub use self::avito_search::*;
+pub use self::avito_sellers::*;
 pub use self::response::*;
 pub use self::shared::*;
 pub use self::users::*;
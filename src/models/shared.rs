@@ -1,15 +1,28 @@
-use serde::Deserialize;
-use sqlx::FromRow;
+use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug)]
 pub struct FilterOptions {
+	/// Offset paging from page 1, used only when no cursor is given
 	pub page: Option<usize>,
 	pub limit: Option<usize>,
+	/// next_cursor of the previous page
+	pub cursor: Option<String>,
+	/// One of the listing's sort keys
+	pub sort: Option<String>,
+	/// "asc" or "desc"
+	pub order: Option<String>,
+	/// Count all matching rows; on by default unless a cursor is given
+	pub with_total: Option<bool>,
 }
 
-#[derive(Deserialize, Debug, FromRow)]
-pub struct Count {
-	pub count: Option<i64>,
+// Position after the last row of a page, for the sort it was taken with
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PageCursor {
+	pub sort: String,
+	pub descending: bool,
+	/// Sort key and row id as Postgres text, cast back when binding
+	pub key: String,
+	pub id: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,543 @@
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use uuid::Uuid;
+
+use crate::utils::bulk_edit::parse_price;
+use crate::utils::image_quality::{hash_distance, DUPLICATE_HASH_DISTANCE};
+use crate::utils::images::split_image_urls;
+
+// Weighted score at or above which two ads count as duplicates
+pub const DEFAULT_DUPLICATE_THRESHOLD: f64 = 0.85;
+
+// Words per description shingle
+const SHINGLE_SIZE: usize = 3;
+
+// Score weights; components missing on either ad are left out and the rest rescaled
+const TITLE_WEIGHT: f64 = 0.3;
+const DESCRIPTION_WEIGHT: f64 = 0.35;
+const PRICE_WEIGHT: f64 = 0.1;
+const IMAGES_WEIGHT: f64 = 0.25;
+
+// MinHash LSH: ads sharing all rows of any band are compared in full
+const LSH_BANDS: usize = 8;
+const LSH_ROWS: usize = 3;
+
+// Comparable features of one ad
+#[derive(Debug, Clone)]
+pub struct DuplicateCandidate {
+	pub ad_id: Uuid,
+	title_words: HashSet<String>,
+	shingles: HashSet<u64>,
+	price: Option<f64>,
+	image_urls: HashSet<String>,
+	image_hashes: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateScore {
+	pub score: f64,
+	pub title: Option<f64>,
+	pub description: Option<f64>,
+	pub price: Option<f64>,
+	pub images: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicatePair {
+	pub ad_id: Uuid,
+	pub duplicate_ad_id: Uuid,
+	#[serde(flatten)]
+	pub score: DuplicateScore,
+}
+
+impl DuplicateCandidate {
+	// `image_hashes` are the perceptual hashes from image checks, if any
+	pub fn new(ad_id: Uuid, fields: &HashMap<String, String>, image_hashes: &[i64]) -> Self {
+		let title = fields
+			.get("Title")
+			.map(|t| normalize_text(t))
+			.unwrap_or_default();
+		let description = fields
+			.get("Description")
+			.map(|d| normalize_text(&strip_html(d)))
+			.unwrap_or_default();
+
+		Self {
+			ad_id,
+			title_words: title.split_whitespace().map(str::to_string).collect(),
+			shingles: shingles(&description),
+			price: fields.get("Price").and_then(|price| parse_price(price)),
+			image_urls: fields
+				.get("Images")
+				.map(|images| split_image_urls(images).into_iter().collect())
+				.unwrap_or_default(),
+			image_hashes: image_hashes.iter().map(|hash| *hash as u64).collect(),
+		}
+	}
+
+	// Title words, for looking up likely matches in the search index
+	pub fn title_words(&self) -> Vec<String> {
+		let mut words: Vec<String> = self.title_words.iter().cloned().collect();
+		words.sort();
+		words
+	}
+
+	fn has_text(&self) -> bool {
+		!self.title_words.is_empty() || !self.shingles.is_empty()
+	}
+}
+
+// Lowercase words only, ё folded into е and numbers split from units ("128гб" -> "128 гб")
+pub fn normalize_text(text: &str) -> String {
+	let mut result = String::with_capacity(text.len());
+	let mut previous: Option<char> = None;
+	for c in text.to_lowercase().replace('ё', "е").chars() {
+		if !c.is_alphanumeric() {
+			result.push(' ');
+			previous = None;
+			continue;
+		}
+		if let Some(previous) = previous {
+			if previous.is_numeric() != c.is_numeric() {
+				result.push(' ');
+			}
+		}
+		result.push(c);
+		previous = Some(c);
+	}
+	result.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn strip_html(text: &str) -> String {
+	let mut result = String::with_capacity(text.len());
+	let mut in_tag = false;
+	for c in text.chars() {
+		match c {
+			'<' => in_tag = true,
+			'>' if in_tag => {
+				in_tag = false;
+				result.push(' ');
+			}
+			_ if !in_tag => result.push(c),
+			_ => {}
+		}
+	}
+	result
+}
+
+fn hash_str(value: &str) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	value.hash(&mut hasher);
+	hasher.finish()
+}
+
+// Hashed runs of SHINGLE_SIZE words; shorter texts give a single shingle
+pub fn shingles(normalized: &str) -> HashSet<u64> {
+	let words: Vec<&str> = normalized.split_whitespace().collect();
+	if words.is_empty() {
+		return HashSet::new();
+	}
+	if words.len() < SHINGLE_SIZE {
+		return std::iter::once(hash_str(&words.join(" "))).collect();
+	}
+	words
+		.windows(SHINGLE_SIZE)
+		.map(|window| hash_str(&window.join(" ")))
+		.collect()
+}
+
+fn jaccard<T: Eq + Hash>(a: &HashSet<T>, b: &HashSet<T>) -> Option<f64> {
+	if a.is_empty() || b.is_empty() {
+		return None;
+	}
+	let shared = a.intersection(b).count();
+	Some(shared as f64 / (a.len() + b.len() - shared) as f64)
+}
+
+fn price_similarity(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+	match (a, b) {
+		(Some(a), Some(b)) if a > 0.0 && b > 0.0 => Some(1.0 - (a - b).abs() / a.max(b)),
+		(Some(a), Some(b)) if a == b => Some(1.0),
+		_ => None,
+	}
+}
+
+// Share of the smaller image set that also appears in the other ad, by URL or near-equal hash
+fn image_similarity(a: &DuplicateCandidate, b: &DuplicateCandidate) -> Option<f64> {
+	let by_url = if a.image_urls.is_empty() || b.image_urls.is_empty() {
+		None
+	} else {
+		let shared = a.image_urls.intersection(&b.image_urls).count();
+		Some(shared as f64 / a.image_urls.len().min(b.image_urls.len()) as f64)
+	};
+	let by_hash = if a.image_hashes.is_empty() || b.image_hashes.is_empty() {
+		None
+	} else {
+		let (small, large) = if a.image_hashes.len() <= b.image_hashes.len() {
+			(&a.image_hashes, &b.image_hashes)
+		} else {
+			(&b.image_hashes, &a.image_hashes)
+		};
+		let shared = small
+			.iter()
+			.filter(|hash| {
+				large
+					.iter()
+					.any(|other| hash_distance(**hash, *other) <= DUPLICATE_HASH_DISTANCE)
+			})
+			.count();
+		Some(shared as f64 / small.len() as f64)
+	};
+	match (by_url, by_hash) {
+		(Some(url), Some(hash)) => Some(url.max(hash)),
+		(url, hash) => url.or(hash),
+	}
+}
+
+pub fn similarity(a: &DuplicateCandidate, b: &DuplicateCandidate) -> DuplicateScore {
+	let title = jaccard(&a.title_words, &b.title_words);
+	let description = jaccard(&a.shingles, &b.shingles);
+	let price = price_similarity(a.price, b.price);
+	let images = image_similarity(a, b);
+
+	// Price and photos alone don't make a duplicate
+	let score = if title.is_none() && description.is_none() {
+		0.0
+	} else {
+		let components = [
+			(title, TITLE_WEIGHT),
+			(description, DESCRIPTION_WEIGHT),
+			(price, PRICE_WEIGHT),
+			(images, IMAGES_WEIGHT),
+		];
+		let (total, weights) = components
+			.iter()
+			.filter_map(|(value, weight)| value.map(|value| (value * weight, *weight)))
+			.fold((0.0, 0.0), |(total, weights), (value, weight)| {
+				(total + value, weights + weight)
+			});
+		total / weights
+	};
+
+	DuplicateScore {
+		score: (score * 1000.0).round() / 1000.0,
+		title,
+		description,
+		price,
+		images,
+	}
+}
+
+fn minhash_signature(features: &HashSet<u64>) -> Vec<u64> {
+	(0..LSH_BANDS * LSH_ROWS)
+		.map(|i| {
+			let seed = (i as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+			features
+				.iter()
+				.map(|feature| {
+					let mut z = feature ^ seed;
+					z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+					z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+					z ^ (z >> 31)
+				})
+				.min()
+				.unwrap_or(u64::MAX)
+		})
+		.collect()
+}
+
+// Index pairs worth scoring: same LSH bucket over title words and shingles, or a shared image
+fn candidate_pairs(candidates: &[DuplicateCandidate]) -> HashSet<(usize, usize)> {
+	let mut buckets: HashMap<(usize, Vec<u64>), Vec<usize>> = HashMap::new();
+	let mut image_buckets: HashMap<String, Vec<usize>> = HashMap::new();
+
+	for (index, candidate) in candidates.iter().enumerate() {
+		if !candidate.has_text() {
+			continue;
+		}
+		let mut features: HashSet<u64> = candidate
+			.title_words
+			.iter()
+			.map(|word| hash_str(&format!("t:{}", word)))
+			.collect();
+		features.extend(candidate.shingles.iter().copied());
+
+		let signature = minhash_signature(&features);
+		for band in 0..LSH_BANDS {
+			let rows = signature[band * LSH_ROWS..(band + 1) * LSH_ROWS].to_vec();
+			buckets.entry((band, rows)).or_default().push(index);
+		}
+		for url in &candidate.image_urls {
+			image_buckets.entry(url.clone()).or_default().push(index);
+		}
+		// Only exact hash matches are bucketed; near matches still count once compared
+		for hash in &candidate.image_hashes {
+			image_buckets
+				.entry(format!("h:{:016x}", hash))
+				.or_default()
+				.push(index);
+		}
+	}
+
+	let mut pairs = HashSet::new();
+	for members in buckets.values().chain(image_buckets.values()) {
+		for (i, a) in members.iter().enumerate() {
+			for b in &members[i + 1..] {
+				if a != b {
+					pairs.insert(((*a).min(*b), (*a).max(*b)));
+				}
+			}
+		}
+	}
+	pairs
+}
+
+// All pairs scoring at or above `threshold`, best first
+pub fn find_duplicate_pairs(
+	candidates: &[DuplicateCandidate],
+	threshold: f64,
+) -> Vec<DuplicatePair> {
+	let mut pairs: Vec<DuplicatePair> = candidate_pairs(candidates)
+		.into_iter()
+		.filter_map(|(a, b)| {
+			let score = similarity(&candidates[a], &candidates[b]);
+			(score.score >= threshold).then_some(DuplicatePair {
+				ad_id: candidates[a].ad_id,
+				duplicate_ad_id: candidates[b].ad_id,
+				score,
+			})
+		})
+		.collect();
+	pairs.sort_by(|a, b| b.score.score.total_cmp(&a.score.score));
+	pairs
+}
+
+// Ads of `candidates` too close to `ad`, best first
+pub fn find_matches(
+	ad: &DuplicateCandidate,
+	candidates: &[DuplicateCandidate],
+	threshold: f64,
+) -> Vec<DuplicatePair> {
+	let mut matches: Vec<DuplicatePair> = candidates
+		.iter()
+		.filter(|candidate| candidate.ad_id != ad.ad_id)
+		.filter_map(|candidate| {
+			let score = similarity(ad, candidate);
+			(score.score >= threshold).then_some(DuplicatePair {
+				ad_id: ad.ad_id,
+				duplicate_ad_id: candidate.ad_id,
+				score,
+			})
+		})
+		.collect();
+	matches.sort_by(|a, b| b.score.score.total_cmp(&a.score.score));
+	matches
+}
+
+// Connected groups of ads linked by duplicate pairs, largest first
+pub fn cluster_pairs(pairs: &[DuplicatePair]) -> Vec<Vec<Uuid>> {
+	let mut parent: HashMap<Uuid, Uuid> = HashMap::new();
+
+	fn find(parent: &mut HashMap<Uuid, Uuid>, id: Uuid) -> Uuid {
+		let next = *parent.entry(id).or_insert(id);
+		if next == id {
+			return id;
+		}
+		let root = find(parent, next);
+		parent.insert(id, root);
+		root
+	}
+
+	for pair in pairs {
+		let a = find(&mut parent, pair.ad_id);
+		let b = find(&mut parent, pair.duplicate_ad_id);
+		if a != b {
+			parent.insert(a.max(b), a.min(b));
+		}
+	}
+
+	let ids: Vec<Uuid> = parent.keys().copied().collect();
+	let mut clusters: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+	for id in ids {
+		let root = find(&mut parent, id);
+		clusters.entry(root).or_default().push(id);
+	}
+
+	let mut clusters: Vec<Vec<Uuid>> = clusters
+		.into_values()
+		.map(|mut members| {
+			members.sort();
+			members
+		})
+		.collect();
+	clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+	clusters
+}
+
+// Fields of the kept ad completed with tags only the duplicates have; images are combined
+pub fn merge_duplicate_fields(
+	keep: &HashMap<String, String>,
+	duplicates: &[HashMap<String, String>],
+) -> HashMap<String, String> {
+	let mut merged = keep.clone();
+	for fields in duplicates {
+		for (tag, value) in fields {
+			if tag == "Id" || tag == "Images" || value.trim().is_empty() {
+				continue;
+			}
+			let missing = merged
+				.get(tag)
+				.map(|current| current.trim().is_empty())
+				.unwrap_or(true);
+			if missing {
+				merged.insert(tag.clone(), value.clone());
+			}
+		}
+	}
+
+	let mut images: Vec<String> = Vec::new();
+	for fields in std::iter::once(keep).chain(duplicates.iter()) {
+		if let Some(value) = fields.get("Images") {
+			for url in split_image_urls(value) {
+				if !images.contains(&url) {
+					images.push(url);
+				}
+			}
+		}
+	}
+	if !images.is_empty() {
+		merged.insert("Images".to_string(), images.join(", "));
+	}
+	merged
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+		pairs
+			.iter()
+			.map(|(k, v)| (k.to_string(), v.to_string()))
+			.collect()
+	}
+
+	fn candidate(id: u128, pairs: &[(&str, &str)]) -> DuplicateCandidate {
+		DuplicateCandidate::new(Uuid::from_u128(id), &fields(pairs), &[])
+	}
+
+	fn pair(a: u128, b: u128) -> DuplicatePair {
+		DuplicatePair {
+			ad_id: Uuid::from_u128(a),
+			duplicate_ad_id: Uuid::from_u128(b),
+			score: DuplicateScore {
+				score: 1.0,
+				title: None,
+				description: None,
+				price: None,
+				images: None,
+			},
+		}
+	}
+
+	#[test]
+	fn text_is_normalized() {
+		assert_eq!(
+			normalize_text("iPhone 13, 128Гб — ёлка!"),
+			"iphone 13 128 гб елка"
+		);
+	}
+
+	#[test]
+	fn copies_with_reworded_markup_are_duplicates() {
+		let a = candidate(
+			1,
+			&[
+				("Title", "Диван угловой Лофт"),
+				(
+					"Description",
+					"<p>Новый угловой диван, доставка по городу</p>",
+				),
+				("Price", "25 000"),
+				("Images", "https://img/1.jpg, https://img/2.jpg"),
+			],
+		);
+		let b = candidate(
+			2,
+			&[
+				("Title", "диван УГЛОВОЙ лофт"),
+				("Description", "Новый угловой диван, доставка по городу"),
+				("Price", "24000"),
+				("Images", "https://img/2.jpg"),
+			],
+		);
+
+		let score = similarity(&a, &b);
+		assert_eq!(score.title, Some(1.0));
+		assert_eq!(score.description, Some(1.0));
+		assert_eq!(score.images, Some(1.0));
+		assert!(score.score >= DEFAULT_DUPLICATE_THRESHOLD);
+		assert_eq!(
+			find_matches(&a, &[a.clone(), b.clone()], DEFAULT_DUPLICATE_THRESHOLD).len(),
+			1
+		);
+	}
+
+	#[test]
+	fn price_and_photos_alone_are_not_duplicates() {
+		let a = candidate(1, &[("Price", "1000"), ("Images", "https://img/1.jpg")]);
+		let b = candidate(2, &[("Price", "1000"), ("Images", "https://img/1.jpg")]);
+
+		let score = similarity(&a, &b);
+		assert_eq!(score.price, Some(1.0));
+		assert_eq!(score.score, 0.0);
+	}
+
+	#[test]
+	fn different_ads_score_low() {
+		let a = candidate(1, &[("Title", "Диван угловой"), ("Price", "25000")]);
+		let b = candidate(2, &[("Title", "Велосипед горный"), ("Price", "9000")]);
+		assert!(similarity(&a, &b).score < DEFAULT_DUPLICATE_THRESHOLD);
+	}
+
+	#[test]
+	fn pairs_are_clustered_transitively() {
+		let clusters = cluster_pairs(&[pair(3, 2), pair(1, 2), pair(5, 4)]);
+		assert_eq!(
+			clusters,
+			vec![
+				vec![Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3)],
+				vec![Uuid::from_u128(4), Uuid::from_u128(5)],
+			]
+		);
+		assert!(cluster_pairs(&[]).is_empty());
+	}
+
+	#[test]
+	fn merge_fills_missing_tags_and_combines_images() {
+		let keep = fields(&[
+			("Id", "keep"),
+			("Title", "Диван"),
+			("Color", " "),
+			("Images", "https://img/1.jpg"),
+		]);
+		let duplicates = vec![
+			fields(&[
+				("Id", "dup"),
+				("Title", "Другой диван"),
+				("Color", "Серый"),
+				("Images", "https://img/1.jpg, https://img/2.jpg"),
+			]),
+			fields(&[("Material", "Велюр"), ("Color", "Синий")]),
+		];
+
+		let merged = merge_duplicate_fields(&keep, &duplicates);
+		assert_eq!(merged["Id"], "keep");
+		assert_eq!(merged["Title"], "Диван");
+		assert_eq!(merged["Color"], "Серый");
+		assert_eq!(merged["Material"], "Велюр");
+		assert_eq!(merged["Images"], "https://img/1.jpg, https://img/2.jpg");
+	}
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+// Tag value as stored in avito_ad_field_values: arrays become comma-separated lists,
+// objects are kept as JSON and null becomes an empty value
+pub fn field_value_to_string(value: &serde_json::Value) -> String {
+	match value {
+		serde_json::Value::String(s) => s.clone(),
+		serde_json::Value::Number(n) => n.to_string(),
+		serde_json::Value::Bool(b) => b.to_string(),
+		serde_json::Value::Array(arr) => arr
+			.iter()
+			.map(|v| match v {
+				serde_json::Value::String(s) => s.clone(),
+				_ => serde_json::to_string(v).unwrap_or_default(),
+			})
+			.collect::<Vec<_>>()
+			.join(","),
+		serde_json::Value::Object(_) => serde_json::to_string(value).unwrap_or_default(),
+		serde_json::Value::Null => String::new(),
+	}
+}
+
+pub fn fields_from_json(fields: &HashMap<String, serde_json::Value>) -> HashMap<String, String> {
+	fields
+		.iter()
+		.map(|(tag, value)| (tag.clone(), field_value_to_string(value)))
+		.filter(|(_, value)| !value.trim().is_empty())
+		.collect()
+}
+
+// Overrides replace tag values; an empty value removes the tag
+pub fn apply_field_overrides(
+	fields: &mut HashMap<String, String>,
+	overrides: &HashMap<String, serde_json::Value>,
+) {
+	for (tag, value) in overrides {
+		let value = field_value_to_string(value);
+		if value.trim().is_empty() {
+			fields.remove(tag);
+		} else {
+			fields.insert(tag.clone(), value);
+		}
+	}
+}
@@ -0,0 +1,154 @@
+use crate::models::{AdState, TransitionSource};
+
+// Whether `source` may move an ad from `from` to `to`
+pub fn is_transition_allowed(from: AdState, to: AdState, source: TransitionSource) -> bool {
+	use AdState::*;
+
+	if from == to {
+		return false;
+	}
+	// The scheduler publishes drafts and expires ads whose window has ended
+	if source == TransitionSource::System
+		&& matches!(
+			(from, to),
+			(Draft, Published)
+				| (Expired, Published)
+				| (Draft, Expired)
+				| (Ready, Expired)
+				| (Published, Expired)
+		) {
+		return true;
+	}
+	match source {
+		TransitionSource::User | TransitionSource::System => matches!(
+			(from, to),
+			(Draft, Ready)
+				| (Draft, Archived)
+				| (Ready, Draft)
+				| (Ready, Published)
+				| (Ready, Archived)
+				| (Published, Archived)
+				| (Rejected, Draft)
+				| (Rejected, Ready)
+				| (Rejected, Archived)
+				| (Blocked, Draft)
+				| (Blocked, Archived)
+				| (Expired, Ready)
+				| (Expired, Archived)
+				| (Archived, Draft)
+		),
+		// Autoload reports what Avito shows, so it may move any live ad to any
+		// moderation outcome, but it never revives an ad the user archived
+		TransitionSource::Autoload => {
+			from != Archived && matches!(to, Published | Rejected | Blocked | Expired | Archived)
+		}
+	}
+}
+
+// Lifecycle state for an autoload report `avito_status`
+pub fn state_for_avito_status(avito_status: &str) -> Option<AdState> {
+	match avito_status {
+		"active" => Some(AdState::Published),
+		"old" | "expired" => Some(AdState::Expired),
+		"blocked" => Some(AdState::Blocked),
+		"rejected" => Some(AdState::Rejected),
+		"archived" | "removed" => Some(AdState::Archived),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use AdState::*;
+
+	#[test]
+	fn report_statuses_map_to_states() {
+		assert_eq!(state_for_avito_status("active"), Some(Published));
+		assert_eq!(state_for_avito_status("old"), Some(Expired));
+		assert_eq!(state_for_avito_status("expired"), Some(Expired));
+		assert_eq!(state_for_avito_status("blocked"), Some(Blocked));
+		assert_eq!(state_for_avito_status("rejected"), Some(Rejected));
+		assert_eq!(state_for_avito_status("removed"), Some(Archived));
+		assert_eq!(state_for_avito_status("unknown"), None);
+	}
+
+	#[test]
+	fn users_follow_the_lifecycle() {
+		assert!(is_transition_allowed(Draft, Ready, TransitionSource::User));
+		assert!(is_transition_allowed(
+			Rejected,
+			Draft,
+			TransitionSource::User
+		));
+		assert!(is_transition_allowed(
+			Archived,
+			Draft,
+			TransitionSource::User
+		));
+		assert!(!is_transition_allowed(
+			Draft,
+			Published,
+			TransitionSource::User
+		));
+		assert!(!is_transition_allowed(
+			Published,
+			Draft,
+			TransitionSource::User
+		));
+		assert!(!is_transition_allowed(Ready, Ready, TransitionSource::User));
+	}
+
+	#[test]
+	fn scheduler_publishes_and_expires() {
+		assert!(is_transition_allowed(
+			Draft,
+			Published,
+			TransitionSource::System
+		));
+		assert!(is_transition_allowed(
+			Expired,
+			Published,
+			TransitionSource::System
+		));
+		assert!(is_transition_allowed(
+			Published,
+			Expired,
+			TransitionSource::System
+		));
+		assert!(!is_transition_allowed(
+			Archived,
+			Published,
+			TransitionSource::System
+		));
+	}
+
+	#[test]
+	fn autoload_never_revives_archived_ads() {
+		assert!(is_transition_allowed(
+			Draft,
+			Published,
+			TransitionSource::Autoload
+		));
+		assert!(is_transition_allowed(
+			Published,
+			Blocked,
+			TransitionSource::Autoload
+		));
+		assert!(is_transition_allowed(
+			Rejected,
+			Archived,
+			TransitionSource::Autoload
+		));
+		assert!(!is_transition_allowed(
+			Archived,
+			Published,
+			TransitionSource::Autoload
+		));
+		assert!(!is_transition_allowed(
+			Published,
+			Ready,
+			TransitionSource::Autoload
+		));
+	}
+}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+
+// Autoload tags Avito uses to start and stop showing an ad
+pub const DATE_BEGIN_TAG: &str = "DateBegin";
+pub const DATE_END_TAG: &str = "DateEnd";
+
+// Avito reads autoload dates in Moscow time
+const MOSCOW_OFFSET_SECS: i32 = 3 * 3600;
+
+// "2025-11-18T09:00:00+03:00"
+pub fn avito_date(date: &DateTime<Utc>) -> String {
+	let moscow = FixedOffset::east_opt(MOSCOW_OFFSET_SECS).expect("valid offset");
+	date.with_timezone(&moscow)
+		.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+pub fn validate_schedule(
+	publish_at: Option<DateTime<Utc>>,
+	unpublish_at: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+	if let (Some(publish_at), Some(unpublish_at)) = (publish_at, unpublish_at) {
+		if unpublish_at <= publish_at {
+			return Err("unpublish_at must be later than publish_at".to_string());
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+
+	#[test]
+	fn dates_are_written_in_moscow_time() {
+		let date = Utc.with_ymd_and_hms(2025, 11, 18, 6, 0, 0).unwrap();
+		assert_eq!(avito_date(&date), "2025-11-18T09:00:00+03:00");
+
+		let late = Utc.with_ymd_and_hms(2025, 12, 31, 22, 30, 15).unwrap();
+		assert_eq!(avito_date(&late), "2026-01-01T01:30:15+03:00");
+	}
+
+	#[test]
+	fn unpublish_must_follow_publish() {
+		let publish_at = Utc.with_ymd_and_hms(2025, 11, 18, 6, 0, 0).unwrap();
+		assert!(validate_schedule(Some(publish_at), None).is_ok());
+		assert!(validate_schedule(Some(publish_at), Some(publish_at)).is_err());
+		assert!(validate_schedule(
+			Some(publish_at),
+			Some(publish_at + chrono::Duration::hours(1))
+		)
+		.is_ok());
+	}
+}
@@ -0,0 +1,266 @@
+use crate::models::ImportRowError;
+use std::collections::{HashMap, HashSet};
+
+// Variants at least this similar (word-pair Jaccard) to an earlier one are rejected
+pub const DEFAULT_SIMILARITY_THRESHOLD: f64 = 0.8;
+// Tags where {a|b|c} spintax is expanded; placeholders work in every tag
+pub const SPINTAX_TAGS: [&str; 2] = ["Title", "Description"];
+
+// Small deterministic generator so the same seed gives the same variants
+pub struct SpinRng(u64);
+
+impl SpinRng {
+	pub fn new(seed: u64) -> Self {
+		SpinRng(seed)
+	}
+
+	// splitmix64
+	pub fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+
+	pub fn pick(&mut self, len: usize) -> usize {
+		(self.next_u64() % len.max(1) as u64) as usize
+	}
+}
+
+// Replace {name} with the row variable of that name. Anything else in braces
+// (spintax, JSON snippets, names that are not row variables) is kept as written.
+pub fn expand_placeholders(text: &str, vars: &HashMap<String, String>) -> String {
+	let mut result = String::with_capacity(text.len());
+	let mut rest = text;
+
+	while let Some(start) = rest.find('{') {
+		result.push_str(&rest[..start]);
+		let after = &rest[start + 1..];
+		let value = match after.find(['{', '}']) {
+			Some(end) if after[end..].starts_with('}') => {
+				let key = after[..end].trim();
+				vars.get(key)
+					.or_else(|| {
+						vars.iter()
+							.find(|(k, _)| k.eq_ignore_ascii_case(key))
+							.map(|(_, v)| v)
+					})
+					.map(|value| (value, end))
+			}
+			_ => None,
+		};
+
+		match value {
+			Some((value, end)) => {
+				result.push_str(value);
+				rest = &after[end + 1..];
+			}
+			None => {
+				result.push('{');
+				rest = after;
+			}
+		}
+	}
+	result.push_str(rest);
+
+	result
+}
+
+// Resolve {a|b|c} choices, innermost first, so {a|{b|c}} works. Braces without a
+// choice inside and unmatched braces are kept as written.
+pub fn expand_spintax(text: &str, rng: &mut SpinRng) -> String {
+	let mut result = String::with_capacity(text.len());
+	// Offsets in `result` of the '{' not closed yet
+	let mut open: Vec<usize> = Vec::new();
+
+	for ch in text.chars() {
+		match ch {
+			'{' => {
+				open.push(result.len());
+				result.push(ch);
+			}
+			'}' => match open.pop() {
+				Some(start) if result[start + 1..].contains('|') => {
+					let options: Vec<&str> = result[start + 1..].split('|').collect();
+					let choice = options[rng.pick(options.len())].to_string();
+					result.truncate(start);
+					result.push_str(&choice);
+				}
+				_ => result.push(ch),
+			},
+			_ => result.push(ch),
+		}
+	}
+
+	result
+}
+
+fn shingles(text: &str) -> HashSet<String> {
+	let words: Vec<String> = text
+		.split(|c: char| !c.is_alphanumeric())
+		.filter(|w| !w.is_empty())
+		.map(|w| w.to_lowercase())
+		.collect();
+
+	if words.len() < 2 {
+		return words.into_iter().collect();
+	}
+	words.windows(2).map(|w| w.join(" ")).collect()
+}
+
+// Jaccard similarity of word pairs, 1.0 for identical texts
+pub fn text_similarity(a: &str, b: &str) -> f64 {
+	let (a, b) = (shingles(a), shingles(b));
+	if a.is_empty() && b.is_empty() {
+		return 1.0;
+	}
+	let common = a.intersection(&b).count();
+	common as f64 / (a.len() + b.len() - common) as f64
+}
+
+// A generated ad with the line of the variable row it came from
+#[derive(Debug, Clone)]
+pub struct AdVariant {
+	pub line: usize,
+	pub fields: HashMap<String, String>,
+}
+
+// Expand the template once per row. Rows whose Title + Description are too close
+// to an already accepted variant are rejected.
+pub fn generate_variants(
+	template_fields: &HashMap<String, String>,
+	rows: &[(usize, HashMap<String, String>)],
+	similarity_threshold: f64,
+	seed: u64,
+) -> (Vec<AdVariant>, Vec<ImportRowError>) {
+	let mut variants: Vec<AdVariant> = Vec::new();
+	let mut errors = Vec::new();
+
+	for (line, vars) in rows {
+		let mut rng = SpinRng::new(seed.wrapping_add(*line as u64));
+		let mut fields = HashMap::new();
+
+		for (tag, template) in template_fields {
+			let mut value = expand_placeholders(template, vars);
+			if SPINTAX_TAGS.contains(&tag.as_str()) {
+				value = expand_spintax(&value, &mut rng);
+			}
+			if !value.trim().is_empty() {
+				fields.insert(tag.clone(), value.trim().to_string());
+			}
+		}
+
+		let text = variant_text(&fields);
+		if let Some((similar, similarity)) = variants
+			.iter()
+			.map(|v| (v, text_similarity(&text, &variant_text(&v.fields))))
+			.find(|(_, similarity)| *similarity >= similarity_threshold)
+		{
+			errors.push(ImportRowError {
+				line: *line,
+				ad_id: None,
+				tag: None,
+				message: format!(
+					"Variant is {:.0}% similar to the one from row {}",
+					similarity * 100.0,
+					similar.line
+				),
+			});
+			continue;
+		}
+
+		variants.push(AdVariant {
+			line: *line,
+			fields,
+		});
+	}
+
+	(variants, errors)
+}
+
+fn variant_text(fields: &HashMap<String, String>) -> String {
+	SPINTAX_TAGS
+		.iter()
+		.filter_map(|tag| fields.get(*tag))
+		.cloned()
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+		pairs
+			.iter()
+			.map(|(k, v)| (k.to_string(), v.to_string()))
+			.collect()
+	}
+
+	#[test]
+	fn placeholders_are_filled_case_insensitively() {
+		let vars = vars(&[("city", "Москва"), ("Model", "X5")]);
+		assert_eq!(
+			expand_placeholders("{Model} в городе { CITY }", &vars),
+			"X5 в городе Москва"
+		);
+	}
+
+	#[test]
+	fn other_braces_pass_through() {
+		let vars = vars(&[("city", "Казань")]);
+		let text = r#"{"a": 1} {unknown} {a|b} {city} { } }{"#;
+		assert_eq!(
+			expand_placeholders(text, &vars),
+			r#"{"a": 1} {unknown} {a|b} Казань { } }{"#
+		);
+	}
+
+	#[test]
+	fn spintax_picks_one_option_per_group() {
+		let mut rng = SpinRng::new(7);
+		let text = expand_spintax("{Купить|Продам} {синий|{красный|зелёный}} диван", &mut rng);
+		let words: Vec<&str> = text.split(' ').collect();
+		assert!(["Купить", "Продам"].contains(&words[0]));
+		assert!(["синий", "красный", "зелёный"].contains(&words[1]));
+		assert_eq!(words[2], "диван");
+	}
+
+	#[test]
+	fn stray_braces_do_not_stop_spintax() {
+		let mut rng = SpinRng::new(1);
+		let text = expand_spintax(r#"} {"k": 1} {a|a} { {b|b}"#, &mut rng);
+		assert_eq!(text, r#"} {"k": 1} a { b"#);
+	}
+
+	#[test]
+	fn same_seed_gives_same_text() {
+		let template = "{a|b|c|d} {e|f|g|h} {i|j|k|l}";
+		let first = expand_spintax(template, &mut SpinRng::new(42));
+		let second = expand_spintax(template, &mut SpinRng::new(42));
+		assert_eq!(first, second);
+	}
+
+	#[test]
+	fn similar_variants_are_rejected() {
+		let template = vars(&[
+			("Title", "Диван {model}"),
+			("Description", "Диван {model} в наличии"),
+		]);
+		let rows = vec![
+			(2, vars(&[("model", "Лофт")])),
+			(3, vars(&[("model", "Лофт")])),
+			(4, vars(&[("model", "Честер угловой большой")])),
+		];
+		let (variants, errors) =
+			generate_variants(&template, &rows, DEFAULT_SIMILARITY_THRESHOLD, 1);
+
+		let lines: Vec<usize> = variants.iter().map(|v| v.line).collect();
+		assert_eq!(lines, vec![2, 4]);
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].line, 3);
+		assert_eq!(text_similarity("a b c", "a b c"), 1.0);
+	}
+}
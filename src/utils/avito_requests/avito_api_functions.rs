@@ -1,3 +1,4 @@
+use crate::models::ApiError;
 use reqwest::header::{self, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
 use std::env;
@@ -110,3 +111,183 @@ pub async fn get_client_id_from_avito(
 
 	Ok(profile_data.id)
 }
+
+// Headers for the autoload docs API; the token comes from the client, so it may not fit a header
+fn docs_headers(avito_token: &str) -> Result<HeaderMap, ApiError> {
+	let mut headers = HeaderMap::new();
+	headers.insert(
+		header::AUTHORIZATION,
+		format!("Bearer {}", avito_token)
+			.parse()
+			.map_err(|_| ApiError::Other("Invalid Avito token".to_string()))?,
+	);
+	headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+	Ok(headers)
+}
+
+// Function to get the list of mandatory tags for an autoload category from Avito API
+pub async fn get_category_required_tags(
+	avito_token: &str,
+	avito_slug: &str,
+) -> Result<Vec<String>, ApiError> {
+	let url = env::var("AVITO_BASE_URL")
+		.map_err(|e| ApiError::Other(format!("AVITO_BASE_URL is not set: {}", e)))?;
+	let headers = docs_headers(avito_token)?;
+
+	let api_url = format!("{}/autoload/v1/user-docs/node/{}/fields", url, avito_slug);
+
+	// Make request
+	let response = reqwest::Client::builder()
+		.danger_accept_invalid_certs(true)
+		.build()?
+		.get(&api_url)
+		.headers(headers)
+		.send()
+		.await?;
+
+	// Check response status
+	if !response.status().is_success() {
+		let status_code = response.status().as_u16();
+		let error_body = response.text().await?;
+		return Err(ApiError::AvitoApiError(status_code, error_body));
+	}
+
+	// Parse response
+	let response_text = response.text().await?;
+	let fields_data: serde_json::Value = serde_json::from_str(&response_text)?;
+
+	Ok(extract_required_tags(&fields_data))
+}
+
+// A field is mandatory when the field itself or any of its content items is marked "required"
+pub fn extract_required_tags(fields_data: &serde_json::Value) -> Vec<String> {
+	let is_required = |node: &serde_json::Value| -> bool {
+		node.get("required")
+			.and_then(|r| r.as_bool())
+			.unwrap_or(false)
+			|| node
+				.get("content")
+				.and_then(|c| c.as_array())
+				.map(|items| {
+					items.iter().any(|item| {
+						item.get("required")
+							.and_then(|r| r.as_bool())
+							.unwrap_or(false)
+					})
+				})
+				.unwrap_or(false)
+	};
+
+	let mut tags = Vec::new();
+	if let Some(fields) = fields_data.get("fields").and_then(|f| f.as_array()) {
+		for field in fields {
+			if is_required(field) {
+				if let Some(tag) = field.get("tag").and_then(|t| t.as_str()) {
+					tags.push(tag.to_string());
+				}
+			}
+		}
+	}
+	tags
+}
+
+// Function to get the names of all autoload categories from the Avito docs tree
+pub async fn get_category_names(
+	avito_token: &str,
+) -> Result<std::collections::HashSet<String>, ApiError> {
+	let url = env::var("AVITO_BASE_URL")
+		.map_err(|e| ApiError::Other(format!("AVITO_BASE_URL is not set: {}", e)))?;
+	let headers = docs_headers(avito_token)?;
+
+	let api_url = format!("{}/autoload/v1/user-docs/tree", url);
+
+	// Make request
+	let response = reqwest::Client::builder()
+		.danger_accept_invalid_certs(true)
+		.build()?
+		.get(&api_url)
+		.headers(headers)
+		.send()
+		.await?;
+
+	// Check response status
+	if !response.status().is_success() {
+		let status_code = response.status().as_u16();
+		let error_body = response.text().await?;
+		return Err(ApiError::AvitoApiError(status_code, error_body));
+	}
+
+	// Parse response
+	let response_text = response.text().await?;
+	let tree_data: serde_json::Value = serde_json::from_str(&response_text)?;
+
+	let mut names = std::collections::HashSet::new();
+	extract_category_names(&tree_data, &mut names);
+	Ok(names)
+}
+
+// Collect the names of category nodes: the top-level "categories" list and the
+// "nested" children of each node. Field descriptions elsewhere in the tree also
+// carry names and titles, so the tree is not searched blindly.
+pub fn extract_category_names(
+	tree: &serde_json::Value,
+	names: &mut std::collections::HashSet<String>,
+) {
+	let nodes = if tree.is_array() {
+		Some(tree)
+	} else {
+		tree.get("categories")
+	};
+	for node in nodes.and_then(|n| n.as_array()).into_iter().flatten() {
+		collect_category_node(node, names);
+	}
+}
+
+fn collect_category_node(node: &serde_json::Value, names: &mut std::collections::HashSet<String>) {
+	if let Some(name) = node
+		.get("name")
+		.or_else(|| node.get("title"))
+		.and_then(|n| n.as_str())
+	{
+		if !name.trim().is_empty() {
+			names.insert(name.trim().to_string());
+		}
+	}
+	for child in node
+		.get("nested")
+		.and_then(|n| n.as_array())
+		.into_iter()
+		.flatten()
+	{
+		collect_category_node(child, names);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn only_category_nodes_are_collected() {
+		let tree = json!({
+			"categories": [{
+				"name": "Транспорт",
+				"nested": [
+					{ "name": "Автомобили", "fields": [{ "name": "Make", "title": "Марка" }] },
+					{ "name": "Мотоциклы", "nested": [{ "name": "Мопеды" }] }
+				]
+			}],
+			"meta": { "title": "Autoload docs" }
+		});
+		let mut names = std::collections::HashSet::new();
+		extract_category_names(&tree, &mut names);
+
+		let mut names: Vec<String> = names.into_iter().collect();
+		names.sort();
+		assert_eq!(
+			names,
+			vec!["Автомобили", "Мопеды", "Мотоциклы", "Транспорт"]
+		);
+	}
+}
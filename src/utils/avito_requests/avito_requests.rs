@@ -11,5 +11,8 @@ pub fn filter_add_avito_request_record(request: &AvitoRequest) -> FilteredAvitoR
 		radius: request.radius.clone(),
 		district: request.district.clone(),
 		created_ts: request.created_ts,
+		status: request.status.clone(),
+		finished_ts: request.finished_ts,
+		ads_count: request.ads_count,
 	}
 }
@@ -0,0 +1,142 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::models::{CrawlProgress, CrawlStatus};
+
+// Crawler state names mapped onto job statuses
+fn crawl_status_from_str(value: &str) -> Option<CrawlStatus> {
+	match value.trim().to_lowercase().as_str() {
+		"queued" | "pending" => Some(CrawlStatus::Queued),
+		"started" | "running" | "in_progress" | "progress" | "processing" => {
+			Some(CrawlStatus::Running)
+		}
+		"done" | "completed" | "finished" | "success" => Some(CrawlStatus::Done),
+		"failed" | "error" => Some(CrawlStatus::Failed),
+		"cancelled" | "canceled" => Some(CrawlStatus::Cancelled),
+		_ => None,
+	}
+}
+
+// First of `keys` holding a number or a numeric string, clamped to 0..=i32::MAX
+fn counter(obj: &serde_json::Map<String, Value>, keys: &[&str]) -> Option<i32> {
+	keys.iter()
+		.find_map(|key| match obj.get(*key)? {
+			Value::Number(n) => n.as_i64(),
+			Value::String(s) => s.trim().parse::<i64>().ok(),
+			_ => None,
+		})
+		.map(|n| n.clamp(0, i32::MAX as i64) as i32)
+}
+
+// Job update from a `progress.avito.*` message; None when it names no request.
+// Messages with counters but no known state count as running.
+pub fn parse_crawl_progress(message: &Value) -> Option<CrawlProgress> {
+	let obj = message.as_object()?;
+	let request_id = obj
+		.get("request_id")
+		.or_else(|| {
+			obj.get("request_data")
+				.and_then(|data| data.get("request_id"))
+		})
+		.and_then(Value::as_str)
+		.and_then(|id| Uuid::parse_str(id).ok())?;
+
+	let status = ["status", "state", "stage", "event"]
+		.iter()
+		.filter_map(|key| obj.get(*key).and_then(Value::as_str))
+		.find_map(crawl_status_from_str)
+		.unwrap_or(CrawlStatus::Running);
+
+	let error = match status {
+		CrawlStatus::Failed => ["error", "message"]
+			.iter()
+			.filter_map(|key| obj.get(*key).and_then(Value::as_str))
+			.map(str::to_string)
+			.next(),
+		_ => None,
+	};
+
+	Some(CrawlProgress {
+		request_id,
+		status,
+		progress_current: counter(obj, &["progress_current", "current", "processed"]),
+		progress_total: counter(obj, &["progress_total", "total"]),
+		ads_count: counter(obj, &["ads_count", "ads_saved", "saved"]),
+		error,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	const REQUEST_ID: &str = "6f1c2b8e-3d4a-4b5c-9e8f-0a1b2c3d4e5f";
+
+	#[test]
+	fn request_id_is_read_from_request_data() {
+		let progress = parse_crawl_progress(&json!({
+			"request_data": {"request_id": REQUEST_ID},
+			"stage": "processing",
+			"current": "12",
+			"total": 40
+		}))
+		.unwrap();
+		assert_eq!(progress.request_id.to_string(), REQUEST_ID);
+		assert_eq!(progress.status, CrawlStatus::Running);
+		assert_eq!(progress.progress_current, Some(12));
+		assert_eq!(progress.progress_total, Some(40));
+		assert_eq!(progress.ads_count, None);
+	}
+
+	#[test]
+	fn failures_keep_their_message() {
+		let progress = parse_crawl_progress(&json!({
+			"request_id": REQUEST_ID,
+			"status": "Error",
+			"message": "captcha",
+			"saved": -3
+		}))
+		.unwrap();
+		assert_eq!(progress.status, CrawlStatus::Failed);
+		assert_eq!(progress.error.as_deref(), Some("captcha"));
+		assert_eq!(progress.ads_count, Some(0));
+
+		let progress = parse_crawl_progress(&json!({
+			"request_id": REQUEST_ID,
+			"status": "failed",
+			"saved": "-3"
+		}))
+		.unwrap();
+		assert_eq!(progress.error, None);
+		assert_eq!(progress.ads_count, Some(0));
+	}
+
+	#[test]
+	fn first_known_state_is_used() {
+		let progress = parse_crawl_progress(&json!({
+			"request_id": REQUEST_ID,
+			"status": "warming_up",
+			"event": "finished",
+			"message": "ignored"
+		}))
+		.unwrap();
+		assert_eq!(progress.status, CrawlStatus::Done);
+		assert_eq!(progress.error, None);
+	}
+
+	#[test]
+	fn counters_alone_mean_running() {
+		let progress =
+			parse_crawl_progress(&json!({"request_id": REQUEST_ID, "ads_saved": 5})).unwrap();
+		assert_eq!(progress.status, CrawlStatus::Running);
+		assert_eq!(progress.ads_count, Some(5));
+	}
+
+	#[test]
+	fn messages_without_a_request_are_ignored() {
+		assert!(parse_crawl_progress(&json!({"status": "done"})).is_none());
+		assert!(parse_crawl_progress(&json!({"request_id": "not-a-uuid"})).is_none());
+		assert!(parse_crawl_progress(&json!(["done"])).is_none());
+	}
+}
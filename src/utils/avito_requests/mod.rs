@@ -1,5 +1,7 @@
 pub mod avito_api_functions;
 pub mod avito_requests;
+pub mod crawl_progress;
 
 pub use self::avito_api_functions::*;
 pub use self::avito_requests::*;
+pub use self::crawl_progress::*;
@@ -0,0 +1,235 @@
+use crate::models::{BulkEditFilter, BulkEditOperation, FieldChange};
+use std::collections::{BTreeSet, HashMap};
+
+// Prices may come with spaces or a decimal comma: "12 500,00"
+pub fn parse_price(value: &str) -> Option<f64> {
+	value
+		.chars()
+		.filter(|c| !c.is_whitespace())
+		.collect::<String>()
+		.replace(',', ".")
+		.parse::<f64>()
+		.ok()
+}
+
+pub fn matches_filter(
+	fields: &HashMap<String, String>,
+	status: &str,
+	filter: &BulkEditFilter,
+) -> bool {
+	if let Some(category) = &filter.category {
+		if fields.get("Category") != Some(category) {
+			return false;
+		}
+	}
+	if let Some(expected) = &filter.status {
+		if status != expected {
+			return false;
+		}
+	}
+	if filter
+		.tags
+		.iter()
+		.any(|(tag, value)| fields.get(tag) != Some(value))
+	{
+		return false;
+	}
+	if filter.price_min.is_some() || filter.price_max.is_some() {
+		let price = match fields.get("Price").and_then(|p| parse_price(p)) {
+			Some(price) => price,
+			None => return false,
+		};
+		if filter.price_min.map(|min| price < min).unwrap_or(false)
+			|| filter.price_max.map(|max| price > max).unwrap_or(false)
+		{
+			return false;
+		}
+	}
+	true
+}
+
+// Case-insensitive literal replace that keeps the rest of the text untouched
+fn replace_ignore_case(text: &str, find: &str, replace: &str) -> String {
+	let lower_text = text.to_lowercase();
+	let lower_find = find.to_lowercase();
+	// Lowercasing can change byte lengths for some characters, fall back to exact match then
+	if lower_text.len() != text.len() || lower_find.len() != find.len() {
+		return text.replace(find, replace);
+	}
+
+	let mut result = String::with_capacity(text.len());
+	let mut last = 0;
+	for (start, _) in lower_text.match_indices(&lower_find) {
+		result.push_str(&text[last..start]);
+		result.push_str(replace);
+		last = start + find.len();
+	}
+	result.push_str(&text[last..]);
+	result
+}
+
+// Apply operations in order and return the new field set
+pub fn apply_operations(
+	original: &HashMap<String, String>,
+	operations: &[BulkEditOperation],
+) -> HashMap<String, String> {
+	let mut fields = original.clone();
+
+	for operation in operations {
+		match operation {
+			BulkEditOperation::Set { tag, value } => {
+				fields.insert(tag.clone(), value.clone());
+			}
+			BulkEditOperation::Delete { tag } => {
+				fields.remove(tag);
+			}
+			BulkEditOperation::Replace {
+				find,
+				replace,
+				tags,
+				ignore_case,
+			} => {
+				if find.is_empty() {
+					continue;
+				}
+				let tags = tags
+					.clone()
+					.unwrap_or_else(|| vec!["Title".to_string(), "Description".to_string()]);
+				for tag in tags {
+					if let Some(value) = fields.get_mut(&tag) {
+						*value = if *ignore_case {
+							replace_ignore_case(value, find, replace)
+						} else {
+							value.replace(find.as_str(), replace)
+						};
+					}
+				}
+			}
+			BulkEditOperation::AppendDescription { text, separator } => {
+				let separator = separator.clone().unwrap_or_else(|| "\n\n".to_string());
+				let description = fields.entry("Description".to_string()).or_default();
+				if !description.is_empty() {
+					description.push_str(&separator);
+				}
+				description.push_str(text);
+			}
+			BulkEditOperation::MultiplyPrice { factor, round_to } => {
+				if let Some(price) = fields.get("Price").and_then(|p| parse_price(p)) {
+					let step = round_to.filter(|step| *step > 0).unwrap_or(1) as f64;
+					let new_price = ((price * factor) / step).round() * step;
+					fields.insert("Price".to_string(), (new_price as i64).to_string());
+				}
+			}
+		}
+	}
+
+	// Tags the operations emptied are removed; values that were empty before stay as they are
+	fields.retain(|tag, value| !value.trim().is_empty() || original.get(tag) == Some(value));
+	fields
+}
+
+// Per-tag differences between two field sets, sorted by tag
+pub fn diff_fields(
+	old: &HashMap<String, String>,
+	new: &HashMap<String, String>,
+) -> Vec<FieldChange> {
+	let tags: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+	tags.into_iter()
+		.filter(|tag| old.get(*tag) != new.get(*tag))
+		.map(|tag| FieldChange {
+			tag: tag.clone(),
+			old: old.get(tag).cloned(),
+			new: new.get(tag).cloned(),
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+		pairs
+			.iter()
+			.map(|(k, v)| (k.to_string(), v.to_string()))
+			.collect()
+	}
+
+	#[test]
+	fn prices_with_spaces_and_commas_are_parsed() {
+		assert_eq!(parse_price("12 500,50"), Some(12500.5));
+		assert_eq!(parse_price("договорная"), None);
+	}
+
+	#[test]
+	fn filter_checks_category_tags_and_price() {
+		let ad = fields(&[
+			("Category", "Мебель"),
+			("Condition", "Новое"),
+			("Price", "5 000"),
+		]);
+		let mut filter = BulkEditFilter {
+			category: Some("Мебель".to_string()),
+			price_min: Some(1000.0),
+			price_max: Some(5000.0),
+			..BulkEditFilter::default()
+		};
+		assert!(matches_filter(&ad, "active", &filter));
+
+		filter
+			.tags
+			.insert("Condition".to_string(), "Б/у".to_string());
+		assert!(!matches_filter(&ad, "active", &filter));
+	}
+
+	#[test]
+	fn operations_apply_in_order() {
+		let ad = fields(&[
+			("Title", "Диван Лофт"),
+			("Description", "Новый ДИВАН"),
+			("Price", "10 000"),
+		]);
+		let operations = vec![
+			BulkEditOperation::Replace {
+				find: "диван".to_string(),
+				replace: "Софа".to_string(),
+				tags: None,
+				ignore_case: true,
+			},
+			BulkEditOperation::AppendDescription {
+				text: "Доставка".to_string(),
+				separator: Some(". ".to_string()),
+			},
+			BulkEditOperation::MultiplyPrice {
+				factor: 1.07,
+				round_to: Some(100),
+			},
+		];
+		let result = apply_operations(&ad, &operations);
+		assert_eq!(result["Title"], "Софа Лофт");
+		assert_eq!(result["Description"], "Новый Софа. Доставка");
+		assert_eq!(result["Price"], "10700");
+	}
+
+	#[test]
+	fn only_tags_emptied_by_operations_are_dropped() {
+		let ad = fields(&[("Title", "Диван"), ("Color", ""), ("Material", "Кожа")]);
+		let operations = vec![BulkEditOperation::Set {
+			tag: "Material".to_string(),
+			value: " ".to_string(),
+		}];
+		let result = apply_operations(&ad, &operations);
+
+		assert!(!result.contains_key("Material"));
+		let changes = diff_fields(&ad, &result);
+		assert_eq!(
+			changes,
+			vec![FieldChange {
+				tag: "Material".to_string(),
+				old: Some("Кожа".to_string()),
+				new: None,
+			}]
+		);
+	}
+}
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{
+	AdRecord, CrawlDiff, CrawlDiffAd, CrawlPositionChange, CrawlPriceChange, CrawlPromotionChange,
+};
+
+fn diff_ad(record: &AdRecord, own_ads: &HashSet<String>) -> CrawlDiffAd {
+	CrawlDiffAd {
+		avito_ad_id: record.avito_ad_id.clone(),
+		title: record.title.clone(),
+		link: record.link.clone(),
+		seller_id: record.seller_id.clone(),
+		seller_name: record.seller_name.clone(),
+		position: record.position,
+		price: record.price.clone(),
+		promotion: record.promotion.clone(),
+		own: own_ads.contains(&record.avito_ad_id),
+	}
+}
+
+// Ads of one run by Avito id; an ad listed twice keeps its best position
+fn by_avito_id(records: &[AdRecord]) -> HashMap<&str, &AdRecord> {
+	let mut ads: HashMap<&str, &AdRecord> = HashMap::new();
+	for record in records {
+		let id = record.avito_ad_id.trim();
+		if id.is_empty() {
+			continue;
+		}
+		ads.entry(id)
+			.and_modify(|current| {
+				if record.position < current.position {
+					*current = record;
+				}
+			})
+			.or_insert(record);
+	}
+	ads
+}
+
+// Whitespace and case differences in displayed values are not changes
+fn same_text(a: &str, b: &str) -> bool {
+	let normalize = |value: &str| {
+		value
+			.split_whitespace()
+			.collect::<Vec<_>>()
+			.join(" ")
+			.to_lowercase()
+	};
+	normalize(a) == normalize(b)
+}
+
+// Categorised changes between an earlier and a later run. Position changes come biggest jump
+// first; the other lists are ordered by position in the run the ad was last seen in.
+// `own_ads` are the Avito ids of our own ads
+pub fn diff_crawl_runs(
+	from: &[AdRecord],
+	to: &[AdRecord],
+	own_ads: &HashSet<String>,
+	position_jump: i32,
+) -> CrawlDiff {
+	let before = by_avito_id(from);
+	let after = by_avito_id(to);
+	let mut diff = CrawlDiff::default();
+
+	for (id, record) in &after {
+		let Some(old) = before.get(id) else {
+			diff.new_ads.push(diff_ad(record, own_ads));
+			continue;
+		};
+
+		let mut changed = false;
+		if !same_text(&old.price, &record.price) {
+			let change = old
+				.price_value
+				.zip(record.price_value)
+				.map(|(old, new)| new - old);
+			let change_percent = old
+				.price_value
+				.zip(change)
+				.filter(|(old, _)| *old > 0.0)
+				.map(|(old, change)| (change / old * 1000.0).round() / 10.0);
+			// "12 000 ₽" and "12000 ₽" parse the same and are not a change
+			if change != Some(0.0) {
+				diff.price_changes.push(CrawlPriceChange {
+					ad: diff_ad(record, own_ads),
+					old_price: old.price.clone(),
+					new_price: record.price.clone(),
+					change,
+					change_percent,
+				});
+				changed = true;
+			}
+		}
+		if !same_text(&old.promotion, &record.promotion) {
+			diff.promotion_changes.push(CrawlPromotionChange {
+				ad: diff_ad(record, own_ads),
+				old_promotion: old.promotion.clone(),
+				new_promotion: record.promotion.clone(),
+			});
+			changed = true;
+		}
+		let change = old.position - record.position;
+		if change.abs() >= position_jump {
+			diff.position_changes.push(CrawlPositionChange {
+				ad: diff_ad(record, own_ads),
+				old_position: old.position,
+				new_position: record.position,
+				change,
+			});
+			changed = true;
+		}
+		if !changed {
+			diff.unchanged += 1;
+		}
+	}
+
+	for (id, record) in &before {
+		if !after.contains_key(id) {
+			diff.removed_ads.push(diff_ad(record, own_ads));
+		}
+	}
+
+	diff.new_ads.sort_by_key(|ad| ad.position);
+	diff.removed_ads.sort_by_key(|ad| ad.position);
+	diff.price_changes.sort_by_key(|change| change.ad.position);
+	diff.promotion_changes
+		.sort_by_key(|change| change.ad.position);
+	diff.position_changes
+		.sort_by_key(|change| (-change.change.abs(), change.new_position));
+	diff
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::Utc;
+	use uuid::Uuid;
+
+	fn record(avito_ad_id: &str, position: i32, price: &str, promotion: &str) -> AdRecord {
+		let price_value = price
+			.chars()
+			.filter(char::is_ascii_digit)
+			.collect::<String>()
+			.parse()
+			.ok();
+		AdRecord {
+			ad_id: None,
+			my_ad: String::new(),
+			run_date: Utc::now(),
+			city_query: String::new(),
+			search_query: String::new(),
+			position,
+			views: String::new(),
+			views_today: String::new(),
+			promotion: promotion.to_string(),
+			delivery: String::new(),
+			ad_date: String::new(),
+			avito_ad_id: avito_ad_id.to_string(),
+			title: format!("Ad {}", avito_ad_id),
+			price: price.to_string(),
+			link: String::new(),
+			categories: String::new(),
+			seller_id: String::new(),
+			seller_name: String::new(),
+			seller_type: String::new(),
+			register_date: String::new(),
+			answer_time: String::new(),
+			rating: String::new(),
+			reviews_count: String::new(),
+			ads_count: String::new(),
+			closed_ads_count: String::new(),
+			photo_count: String::new(),
+			address: String::new(),
+			description: String::new(),
+			avito_request_id: Uuid::nil(),
+			created_ts: None,
+			views_value: None,
+			views_today_value: None,
+			price_value,
+			rating_value: None,
+			reviews_count_value: None,
+			ads_count_value: None,
+			photo_count_value: None,
+			ad_ts: None,
+		}
+	}
+
+	#[test]
+	fn changes_are_categorised() {
+		let from = vec![
+			record("1", 1, "10 000 ₽", ""),
+			record("2", 2, "5 000 ₽", "XL"),
+			record("3", 3, "7 000 ₽", ""),
+			record("4", 4, "1 000 ₽", ""),
+		];
+		let to = vec![
+			record("2", 1, "5000 ₽", "xl "),
+			record("1", 9, "12 000 ₽", ""),
+			record("4", 3, "1 000 ₽", "Premium"),
+			record("5", 2, "3 000 ₽", ""),
+		];
+		let own_ads: HashSet<String> = ["1".to_string()].into_iter().collect();
+		let diff = diff_crawl_runs(&from, &to, &own_ads, 5);
+
+		let ids = |ads: &[CrawlDiffAd]| -> Vec<String> {
+			ads.iter().map(|ad| ad.avito_ad_id.clone()).collect()
+		};
+		assert_eq!(ids(&diff.new_ads), vec!["5"]);
+		assert_eq!(ids(&diff.removed_ads), vec!["3"]);
+
+		assert_eq!(diff.price_changes.len(), 1);
+		let price = &diff.price_changes[0];
+		assert_eq!(price.ad.avito_ad_id, "1");
+		assert!(price.ad.own);
+		assert_eq!(price.change, Some(2000.0));
+		assert_eq!(price.change_percent, Some(20.0));
+
+		assert_eq!(diff.promotion_changes.len(), 1);
+		assert_eq!(diff.promotion_changes[0].new_promotion, "Premium");
+
+		assert_eq!(diff.position_changes.len(), 1);
+		assert_eq!(diff.position_changes[0].change, -8);
+
+		// Ad 2 only changed spacing and case
+		assert_eq!(diff.unchanged, 1);
+	}
+
+	#[test]
+	fn repeated_ads_keep_their_best_position() {
+		let from = vec![record("1", 8, "100", ""), record("1", 2, "100", "")];
+		let to = vec![record("1", 3, "100", "")];
+		let diff = diff_crawl_runs(&from, &to, &HashSet::new(), 5);
+		assert!(diff.position_changes.is_empty());
+		assert_eq!(diff.unchanged, 1);
+	}
+}
@@ -0,0 +1,301 @@
+use crate::{
+	models::{ExportFormat, HeaderLang},
+	utils::transliterate::Translit,
+};
+
+// A column of the crawl results export
+#[derive(Debug)]
+pub struct ExportColumn {
+	/// Column of avito_analytics_ads and the key in the ndjson output
+	pub key: &'static str,
+	pub ru: &'static str,
+	pub en: &'static str,
+	/// Written as a number cell in XLSX
+	pub numeric: bool,
+	/// Part of the export when no columns are requested
+	pub default: bool,
+}
+
+impl ExportColumn {
+	pub fn header(&self, lang: HeaderLang) -> &'static str {
+		match lang {
+			HeaderLang::Ru => self.ru,
+			HeaderLang::En => self.en,
+		}
+	}
+}
+
+const fn column(
+	key: &'static str,
+	ru: &'static str,
+	en: &'static str,
+	numeric: bool,
+	default: bool,
+) -> ExportColumn {
+	ExportColumn {
+		key,
+		ru,
+		en,
+		numeric,
+		default,
+	}
+}
+
+// The default columns come first, in the order of the original CSV export
+pub const EXPORT_COLUMNS: &[ExportColumn] = &[
+	column("my_ad", "Мое", "Own", false, true),
+	column("run_date", "Дата прогона", "Run date", false, true),
+	column("city_query", "Город (запрос)", "City (query)", false, true),
+	column(
+		"search_query",
+		"Поиск (запрос)",
+		"Search (query)",
+		false,
+		true,
+	),
+	column("position", "Поз.", "Position", true, true),
+	column("views", "Просмотров", "Views", false, true),
+	column(
+		"views_today",
+		"Просмотров сегодня",
+		"Views today",
+		false,
+		true,
+	),
+	column("promotion", "Продвижение", "Promotion", false, true),
+	column("delivery", "Доставка", "Delivery", false, true),
+	column("ad_date", "Дата объявления", "Ad date", false, true),
+	column("avito_ad_id", "id", "Avito id", false, true),
+	column("title", "Название", "Title", false, true),
+	column("price", "Цена", "Price", false, true),
+	column("link", "Ссылка", "Link", false, true),
+	column("categories", "Категории", "Categories", false, true),
+	column("seller_id", "id Продавца", "Seller id", false, true),
+	column("seller_name", "Продавец", "Seller", false, true),
+	column("seller_type", "Тип продавца", "Seller type", false, true),
+	column(
+		"register_date",
+		"Дата регистрации",
+		"Registered",
+		false,
+		true,
+	),
+	column("answer_time", "Время ответа", "Answer time", false, true),
+	column("rating", "Рейтинг", "Rating", false, true),
+	column("reviews_count", "Кол. отзывов", "Reviews", false, true),
+	column("ads_count", "Кол. объявлений", "Ads", false, true),
+	column(
+		"closed_ads_count",
+		"Кол. закрытых",
+		"Closed ads",
+		false,
+		true,
+	),
+	column("photo_count", "Фото", "Photos", false, true),
+	column("address", "Адрес", "Address", false, true),
+	column("description", "Описание", "Description", false, true),
+	column("ad_id", "id записи", "Row id", false, false),
+	column(
+		"views_value",
+		"Просмотров (число)",
+		"Views (number)",
+		true,
+		false,
+	),
+	column(
+		"views_today_value",
+		"Просмотров сегодня (число)",
+		"Views today (number)",
+		true,
+		false,
+	),
+	column("price_value", "Цена (число)", "Price (number)", true, false),
+	column(
+		"rating_value",
+		"Рейтинг (число)",
+		"Rating (number)",
+		true,
+		false,
+	),
+	column(
+		"reviews_count_value",
+		"Кол. отзывов (число)",
+		"Reviews (number)",
+		true,
+		false,
+	),
+	column(
+		"ads_count_value",
+		"Кол. объявлений (число)",
+		"Ads (number)",
+		true,
+		false,
+	),
+	column(
+		"photo_count_value",
+		"Фото (число)",
+		"Photos (number)",
+		true,
+		false,
+	),
+	column("ad_ts", "Время объявления", "Ad time", false, false),
+];
+
+// Columns in the requested order; the error is a message for the client
+pub fn select_export_columns(spec: Option<&str>) -> Result<Vec<&'static ExportColumn>, String> {
+	let keys: Vec<&str> = spec
+		.unwrap_or("")
+		.split(',')
+		.map(str::trim)
+		.filter(|key| !key.is_empty())
+		.collect();
+	if keys.is_empty() {
+		return Ok(EXPORT_COLUMNS
+			.iter()
+			.filter(|column| column.default)
+			.collect());
+	}
+
+	let mut columns: Vec<&'static ExportColumn> = Vec::with_capacity(keys.len());
+	for key in keys {
+		let column = EXPORT_COLUMNS
+			.iter()
+			.find(|column| column.key.eq_ignore_ascii_case(key))
+			.ok_or_else(|| {
+				format!(
+					"Unknown column '{}', expected one of: {}",
+					key,
+					EXPORT_COLUMNS
+						.iter()
+						.map(|column| column.key)
+						.collect::<Vec<_>>()
+						.join(", ")
+				)
+			})?;
+		if columns.iter().any(|selected| selected.key == column.key) {
+			return Err(format!("Column '{}' is requested twice", column.key));
+		}
+		columns.push(column);
+	}
+	Ok(columns)
+}
+
+// run_date as chrono's to_rfc3339 wrote it in the original CSV export: UTC, with
+// milli- or microseconds only when they are not zero
+const RUN_DATE_TEXT: &str = concat!(
+	"to_char(run_date AT TIME ZONE 'UTC', CASE",
+	r#" WHEN run_date = date_trunc('second', run_date) THEN 'YYYY-MM-DD"T"HH24:MI:SS"+00:00"'"#,
+	r#" WHEN run_date = date_trunc('milliseconds', run_date) THEN 'YYYY-MM-DD"T"HH24:MI:SS.MS"+00:00"'"#,
+	r#" ELSE 'YYYY-MM-DD"T"HH24:MI:SS.US"+00:00"' END)"#
+);
+
+// Every column as text, for CSV and XLSX rows
+pub fn export_text_select(columns: &[&ExportColumn]) -> String {
+	columns
+		.iter()
+		.map(|column| match column.key {
+			"run_date" => RUN_DATE_TEXT.to_string(),
+			key => format!("{}::text", key),
+		})
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+// One JSON object per row with keys in column order; numbers and timestamps keep their types
+pub fn export_json_select(columns: &[&ExportColumn]) -> String {
+	let pairs = columns
+		.iter()
+		.map(|column| format!("'{0}', {0}", column.key))
+		.collect::<Vec<_>>()
+		.join(", ");
+	format!("json_build_object({})::text", pairs)
+}
+
+// Search query as part of a file name: reserved characters replaced, then transliterated
+pub fn file_name_query(search_query: &str) -> String {
+	let sanitized_query = search_query
+		.chars()
+		.map(|c| match c {
+			'/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '\0' => '_',
+			_ => c,
+		})
+		.collect::<String>();
+	Translit::convert(Some(sanitized_query))
+}
+
+// Same naming as the original CSV export: transliterated search query of the first ad and date
+pub fn export_filename(search_query: Option<&str>, date: &str, format: ExportFormat) -> String {
+	match search_query {
+		Some(search_query) => format!(
+			"{}_{}.{}",
+			file_name_query(search_query),
+			date,
+			format.extension()
+		),
+		None => format!("ads_{}.{}", date, format.extension()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn keys(columns: &[&ExportColumn]) -> Vec<&'static str> {
+		columns.iter().map(|column| column.key).collect()
+	}
+
+	#[test]
+	fn select_export_columns_defaults_to_classic_columns() {
+		for spec in [None, Some(""), Some(" , ")] {
+			let columns = select_export_columns(spec).unwrap();
+			assert_eq!(columns.len(), 27);
+			assert_eq!(columns[0].key, "my_ad");
+			assert_eq!(columns[1].key, "run_date");
+			assert_eq!(columns[26].key, "description");
+		}
+	}
+
+	#[test]
+	fn select_export_columns_keeps_requested_order() {
+		let columns = select_export_columns(Some(" price_value, Title ,ad_id")).unwrap();
+		assert_eq!(keys(&columns), vec!["price_value", "title", "ad_id"]);
+	}
+
+	#[test]
+	fn select_export_columns_rejects_unknown_and_repeated_keys() {
+		let err = select_export_columns(Some("title,nope")).unwrap_err();
+		assert!(err.starts_with("Unknown column 'nope', expected one of: my_ad, run_date"));
+
+		let err = select_export_columns(Some("title,TITLE")).unwrap_err();
+		assert_eq!(err, "Column 'title' is requested twice");
+	}
+
+	#[test]
+	fn export_text_select_writes_run_date_as_rfc3339() {
+		let columns = select_export_columns(Some("run_date,price")).unwrap();
+		assert_eq!(
+			export_text_select(&columns),
+			format!("{}, price::text", RUN_DATE_TEXT)
+		);
+	}
+
+	#[test]
+	fn export_filename_uses_search_query_and_date() {
+		assert_eq!(
+			export_filename(Some("Велосипед детский"), "2025-11-23", ExportFormat::Csv),
+			"velosiped-detskii_2025-11-23.csv"
+		);
+		assert_eq!(
+			export_filename(Some("Bike: kids/road?"), "2025-11-23", ExportFormat::Xlsx),
+			"bike_-kids_road__2025-11-23.xlsx"
+		);
+	}
+
+	#[test]
+	fn export_filename_falls_back_without_ads() {
+		assert_eq!(
+			export_filename(None, "2025-11-23", ExportFormat::Ndjson),
+			"ads_2025-11-23.ndjson"
+		);
+	}
+}
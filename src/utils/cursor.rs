@@ -0,0 +1,33 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+// Opaque pagination cursors: hex-encoded JSON, so clients can't rely on the layout
+pub fn encode_cursor<T: Serialize>(position: &T) -> String {
+	hex::encode(serde_json::to_vec(position).unwrap_or_default())
+}
+
+pub fn decode_cursor<T: DeserializeOwned>(cursor: &str) -> Option<T> {
+	let bytes = hex::decode(cursor.trim()).ok()?;
+	serde_json::from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn cursors_round_trip() {
+		let cursor = encode_cursor(&("2025-11-23 12:30:05+03".to_string(), 42));
+		assert!(cursor.chars().all(|c| c.is_ascii_hexdigit()));
+		assert_eq!(
+			decode_cursor::<(String, i64)>(&format!(" {} ", cursor)),
+			Some(("2025-11-23 12:30:05+03".to_string(), 42))
+		);
+	}
+
+	#[test]
+	fn malformed_cursors_are_rejected() {
+		assert_eq!(decode_cursor::<(String, i64)>("not hex"), None);
+		assert_eq!(decode_cursor::<(String, i64)>("7b7d"), None);
+		assert_eq!(decode_cursor::<(String, i64)>(""), None);
+	}
+}
@@ -0,0 +1,146 @@
+use crate::models::{ImportRowError, PositionedXmlAd};
+use std::collections::{HashMap, HashSet};
+
+// Tags every Avito ad must have regardless of category
+pub const BASE_REQUIRED_TAGS: [&str; 4] = ["Id", "Category", "Title", "Description"];
+
+#[derive(Debug, Default)]
+pub struct FeedValidationOptions {
+	// Extra mandatory tags, e.g. from the category schema
+	pub required_tags: Vec<String>,
+	// Category names Avito accepts; the check is skipped when unknown
+	pub known_categories: Option<HashSet<String>>,
+}
+
+// Check parsed ads without touching the DB and report every problem with its line
+pub fn validate_feed_ads(
+	ads: &[PositionedXmlAd],
+	options: &FeedValidationOptions,
+) -> Vec<ImportRowError> {
+	let mut errors = Vec::new();
+	let mut first_seen: HashMap<&str, usize> = HashMap::new();
+
+	let mut required_tags: Vec<String> = BASE_REQUIRED_TAGS.iter().map(|t| t.to_string()).collect();
+	for tag in &options.required_tags {
+		if !required_tags.contains(tag) {
+			required_tags.push(tag.clone());
+		}
+	}
+
+	for positioned in ads {
+		let ad = &positioned.ad;
+		let ad_id = if ad.id.is_empty() {
+			None
+		} else {
+			Some(ad.id.clone())
+		};
+		let line_of = |tag: &str| {
+			positioned
+				.field_lines
+				.get(tag)
+				.copied()
+				.unwrap_or(positioned.line)
+		};
+		let mut report = |line: usize, tag: &str, message: String| {
+			errors.push(ImportRowError {
+				line,
+				ad_id: ad_id.clone(),
+				tag: Some(tag.to_string()),
+				message,
+			})
+		};
+
+		if !ad.id.is_empty() {
+			if let Some(first_line) = first_seen.get(ad.id.as_str()) {
+				report(
+					line_of("Id"),
+					"Id",
+					format!("Duplicate Id {}, first used at line {}", ad.id, first_line),
+				);
+			} else {
+				first_seen.insert(ad.id.as_str(), line_of("Id"));
+			}
+		}
+
+		for tag in &required_tags {
+			// An empty Description gets its own message below
+			if tag == "Description" && positioned.field_lines.contains_key(tag) {
+				continue;
+			}
+			if !ad.fields.contains_key(tag) {
+				report(
+					line_of(tag),
+					tag,
+					format!("Mandatory tag {} is missing", tag),
+				);
+			}
+		}
+
+		if positioned.field_lines.contains_key("Description")
+			&& ad
+				.fields
+				.get("Description")
+				.map(|d| strip_html(d).trim().is_empty())
+				.unwrap_or(true)
+		{
+			report(
+				line_of("Description"),
+				"Description",
+				"Description is empty".to_string(),
+			);
+		}
+
+		if let Some(images) = ad.fields.get("Images") {
+			for (i, url) in images.split(',').enumerate() {
+				if !is_valid_image_url(url.trim()) {
+					let line = positioned
+						.image_lines
+						.get(i)
+						.copied()
+						.unwrap_or_else(|| line_of("Images"));
+					report(
+						line,
+						"Images",
+						format!("Invalid image URL \"{}\"", url.trim()),
+					);
+				}
+			}
+		}
+
+		if let (Some(known), Some(category)) =
+			(&options.known_categories, ad.fields.get("Category"))
+		{
+			if !known.contains(category) {
+				report(
+					line_of("Category"),
+					"Category",
+					format!("Unknown category \"{}\"", category),
+				);
+			}
+		}
+	}
+
+	errors
+}
+
+pub fn is_valid_image_url(value: &str) -> bool {
+	match url::Url::parse(value) {
+		Ok(url) => (url.scheme() == "http" || url.scheme() == "https") && url.host().is_some(),
+		Err(_) => false,
+	}
+}
+
+// Description may be HTML inside CDATA, so tags alone don't count as text
+fn strip_html(value: &str) -> String {
+	let mut text = String::with_capacity(value.len());
+	let mut in_tag = false;
+	for c in value.chars() {
+		match c {
+			'<' => in_tag = true,
+			'>' => in_tag = false,
+			_ if !in_tag => text.push(c),
+			_ => (),
+		}
+	}
+	text.replace("&nbsp;", " ")
+}
@@ -0,0 +1,227 @@
+use crate::utils::images::MAX_IMAGE_SIZE;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use serde::Serialize;
+
+// Avito rejects photos smaller than this on either side
+pub const MIN_IMAGE_WIDTH: u32 = 500;
+pub const MIN_IMAGE_HEIGHT: u32 = 500;
+// Long side / short side; stretched banners get cropped badly in the listing
+pub const MAX_ASPECT_RATIO: f64 = 3.0;
+// Files this small are usually placeholders or icons
+pub const MIN_IMAGE_FILE_SIZE: usize = 5 * 1024;
+// Difference hashes closer than this are treated as the same photo
+pub const DUPLICATE_HASH_DISTANCE: u32 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageQualityIssue {
+	pub code: String,
+	/// "error" blocks publishing, "warning" only lowers the ad's quality
+	pub severity: String,
+	pub message: String,
+}
+
+impl ImageQualityIssue {
+	pub fn error(code: &str, message: String) -> Self {
+		ImageQualityIssue {
+			code: code.to_string(),
+			severity: "error".to_string(),
+			message,
+		}
+	}
+
+	pub fn warning(code: &str, message: String) -> Self {
+		ImageQualityIssue {
+			code: code.to_string(),
+			severity: "warning".to_string(),
+			message,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImageQualityReport {
+	pub format: Option<String>,
+	pub width: Option<u32>,
+	pub height: Option<u32>,
+	pub size_bytes: Option<usize>,
+	pub phash: Option<u64>,
+	pub issues: Vec<ImageQualityIssue>,
+}
+
+impl ImageQualityReport {
+	pub fn unreachable(message: String) -> Self {
+		ImageQualityReport {
+			issues: vec![ImageQualityIssue::error("unreachable", message)],
+			..ImageQualityReport::default()
+		}
+	}
+
+	pub fn too_large(size_bytes: usize) -> Self {
+		ImageQualityReport {
+			size_bytes: Some(size_bytes),
+			issues: vec![file_too_large(size_bytes)],
+			..ImageQualityReport::default()
+		}
+	}
+
+	pub fn status(&self) -> &'static str {
+		if self.issues.iter().any(|i| i.severity == "error") {
+			"error"
+		} else if self.issues.is_empty() {
+			"ok"
+		} else {
+			"warning"
+		}
+	}
+}
+
+fn file_too_large(size_bytes: usize) -> ImageQualityIssue {
+	ImageQualityIssue::error(
+		"file_too_large",
+		format!(
+			"File is {} MB, the limit is {} MB",
+			size_bytes / 1024 / 1024,
+			MAX_IMAGE_SIZE / 1024 / 1024
+		),
+	)
+}
+
+// Decode the image and check size, resolution and proportions
+pub fn check_image_bytes(bytes: &[u8]) -> ImageQualityReport {
+	let mut report = ImageQualityReport {
+		size_bytes: Some(bytes.len()),
+		..ImageQualityReport::default()
+	};
+
+	if bytes.len() > MAX_IMAGE_SIZE {
+		report.issues.push(file_too_large(bytes.len()));
+		return report;
+	}
+	if bytes.len() < MIN_IMAGE_FILE_SIZE {
+		report.issues.push(ImageQualityIssue::warning(
+			"file_too_small",
+			format!("File is only {} bytes", bytes.len()),
+		));
+	}
+
+	report.format = image::guess_format(bytes).ok().map(|format| {
+		match format {
+			ImageFormat::Jpeg => "jpg",
+			ImageFormat::Png => "png",
+			ImageFormat::WebP => "webp",
+			ImageFormat::Gif => "gif",
+			_ => "other",
+		}
+		.to_string()
+	});
+
+	let img = match image::load_from_memory(bytes) {
+		Ok(img) => img,
+		Err(e) => {
+			report.issues.push(ImageQualityIssue::error(
+				"not_decodable",
+				format!("Failed to decode image: {}", e),
+			));
+			return report;
+		}
+	};
+
+	let (width, height) = (img.width(), img.height());
+	report.width = Some(width);
+	report.height = Some(height);
+	report.phash = Some(difference_hash(&img));
+
+	if width < MIN_IMAGE_WIDTH || height < MIN_IMAGE_HEIGHT {
+		report.issues.push(ImageQualityIssue::error(
+			"low_resolution",
+			format!(
+				"Image is {}x{}, minimum is {}x{}",
+				width, height, MIN_IMAGE_WIDTH, MIN_IMAGE_HEIGHT
+			),
+		));
+	}
+
+	let ratio = width.max(height) as f64 / width.min(height).max(1) as f64;
+	if ratio > MAX_ASPECT_RATIO {
+		report.issues.push(ImageQualityIssue::warning(
+			"aspect_ratio",
+			format!(
+				"Aspect ratio {:.1}:1 is more than {}:1",
+				ratio, MAX_ASPECT_RATIO
+			),
+		));
+	}
+
+	report
+}
+
+// 64-bit difference hash: compares neighbouring pixels of a 9x8 grayscale thumbnail,
+// so it survives resizing and recompression of the same photo
+pub fn difference_hash(img: &DynamicImage) -> u64 {
+	let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+	let mut hash = 0u64;
+	for y in 0..8 {
+		for x in 0..8 {
+			hash <<= 1;
+			if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0] {
+				hash |= 1;
+			}
+		}
+	}
+	hash
+}
+
+pub fn hash_distance(a: u64, b: u64) -> u32 {
+	(a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::{ImageBuffer, Rgb};
+
+	fn png(width: u32, height: u32) -> Vec<u8> {
+		let img = ImageBuffer::from_fn(width, height, |x, y| {
+			Rgb([(x % 256) as u8, (y % 256) as u8, 0])
+		});
+		let mut bytes = Vec::new();
+		DynamicImage::ImageRgb8(img)
+			.write_to(&mut std::io::Cursor::new(&mut bytes), ImageFormat::Png)
+			.unwrap();
+		bytes
+	}
+
+	fn codes(report: &ImageQualityReport) -> Vec<&str> {
+		report.issues.iter().map(|i| i.code.as_str()).collect()
+	}
+
+	#[test]
+	fn small_and_stretched_images_are_reported() {
+		let report = check_image_bytes(&png(200, 700));
+		assert_eq!(report.format.as_deref(), Some("png"));
+		assert!(codes(&report).contains(&"low_resolution"));
+		assert!(codes(&report).contains(&"aspect_ratio"));
+		assert_eq!(report.status(), "error");
+	}
+
+	#[test]
+	fn oversized_download_is_file_too_large() {
+		let report = ImageQualityReport::too_large(MAX_IMAGE_SIZE + 1);
+		assert_eq!(codes(&report), vec!["file_too_large"]);
+		assert_eq!(report.size_bytes, Some(MAX_IMAGE_SIZE + 1));
+	}
+
+	#[test]
+	fn garbage_is_not_decodable() {
+		let report = check_image_bytes(&[0u8; 10]);
+		assert!(codes(&report).contains(&"not_decodable"));
+	}
+
+	#[test]
+	fn hash_distance_counts_differing_bits() {
+		assert_eq!(hash_distance(0b1011, 0b0001), 2);
+		assert_eq!(hash_distance(u64::MAX, u64::MAX), 0);
+	}
+}
@@ -0,0 +1,157 @@
+use crate::models::ApiError;
+use image::{ImageFormat, ImageReader};
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+// Larger files are rejected by Avito anyway
+pub const MAX_IMAGE_SIZE: usize = 25 * 1024 * 1024;
+
+// Content hash, format and dimensions of a downloaded image
+#[derive(Debug, Clone)]
+pub struct ImageInfo {
+	pub content_hash: String,
+	pub format: String,
+	pub width: u32,
+	pub height: u32,
+	pub size_bytes: usize,
+}
+
+impl ImageInfo {
+	// Mirrored files are named by content, so the same picture is stored once
+	pub fn file_name(&self) -> String {
+		format!("{}.{}", self.content_hash, self.format)
+	}
+}
+
+// Images field holds comma-separated URLs
+pub fn split_image_urls(images: &str) -> Vec<String> {
+	images
+		.split(',')
+		.map(|url| url.trim().to_string())
+		.filter(|url| !url.is_empty())
+		.collect()
+}
+
+pub fn content_hash(bytes: &[u8]) -> String {
+	hex::encode(Sha256::digest(bytes))
+}
+
+// Detect format and read dimensions from the image header without decoding pixels
+pub fn inspect_image(bytes: &[u8]) -> Result<ImageInfo, ApiError> {
+	let reader = ImageReader::new(Cursor::new(bytes))
+		.with_guessed_format()
+		.map_err(|e| ApiError::Other(format!("Failed to read image: {}", e)))?;
+
+	let format = match reader.format() {
+		Some(ImageFormat::Jpeg) => "jpg",
+		Some(ImageFormat::Png) => "png",
+		Some(ImageFormat::WebP) => "webp",
+		Some(ImageFormat::Gif) => "gif",
+		_ => return Err(ApiError::Other("Unsupported image format".to_string())),
+	};
+
+	let (width, height) = reader
+		.into_dimensions()
+		.map_err(|e| ApiError::Other(format!("Failed to read image dimensions: {}", e)))?;
+
+	Ok(ImageInfo {
+		content_hash: content_hash(bytes),
+		format: format.to_string(),
+		width,
+		height,
+		size_bytes: bytes.len(),
+	})
+}
+
+// Download failures, with oversized files kept apart so checks can report them as such
+#[derive(Debug)]
+pub enum ImageDownloadError {
+	/// Declared size or bytes read so far, over MAX_IMAGE_SIZE
+	TooLarge(usize),
+	Failed(ApiError),
+}
+
+impl From<reqwest::Error> for ImageDownloadError {
+	fn from(err: reqwest::Error) -> Self {
+		ImageDownloadError::Failed(err.into())
+	}
+}
+
+impl From<ImageDownloadError> for ApiError {
+	fn from(err: ImageDownloadError) -> ApiError {
+		match err {
+			ImageDownloadError::TooLarge(_) => ApiError::Other("Image is too large".to_string()),
+			ImageDownloadError::Failed(e) => e,
+		}
+	}
+}
+
+pub async fn download_image(client: &Client, url: &str) -> Result<Vec<u8>, ImageDownloadError> {
+	let mut response = client.get(url).send().await?;
+
+	if !response.status().is_success() {
+		return Err(ImageDownloadError::Failed(ApiError::Other(format!(
+			"Image request failed with status {}",
+			response.status()
+		))));
+	}
+	if let Some(len) = response
+		.content_length()
+		.filter(|len| *len as usize > MAX_IMAGE_SIZE)
+	{
+		return Err(ImageDownloadError::TooLarge(len as usize));
+	}
+
+	// Content-Length may be missing or wrong, so the body is read in chunks and cut off early
+	let mut bytes: Vec<u8> = Vec::new();
+	while let Some(chunk) = response.chunk().await? {
+		bytes.extend_from_slice(&chunk);
+		if bytes.len() > MAX_IMAGE_SIZE {
+			return Err(ImageDownloadError::TooLarge(bytes.len()));
+		}
+	}
+
+	Ok(bytes)
+}
+
+// Files are spread over subdirectories by the first two hash characters.
+// Returns None for names that are not ours, so a request can't escape the storage dir.
+pub fn image_path(storage_dir: &str, file_name: &str) -> Option<PathBuf> {
+	let (hash, ext) = file_name.split_once('.')?;
+	if hash.len() != 64
+		|| !hash.chars().all(|c| c.is_ascii_hexdigit())
+		|| !["jpg", "png", "webp", "gif"].contains(&ext)
+	{
+		return None;
+	}
+
+	Some(PathBuf::from(storage_dir).join(&hash[..2]).join(file_name))
+}
+
+pub fn save_image(storage_dir: &str, file_name: &str, bytes: &[u8]) -> Result<(), ApiError> {
+	let path = image_path(storage_dir, file_name)
+		.ok_or_else(|| ApiError::Other(format!("Invalid image file name {}", file_name)))?;
+
+	// Same content is already stored
+	if path.exists() {
+		return Ok(());
+	}
+	if let Some(dir) = path.parent() {
+		std::fs::create_dir_all(dir)
+			.map_err(|e| ApiError::Other(format!("Failed to create image directory: {}", e)))?;
+	}
+	std::fs::write(&path, bytes)
+		.map_err(|e| ApiError::Other(format!("Failed to save image: {}", e)))
+}
+
+pub fn image_content_type(file_name: &str) -> &'static str {
+	match file_name.rsplit('.').next() {
+		Some("jpg") => "image/jpeg",
+		Some("png") => "image/png",
+		Some("webp") => "image/webp",
+		Some("gif") => "image/gif",
+		_ => "application/octet-stream",
+	}
+}
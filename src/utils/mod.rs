@@ -1,6 +1,23 @@
+pub mod ad_duplicates;
+pub mod ad_fields;
+pub mod ad_lifecycle;
+pub mod ad_schedule;
+pub mod ad_variants;
 pub mod avito_requests;
+pub mod bulk_edit;
+pub mod crawl_diff;
+pub mod crawl_export;
+pub mod cursor;
 pub mod encryption;
+pub mod feed_validation;
 pub mod filter_user_record;
+pub mod image_quality;
+pub mod images;
+pub mod multipart;
+pub mod position_report;
+pub mod spreadsheet;
 pub mod transliterate;
+pub mod xml_position;
+pub mod yml_feed;
 
 pub use self::filter_user_record::filter_user_record;
@@ -0,0 +1,57 @@
+use crate::models::ApiError;
+use actix_multipart::Multipart;
+use futures::StreamExt;
+use std::collections::HashMap;
+
+// Uploaded files larger than this are rejected
+pub const MAX_UPLOAD_SIZE: usize = 100 * 1024 * 1024;
+
+pub struct UploadedFile {
+	pub file_name: String,
+	pub bytes: Vec<u8>,
+}
+
+pub struct MultipartForm {
+	pub fields: HashMap<String, String>,
+	pub file: Option<UploadedFile>,
+}
+
+// Read a multipart form: the "file" part is kept as bytes, every other part as text
+pub async fn read_multipart_form(mut payload: Multipart) -> Result<MultipartForm, ApiError> {
+	let mut fields = HashMap::new();
+	let mut file = None;
+
+	while let Some(item) = payload.next().await {
+		let mut field =
+			item.map_err(|e| ApiError::Other(format!("Failed to read multipart field: {}", e)))?;
+
+		let (field_name, file_name) = match field.content_disposition() {
+			Some(cd) => (
+				cd.get_name().unwrap_or_default().to_string(),
+				cd.get_filename().unwrap_or_default().to_string(),
+			),
+			None => (String::new(), String::new()),
+		};
+
+		let mut bytes = Vec::new();
+		while let Some(chunk) = field.next().await {
+			let chunk =
+				chunk.map_err(|e| ApiError::Other(format!("Failed to read upload: {}", e)))?;
+			if bytes.len() + chunk.len() > MAX_UPLOAD_SIZE {
+				return Err(ApiError::Other("Uploaded file is too large".to_string()));
+			}
+			bytes.extend_from_slice(&chunk);
+		}
+
+		if field_name == "file" {
+			file = Some(UploadedFile { file_name, bytes });
+		} else if !field_name.is_empty() {
+			fields.insert(
+				field_name,
+				String::from_utf8_lossy(&bytes).trim().to_string(),
+			);
+		}
+	}
+
+	Ok(MultipartForm { fields, file })
+}
@@ -0,0 +1,227 @@
+use csv::Writer;
+use std::collections::HashMap;
+use std::io::Cursor;
+use uuid::Uuid;
+
+use crate::models::{OwnAdPosition, OwnAdPositions, PositionMovement, PositionRun};
+
+fn movement(previous: Option<i32>, latest: Option<i32>) -> (Option<i32>, PositionMovement) {
+	match (previous, latest) {
+		(Some(previous), Some(latest)) => {
+			let change = previous - latest;
+			let movement = match change {
+				c if c > 0 => PositionMovement::Up,
+				c if c < 0 => PositionMovement::Down,
+				_ => PositionMovement::Same,
+			};
+			(Some(change), movement)
+		}
+		(None, Some(_)) => (None, PositionMovement::New),
+		(Some(_), None) => (None, PositionMovement::Dropped),
+		(None, None) => (None, PositionMovement::Absent),
+	}
+}
+
+// Positions of each of our ads per run (runs oldest first), ordered by the latest position
+pub fn build_position_report(
+	runs: &[PositionRun],
+	positions: Vec<OwnAdPosition>,
+) -> Vec<OwnAdPositions> {
+	let run_index: HashMap<Uuid, usize> = runs
+		.iter()
+		.enumerate()
+		.map(|(index, run)| (run.request_id, index))
+		.collect();
+
+	let mut ads: HashMap<String, OwnAdPositions> = HashMap::new();
+	for position in positions {
+		let Some(index) = run_index.get(&position.request_id) else {
+			continue;
+		};
+		let ad = ads
+			.entry(position.avito_ad_id.clone())
+			.or_insert_with(|| OwnAdPositions {
+				avito_ad_id: position.avito_ad_id.clone(),
+				title: position.title.clone(),
+				link: position.link.clone(),
+				positions: vec![None; runs.len()],
+				best_position: None,
+				change: None,
+				movement: PositionMovement::Absent,
+			});
+		ad.positions[*index] = Some(position.position);
+		// Title and link of the latest run win
+		if ad.positions[*index + 1..].iter().all(Option::is_none) {
+			ad.title = position.title;
+			ad.link = position.link;
+		}
+	}
+
+	let mut report: Vec<OwnAdPositions> = ads
+		.into_values()
+		.map(|mut ad| {
+			ad.best_position = ad.positions.iter().flatten().min().copied();
+			let latest = ad.positions.last().copied().flatten();
+			let previous = if ad.positions.len() > 1 {
+				ad.positions[ad.positions.len() - 2]
+			} else {
+				None
+			};
+			(ad.change, ad.movement) = movement(previous, latest);
+			ad
+		})
+		.collect();
+
+	report.sort_by(|a, b| {
+		let latest_a = a.positions.last().copied().flatten().unwrap_or(i32::MAX);
+		let latest_b = b.positions.last().copied().flatten().unwrap_or(i32::MAX);
+		latest_a
+			.cmp(&latest_b)
+			.then_with(|| a.best_position.cmp(&b.best_position))
+			.then_with(|| a.avito_ad_id.cmp(&b.avito_ad_id))
+	});
+	report
+}
+
+// One row per ad with a position column per run, then a row per run with the top share
+pub fn position_report_csv(
+	runs: &[PositionRun],
+	report: &[OwnAdPositions],
+	top: i32,
+) -> Result<Vec<u8>, csv::Error> {
+	let mut writer = Writer::from_writer(Cursor::new(Vec::new()));
+
+	let run_dates: Vec<String> = runs
+		.iter()
+		.map(|run| {
+			run.created_ts
+				.map(|ts| ts.format("%Y-%m-%d %H:%M").to_string())
+				.unwrap_or_else(|| run.request_id.to_string())
+		})
+		.collect();
+
+	let mut header = vec![
+		"id".to_string(),
+		"Название".to_string(),
+		"Ссылка".to_string(),
+	];
+	header.extend(run_dates.iter().cloned());
+	header.push("Лучшая поз.".to_string());
+	header.push("Изменение".to_string());
+	writer.write_record(&header)?;
+
+	for ad in report {
+		let mut record = vec![ad.avito_ad_id.clone(), ad.title.clone(), ad.link.clone()];
+		record.extend(
+			ad.positions
+				.iter()
+				.map(|position| position.map(|p| p.to_string()).unwrap_or_default()),
+		);
+		record.push(ad.best_position.map(|p| p.to_string()).unwrap_or_default());
+		record.push(match ad.movement {
+			PositionMovement::New => "новое".to_string(),
+			PositionMovement::Dropped => "выпало".to_string(),
+			PositionMovement::Absent => String::new(),
+			_ => format!("{:+}", ad.change.unwrap_or(0)),
+		});
+		writer.write_record(&record)?;
+	}
+
+	// Share of the top block per run, aligned with the run columns
+	let mut share = vec![String::new(), format!("Доля в топ-{}", top), String::new()];
+	share.extend(runs.iter().map(|run| {
+		run.top_share
+			.map(|share| format!("{:.0}%", share * 100.0))
+			.unwrap_or_default()
+	}));
+	share.push(String::new());
+	share.push(String::new());
+	writer.write_record(&share)?;
+
+	Ok(writer
+		.into_inner()
+		.map_err(|e| e.into_error())?
+		.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn run(id: u128, top_share: Option<f64>) -> PositionRun {
+		PositionRun {
+			request_id: Uuid::from_u128(id),
+			created_ts: None,
+			status: "done".to_string(),
+			ads_total: 50,
+			top_slots: 10,
+			own_top_slots: 0,
+			own_ads: 0,
+			top_share,
+		}
+	}
+
+	fn position(run: u128, ad: &str, title: &str, position: i32) -> OwnAdPosition {
+		OwnAdPosition {
+			request_id: Uuid::from_u128(run),
+			avito_ad_id: ad.to_string(),
+			title: title.to_string(),
+			link: format!("https://avito.ru/{}", ad),
+			position,
+		}
+	}
+
+	#[test]
+	fn positions_are_aligned_with_runs() {
+		let runs = vec![run(1, Some(0.1)), run(2, Some(0.2))];
+		let report = build_position_report(
+			&runs,
+			vec![
+				position(2, "a", "Диван новый", 3),
+				position(1, "a", "Диван", 7),
+				position(1, "b", "Кресло", 2),
+				position(2, "c", "Стол", 5),
+				position(9, "d", "Чужой прогон", 1),
+			],
+		);
+
+		let ids: Vec<&str> = report.iter().map(|ad| ad.avito_ad_id.as_str()).collect();
+		assert_eq!(ids, vec!["a", "c", "b"]);
+
+		assert_eq!(report[0].positions, vec![Some(7), Some(3)]);
+		assert_eq!(report[0].title, "Диван новый");
+		assert_eq!(report[0].best_position, Some(3));
+		assert_eq!(report[0].change, Some(4));
+		assert_eq!(report[0].movement, PositionMovement::Up);
+
+		assert_eq!(report[1].movement, PositionMovement::New);
+		assert_eq!(report[2].movement, PositionMovement::Dropped);
+		assert_eq!(report[2].change, None);
+	}
+
+	#[test]
+	fn movement_compares_the_last_two_runs() {
+		assert_eq!(
+			movement(Some(2), Some(5)),
+			(Some(-3), PositionMovement::Down)
+		);
+		assert_eq!(
+			movement(Some(4), Some(4)),
+			(Some(0), PositionMovement::Same)
+		);
+		assert_eq!(movement(None, None), (None, PositionMovement::Absent));
+	}
+
+	#[test]
+	fn csv_has_a_column_per_run_and_a_share_row() {
+		let runs = vec![run(1, Some(0.25)), run(2, None)];
+		let report = build_position_report(&runs, vec![position(1, "a", "Диван", 7)]);
+		let csv = String::from_utf8(position_report_csv(&runs, &report, 10).unwrap()).unwrap();
+		let lines: Vec<&str> = csv.lines().collect();
+
+		assert_eq!(lines.len(), 3);
+		assert!(lines[0].ends_with("Лучшая поз.,Изменение"));
+		assert_eq!(lines[1], "a,Диван,https://avito.ru/a,7,,7,выпало");
+		assert_eq!(lines[2], ",Доля в топ-10,,25%,,,");
+	}
+}
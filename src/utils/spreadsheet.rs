@@ -0,0 +1,232 @@
+use crate::models::ApiError;
+use calamine::{open_workbook_auto_from_rs, Data, Reader};
+use rust_xlsxwriter::{Format, Workbook};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+// A spreadsheet row keyed by its header, with the 1-based line number in the source file
+#[derive(Debug, Clone)]
+pub struct SheetRow {
+	pub line: usize,
+	pub values: HashMap<String, String>,
+}
+
+// XLSX (and other zip-based workbooks) start with the zip signature
+pub fn is_xlsx(file_name: &str, bytes: &[u8]) -> bool {
+	let name = file_name.to_lowercase();
+	name.ends_with(".xlsx") || name.ends_with(".xls") || bytes.starts_with(b"PK\x03\x04")
+}
+
+// Read rows from a CSV or XLSX file depending on its name/content
+pub fn read_sheet_rows(file_name: &str, bytes: &[u8]) -> Result<Vec<SheetRow>, ApiError> {
+	if is_xlsx(file_name, bytes) {
+		read_xlsx_rows(bytes)
+	} else {
+		read_csv_rows(bytes)
+	}
+}
+
+pub fn read_csv_rows(bytes: &[u8]) -> Result<Vec<SheetRow>, ApiError> {
+	// Excel exports CSV with ';' in the Russian locale, so detect the delimiter from the header
+	let header_line = bytes.split(|b| *b == b'\n').next().unwrap_or_default();
+	let delimiter = if header_line.iter().filter(|b| **b == b';').count()
+		> header_line.iter().filter(|b| **b == b',').count()
+	{
+		b';'
+	} else {
+		b','
+	};
+
+	let mut reader = csv::ReaderBuilder::new()
+		.delimiter(delimiter)
+		.flexible(true)
+		.from_reader(strip_bom(bytes));
+
+	let headers: Vec<String> = reader
+		.headers()
+		.map_err(|e| ApiError::Other(format!("Failed to read CSV header: {}", e)))?
+		.iter()
+		.map(|h| h.trim().to_string())
+		.collect();
+
+	let mut rows = Vec::new();
+	for (i, record) in reader.records().enumerate() {
+		let record = record.map_err(|e| ApiError::Other(format!("CSV parse error: {}", e)))?;
+		let line = record
+			.position()
+			.map(|p| p.line() as usize)
+			.unwrap_or(i + 2);
+
+		let values: HashMap<String, String> = headers
+			.iter()
+			.zip(record.iter())
+			.map(|(h, v)| (h.clone(), v.trim().to_string()))
+			.collect();
+
+		if values.values().all(|v| v.is_empty()) {
+			continue;
+		}
+		rows.push(SheetRow { line, values });
+	}
+
+	Ok(rows)
+}
+
+pub fn read_xlsx_rows(bytes: &[u8]) -> Result<Vec<SheetRow>, ApiError> {
+	let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes.to_vec()))
+		.map_err(|e| ApiError::Other(format!("Failed to open workbook: {}", e)))?;
+
+	// Only the first sheet is imported
+	let range = workbook
+		.worksheet_range_at(0)
+		.ok_or_else(|| ApiError::Other("Workbook has no sheets".to_string()))?
+		.map_err(|e| ApiError::Other(format!("Failed to read sheet: {}", e)))?;
+
+	let mut sheet_rows = range.rows();
+	let headers: Vec<String> = match sheet_rows.next() {
+		Some(header) => header.iter().map(cell_to_string).collect(),
+		None => return Ok(Vec::new()),
+	};
+
+	// Line numbers are 1-based and account for any empty rows above the used range
+	let first_line = range.start().map(|(row, _)| row as usize + 1).unwrap_or(1);
+
+	let mut rows = Vec::new();
+	for (i, row) in sheet_rows.enumerate() {
+		let values: HashMap<String, String> = headers
+			.iter()
+			.zip(row.iter())
+			.map(|(h, c)| (h.clone(), cell_to_string(c)))
+			.collect();
+
+		if values.values().all(|v| v.is_empty()) {
+			continue;
+		}
+		rows.push(SheetRow {
+			line: first_line + i + 1,
+			values,
+		});
+	}
+
+	Ok(rows)
+}
+
+// Write a header row and data rows into an in-memory CSV file
+pub fn write_csv(headers: &[String], rows: &[Vec<String>]) -> Result<Vec<u8>, ApiError> {
+	let mut writer = csv::Writer::from_writer(Cursor::new(Vec::new()));
+
+	writer
+		.write_record(headers)
+		.map_err(|e| ApiError::Other(format!("Failed to write CSV: {}", e)))?;
+	for row in rows {
+		writer
+			.write_record(row)
+			.map_err(|e| ApiError::Other(format!("Failed to write CSV: {}", e)))?;
+	}
+
+	let cursor = writer
+		.into_inner()
+		.map_err(|e| ApiError::Other(format!("Failed to write CSV: {}", e)))?;
+	Ok(cursor.into_inner())
+}
+
+// Write a header row and data rows into an in-memory XLSX workbook with a frozen header
+pub fn write_xlsx(headers: &[String], rows: &[Vec<String>]) -> Result<Vec<u8>, ApiError> {
+	let mut workbook = Workbook::new();
+	let header_format = Format::new().set_bold();
+	let worksheet = workbook.add_worksheet();
+
+	for (col, header) in headers.iter().enumerate() {
+		worksheet
+			.write_string_with_format(0, col as u16, header, &header_format)
+			.map_err(|e| ApiError::Other(format!("Failed to write XLSX: {}", e)))?;
+	}
+	for (i, row) in rows.iter().enumerate() {
+		for (col, value) in row.iter().enumerate() {
+			if value.is_empty() {
+				continue;
+			}
+			worksheet
+				.write_string((i + 1) as u32, col as u16, value)
+				.map_err(|e| ApiError::Other(format!("Failed to write XLSX: {}", e)))?;
+		}
+	}
+	worksheet
+		.set_freeze_panes(1, 0)
+		.map_err(|e| ApiError::Other(format!("Failed to write XLSX: {}", e)))?;
+
+	workbook
+		.save_to_buffer()
+		.map_err(|e| ApiError::Other(format!("Failed to write XLSX: {}", e)))
+}
+
+fn cell_to_string(cell: &Data) -> String {
+	match cell {
+		Data::Empty => String::new(),
+		// Whole numbers come back as floats from Excel, keep them without ".0"
+		Data::Float(f) if f.fract() == 0.0 && f.abs() < 1e15 => format!("{}", *f as i64),
+		_ => cell.to_string().trim().to_string(),
+	}
+}
+
+fn strip_bom(bytes: &[u8]) -> &[u8] {
+	bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn column(rows: &[SheetRow], header: &str) -> Vec<String> {
+		rows.iter().map(|row| row.values[header].clone()).collect()
+	}
+
+	#[test]
+	fn csv_with_bom_and_semicolons() {
+		let csv = "\u{FEFF}Id;Title;Price\n1;Стол, дуб;1 500\n\n;;\n2; Стул ;300\n";
+		let rows = read_sheet_rows("ads.csv", csv.as_bytes()).unwrap();
+		assert_eq!(column(&rows, "Id"), vec!["1", "2"]);
+		assert_eq!(column(&rows, "Title"), vec!["Стол, дуб", "Стул"]);
+		assert_eq!(
+			rows.iter().map(|row| row.line).collect::<Vec<_>>(),
+			vec![2, 5]
+		);
+	}
+
+	#[test]
+	fn csv_with_commas_and_short_rows() {
+		let csv = "Id,Title,Price\n1,\"Стол; дуб\",1500\n2,Стул\n";
+		let rows = read_sheet_rows("ads.csv", csv.as_bytes()).unwrap();
+		assert_eq!(column(&rows, "Title"), vec!["Стол; дуб", "Стул"]);
+		assert_eq!(rows[0].values["Price"], "1500");
+		assert!(!rows[1].values.contains_key("Price"));
+	}
+
+	#[test]
+	fn xlsx_rows_skip_empty_lines() {
+		let headers = vec!["Id".to_string(), "Title".to_string()];
+		let rows = vec![
+			vec!["1".to_string(), "Стол".to_string()],
+			vec![String::new(), String::new()],
+			vec!["2".to_string(), "Стул".to_string()],
+		];
+		let bytes = write_xlsx(&headers, &rows).unwrap();
+
+		// Detected by the zip signature even without the extension
+		let rows = read_sheet_rows("upload", &bytes).unwrap();
+		assert_eq!(column(&rows, "Id"), vec!["1", "2"]);
+		assert_eq!(column(&rows, "Title"), vec!["Стол", "Стул"]);
+		assert_eq!(
+			rows.iter().map(|row| row.line).collect::<Vec<_>>(),
+			vec![2, 4]
+		);
+	}
+
+	#[test]
+	fn whole_numbers_lose_their_fraction() {
+		assert_eq!(cell_to_string(&Data::Float(1500.0)), "1500");
+		assert_eq!(cell_to_string(&Data::Float(2.5)), "2.5");
+		assert_eq!(cell_to_string(&Data::Empty), "");
+		assert_eq!(cell_to_string(&Data::String(" Стол ".to_string())), "Стол");
+	}
+}
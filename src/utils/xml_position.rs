@@ -0,0 +1,57 @@
+// 1-based line number of a byte offset in the source text
+pub fn line_at(source: &str, position: usize) -> usize {
+	let end = position.min(source.len());
+	source.as_bytes()[..end]
+		.iter()
+		.filter(|b| **b == b'\n')
+		.count()
+		+ 1
+}
+
+// Line numbers for offsets that only grow while a reader walks the document,
+// so each byte is counted once instead of rescanning from the start every time
+pub struct LineCounter<'a> {
+	source: &'a [u8],
+	offset: usize,
+	line: usize,
+}
+
+impl<'a> LineCounter<'a> {
+	pub fn new(source: &'a str) -> Self {
+		LineCounter {
+			source: source.as_bytes(),
+			offset: 0,
+			line: 1,
+		}
+	}
+
+	pub fn line_at(&mut self, position: usize) -> usize {
+		let end = position.min(self.source.len());
+		if end < self.offset {
+			// Offset went backwards, fall back to a full scan
+			return line_at(std::str::from_utf8(self.source).unwrap_or_default(), end);
+		}
+		self.line += self.source[self.offset..end]
+			.iter()
+			.filter(|b| **b == b'\n')
+			.count();
+		self.offset = end;
+		self.line
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn counter_matches_full_scan() {
+		let source = "a\nbb\n\nccc\nd";
+		let mut counter = LineCounter::new(source);
+		for position in [0, 1, 2, 5, 6, 9, 10, 100] {
+			assert_eq!(counter.line_at(position), line_at(source, position));
+		}
+		// Going backwards falls back to a full scan
+		assert_eq!(counter.line_at(3), 2);
+	}
+}
@@ -0,0 +1,390 @@
+use crate::models::{ApiError, ImportRowError, XmlAd};
+use crate::utils::xml_position::{line_at, LineCounter};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+// Category node from the <categories> block of a Yandex Market (YML) catalog
+#[derive(Debug, Clone)]
+pub struct YmlCategory {
+	pub id: String,
+	pub parent_id: Option<String>,
+	pub name: String,
+}
+
+// Single <offer> of a YML catalog
+#[derive(Debug, Clone, Default)]
+pub struct YmlOffer {
+	pub id: String,
+	pub line: usize,
+	pub available: bool,
+	pub name: String,
+	pub type_prefix: String,
+	pub vendor: String,
+	pub model: String,
+	pub description: String,
+	pub price: String,
+	pub currency_id: String,
+	pub category_id: String,
+	pub pictures: Vec<String>,
+	pub params: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YmlCategoryMapping {
+	pub category: String,
+	pub goods_type: Option<String>,
+}
+
+// How YML offers are turned into Avito tags
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct YmlMapping {
+	/// Avito Category/GoodsType used when the offer's category is not mapped
+	pub default_category: Option<String>,
+	pub default_goods_type: Option<String>,
+	/// YML category id -> Avito Category/GoodsType (parent categories are used as a fallback)
+	#[serde(default)]
+	pub categories: HashMap<String, YmlCategoryMapping>,
+	/// YML <param name="..."> -> Avito tag
+	#[serde(default)]
+	pub params: HashMap<String, String>,
+	/// Constant tags added to every ad (Address, ContactPhone, ...)
+	#[serde(default)]
+	pub extra_fields: HashMap<String, String>,
+}
+
+fn attribute(e: &BytesStart, key: &[u8]) -> Option<String> {
+	e.attributes()
+		.flatten()
+		.find(|attr| attr.key.as_ref() == key)
+		.and_then(|attr| std::str::from_utf8(&attr.value).ok().map(|v| v.to_string()))
+}
+
+pub fn parse_yml_catalog(
+	xml_data: &str,
+) -> Result<(HashMap<String, YmlCategory>, Vec<YmlOffer>), ApiError> {
+	let mut reader = Reader::from_str(xml_data);
+	let mut lines = LineCounter::new(xml_data);
+	let mut buf = Vec::new();
+	let mut categories = HashMap::new();
+	let mut offers = Vec::new();
+
+	let mut current_offer: Option<YmlOffer> = None;
+	let mut current_category: Option<YmlCategory> = None;
+	let mut current_param: Option<String> = None;
+	let mut current_text = String::new();
+
+	loop {
+		let position = reader.buffer_position() as usize;
+		match reader.read_event_into(&mut buf) {
+			Ok(Event::Start(e)) => {
+				let name = std::str::from_utf8(e.name().as_ref())
+					.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?
+					.to_string();
+
+				match name.as_str() {
+					"offer" => {
+						current_offer = Some(YmlOffer {
+							id: attribute(&e, b"id").unwrap_or_default(),
+							line: lines.line_at(position),
+							available: attribute(&e, b"available")
+								.map(|v| v != "false")
+								.unwrap_or(true),
+							..YmlOffer::default()
+						});
+					}
+					"category" if current_offer.is_none() => {
+						current_category = Some(YmlCategory {
+							id: attribute(&e, b"id").unwrap_or_default(),
+							parent_id: attribute(&e, b"parentId"),
+							name: String::new(),
+						});
+					}
+					"param" => {
+						current_param = attribute(&e, b"name");
+					}
+					_ => (),
+				}
+
+				current_text.clear();
+			}
+			Ok(Event::Text(e)) => {
+				let text = e
+					.decode()
+					.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?;
+				current_text.push_str(&text);
+			}
+			Ok(Event::GeneralRef(e)) => {
+				// Entities like &amp; or &#8470; inside text nodes
+				if let Some(ch) = e
+					.resolve_char_ref()
+					.map_err(|e| ApiError::Other(format!("XML parse error: {}", e)))?
+				{
+					current_text.push(ch);
+				} else {
+					let entity = std::str::from_utf8(e.as_ref())
+						.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?;
+					match entity {
+						"amp" => current_text.push('&'),
+						"lt" => current_text.push('<'),
+						"gt" => current_text.push('>'),
+						"quot" => current_text.push('"'),
+						"apos" => current_text.push('\''),
+						// Unknown entities are kept as written rather than dropped
+						_ => {
+							current_text.push('&');
+							current_text.push_str(entity);
+							current_text.push(';');
+						}
+					}
+				}
+			}
+			Ok(Event::CData(e)) => {
+				let text = std::str::from_utf8(e.as_ref())
+					.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?;
+				current_text.push_str(text);
+			}
+			Ok(Event::End(e)) => {
+				let name = std::str::from_utf8(e.name().as_ref())
+					.map_err(|e| ApiError::Other(format!("UTF-8 error: {}", e)))?
+					.to_string();
+				let text = current_text.trim().to_string();
+
+				if let Some(offer) = &mut current_offer {
+					match name.as_str() {
+						"name" => offer.name = text,
+						"typePrefix" => offer.type_prefix = text,
+						"vendor" => offer.vendor = text,
+						"model" => offer.model = text,
+						"description" => offer.description = text,
+						"price" => offer.price = text,
+						"currencyId" => offer.currency_id = text,
+						"categoryId" => offer.category_id = text,
+						"picture" if !text.is_empty() => offer.pictures.push(text),
+						"param" => {
+							if let Some(param_name) = current_param.take() {
+								offer.params.push((param_name, text));
+							}
+						}
+						"offer" => {
+							if let Some(offer) = current_offer.take() {
+								offers.push(offer);
+							}
+						}
+						_ => (),
+					}
+				} else if name == "category" {
+					if let Some(mut category) = current_category.take() {
+						category.name = text;
+						categories.insert(category.id.clone(), category);
+					}
+				}
+
+				current_text.clear();
+			}
+			Ok(Event::Eof) => break,
+			Err(e) => {
+				return Err(ApiError::Other(format!(
+					"XML parse error at line {}: {}",
+					line_at(xml_data, reader.error_position() as usize),
+					e
+				)))
+			}
+			_ => (),
+		}
+
+		buf.clear();
+	}
+
+	Ok((categories, offers))
+}
+
+// Find the Avito category for a YML category, walking up the parent chain
+fn resolve_category<'a>(
+	category_id: &str,
+	categories: &HashMap<String, YmlCategory>,
+	mapping: &'a YmlMapping,
+) -> Option<&'a YmlCategoryMapping> {
+	let mut current = Some(category_id.to_string());
+	let mut depth = 0;
+
+	while let Some(id) = current {
+		if let Some(found) = mapping.categories.get(&id) {
+			return Some(found);
+		}
+		// Guard against cycles in broken catalogs
+		depth += 1;
+		if depth > 32 {
+			break;
+		}
+		current = categories.get(&id).and_then(|c| c.parent_id.clone());
+	}
+
+	None
+}
+
+// Convert YML offers to Avito ads; offers that can't be mapped are reported per item
+pub fn convert_yml_offers(
+	categories: &HashMap<String, YmlCategory>,
+	offers: &[YmlOffer],
+	mapping: &YmlMapping,
+) -> (Vec<XmlAd>, Vec<ImportRowError>) {
+	let mut ads = Vec::new();
+	let mut errors = Vec::new();
+	let mut seen_ids: HashSet<String> = HashSet::new();
+
+	for offer in offers {
+		let report = |tag: &str, message: String| ImportRowError {
+			line: offer.line,
+			ad_id: Some(offer.id.clone()),
+			tag: Some(tag.to_string()),
+			message,
+		};
+
+		if offer.id.is_empty() {
+			errors.push(report("Id", "Offer has no id".to_string()));
+			continue;
+		}
+		if !offer.available {
+			errors.push(report("Id", "Offer is not available".to_string()));
+			continue;
+		}
+
+		// vendor.model offers have no <name>, build it from typePrefix + vendor + model
+		let title = if offer.name.is_empty() {
+			[&offer.type_prefix, &offer.vendor, &offer.model]
+				.iter()
+				.filter(|part| !part.is_empty())
+				.map(|part| part.as_str())
+				.collect::<Vec<_>>()
+				.join(" ")
+		} else {
+			offer.name.clone()
+		};
+		if title.is_empty() {
+			errors.push(report("Title", "Offer has no name".to_string()));
+			continue;
+		}
+
+		let price = match offer.price.replace(',', ".").parse::<f64>() {
+			Ok(price) if price > 0.0 => price.round() as i64,
+			_ => {
+				errors.push(report(
+					"Price",
+					format!("Invalid price \"{}\"", offer.price),
+				));
+				continue;
+			}
+		};
+
+		let (category, goods_type) = match resolve_category(&offer.category_id, categories, mapping)
+		{
+			Some(found) => (Some(found.category.clone()), found.goods_type.clone()),
+			None => (
+				mapping.default_category.clone(),
+				mapping.default_goods_type.clone(),
+			),
+		};
+		let category = match category {
+			Some(category) => category,
+			None => {
+				let category_name = categories
+					.get(&offer.category_id)
+					.map(|c| c.name.clone())
+					.unwrap_or_else(|| offer.category_id.clone());
+				errors.push(report(
+					"Category",
+					format!("Category \"{}\" is not mapped", category_name),
+				));
+				continue;
+			}
+		};
+
+		let mut fields: HashMap<String, String> = mapping.extra_fields.clone();
+		fields.insert("Id".to_string(), offer.id.clone());
+		fields.insert("Title".to_string(), title.clone());
+		fields.insert(
+			"Description".to_string(),
+			if offer.description.is_empty() {
+				title
+			} else {
+				offer.description.clone()
+			},
+		);
+		fields.insert("Price".to_string(), price.to_string());
+		fields.insert("Category".to_string(), category);
+		if let Some(goods_type) = goods_type {
+			fields.insert("GoodsType".to_string(), goods_type);
+		}
+		if !offer.pictures.is_empty() {
+			fields.insert("Images".to_string(), offer.pictures.join(","));
+		}
+		for (param_name, value) in &offer.params {
+			if let Some(tag) = mapping.params.get(param_name) {
+				if !value.is_empty() {
+					fields.insert(tag.clone(), value.clone());
+				}
+			}
+		}
+
+		if !seen_ids.insert(offer.id.clone()) {
+			errors.push(report("Id", format!("Duplicate Id {}", offer.id)));
+			continue;
+		}
+
+		ads.push(XmlAd {
+			id: offer.id.clone(),
+			fields,
+		});
+	}
+
+	(ads, errors)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn character_references_are_decoded() {
+		let xml = "<yml_catalog><shop><offers>\n\
+			<offer id=\"1\"><name>Tom &amp; Jerry &#8470;5 &#x263A; &copy;</name></offer>\n\
+			</offers></shop></yml_catalog>";
+		let (_, offers) = parse_yml_catalog(xml).unwrap();
+		assert_eq!(offers[0].name, "Tom & Jerry \u{2116}5 \u{263A} &copy;");
+	}
+
+	#[test]
+	fn offers_keep_their_line_numbers() {
+		let xml = "<yml_catalog>\n<shop>\n<offers>\n\
+			<offer id=\"1\"><name>A</name></offer>\n\
+			\n\
+			<offer id=\"2\"><name>B</name></offer>\n\
+			</offers>\n</shop>\n</yml_catalog>";
+		let (_, offers) = parse_yml_catalog(xml).unwrap();
+		let lines: Vec<usize> = offers.iter().map(|offer| offer.line).collect();
+		assert_eq!(lines, vec![4, 6]);
+	}
+
+	#[test]
+	fn repeated_offer_ids_are_reported() {
+		let xml = "<yml_catalog><shop><offers>\n\
+			<offer id=\"7\" available=\"true\"><name>A</name><price>100</price></offer>\n\
+			<offer id=\"7\" available=\"true\"><name>B</name><price>200</price></offer>\n\
+			</offers></shop></yml_catalog>";
+		let (categories, offers) = parse_yml_catalog(xml).unwrap();
+		let mapping = YmlMapping {
+			default_category: Some("Мебель".to_string()),
+			..Default::default()
+		};
+
+		let (ads, errors) = convert_yml_offers(&categories, &offers, &mapping);
+		assert_eq!(ads.len(), 1);
+		assert_eq!(ads[0].fields["Title"], "A");
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].line, 3);
+		assert_eq!(errors[0].ad_id.as_deref(), Some("7"));
+		assert_eq!(errors[0].message, "Duplicate Id 7");
+	}
+}